@@ -5,6 +5,7 @@ use crate::{
     renderer::{vector::Vector2, Color},
     simulator::*,
 };
+use std::collections::HashSet;
 
 use super::*;
 
@@ -27,7 +28,13 @@ impl ActionStack {
             self.update_buttons(&app.imp().undo_button(), &app.imp().redo_button());
 
             info!("Un-doing action {}", self.next);
-            action.unwrap().undo(app);
+            let action = action.unwrap();
+            action.undo(app);
+            if let Action::RenameModule(_, from, to) = action {
+                let (from, to) = (from.clone(), to.clone());
+                self.rename_module(&to, &from);
+            }
+            app.imp().refresh_properties();
         }
     }
 
@@ -38,11 +45,23 @@ impl ActionStack {
 
             info!("Re-doing action {}", self.next - 1);
             action.exec(app);
+            if let Action::RenameModule(_, from, to) = action {
+                let (from, to) = (from.clone(), to.clone());
+                self.rename_module(&from, &to);
+            }
+            app.imp().refresh_properties();
 
             self.update_buttons(&app.imp().undo_button(), &app.imp().redo_button());
         }
     }
 
+    // stored actions refer to modules by name and have to follow when a module gets renamed
+    fn rename_module(&mut self, from: &String, to: &String) {
+        self.actions
+            .iter_mut()
+            .for_each(|action| action.rename_module(from, to));
+    }
+
     fn update_buttons(&self, undo_button: &gtk::Button, redo_button: &gtk::Button) {
         undo_button.set_sensitive(self.next != 0);
         redo_button.set_sensitive(self.actions.get(self.next).is_some());
@@ -54,6 +73,10 @@ impl ActionStack {
         }
 
         action.exec(app);
+        if let Action::RenameModule(_, from, to) = &action {
+            self.rename_module(from, to);
+        }
+        app.imp().refresh_properties();
 
         self.next += 1;
         self.dirty = true;
@@ -85,17 +108,140 @@ pub enum Action {
     NewBlock(PlotProvider, Block),
     PasteBlocks(PlotProvider, Vec<Block>, Vec<Connection>),
     MoveBlock(PlotProvider, BlockID, Vector2<i32>, Vector2<i32>),
-    MoveWaypoint(PlotProvider, SegmentID, Vector2<i32>, Vector2<i32>),
+    MoveWaypoint(PlotProvider, SegmentID, Vector2<i32>, Vector2<i32>, bool),
     NewConnection(PlotProvider, Connection),
     WaypointToConnection(PlotProvider, SegmentID, Segment, BlockID, u8),
     AddSegment(PlotProvider, SegmentID, Segment, Option<Id>),
     ChangeBorderColor(PlotProvider, Color, Vec<BlockID>, Vec<Option<Color>>),
-    DeleteSelection(PlotProvider, Vec<Block>, Vec<Connection>, Vec<Connection>),
+    SetLabel(PlotProvider, BlockID, Option<String>, Option<String>),
+    TransformBlocks(
+        PlotProvider,
+        Transformation,
+        Vec<BlockID>,
+        Vec<(Orientation, Vector2<i32>)>,
+    ),
+    RouteWires(PlotProvider, Vec<BlockID>, Vec<Connection>),
+    ConfigureBlock(
+        PlotProvider,
+        BlockID,
+        Vec<(Parameter, u128)>,
+        Vec<(Parameter, u128)>,
+        Vec<Connection>,
+    ),
+    NewAnnotation(PlotProvider, Annotation),
+    MoveAnnotation(PlotProvider, AnnotationID, Vector2<i32>, Vector2<i32>),
+    EditAnnotation(PlotProvider, AnnotationID, String, String),
+    DeleteSelection(
+        PlotProvider,
+        Vec<Block>,
+        Vec<Connection>,
+        Vec<Connection>,
+        Vec<Annotation>,
+    ),
     CreateModule(ProjectRef, Module),
     DeleteModule(ProjectRef, Module),
+    SetPinNames(
+        ProjectRef,
+        String,
+        (Vec<String>, Vec<String>),
+        (Vec<String>, Vec<String>),
+    ),
+    EditModuleInterface(
+        ProjectRef,
+        String,
+        (u8, u8),
+        (u8, u8),
+        Vec<(PlotDescriptor, Vec<Connection>)>,
+        Option<Shape>,
+    ),
+    RenameModule(ProjectRef, String, String),
+    SetModuleDecoration(ProjectRef, String, Decoration, Decoration),
+    SetModuleShape(ProjectRef, String, Option<Shape>, Option<Shape>),
+}
+
+fn set_module_decoration(
+    app: &Application,
+    project: &ProjectRef,
+    module_name: &String,
+    decoration: &Decoration,
+) -> Option<Decoration> {
+    let mut locked = project.lock().unwrap();
+    let previous = locked.set_module_decoration(module_name, decoration.clone());
+    let module = locked.module(module_name).cloned();
+    drop(locked);
+
+    if let Some(module) = module
+        && let Some(window) = app.imp().window().borrow().as_ref()
+    {
+        window.update_module_in_ui(app, &module);
+    }
+    app.imp().rerender_editor();
+    previous
+}
+
+fn rename_module(app: &Application, project: &ProjectRef, from: &String, to: &String) {
+    let mut locked = project.lock().unwrap();
+    if let Err(err) = locked.rename_module(from, to) {
+        warn!("Could not rename module \"{from}\": {err}");
+        return;
+    }
+
+    let module = locked.module(to).cloned();
+    drop(locked);
+
+    if let Some(module) = module
+        && let Some(window) = app.imp().window().borrow().as_ref()
+    {
+        window.rename_module_in_ui(app, from, &module);
+    }
+    app.imp().rerender_editor();
 }
 
 impl Action {
+    // replaces the name of a renamed module in the plots and modules the action refers to
+    fn rename_module(&mut self, from: &String, to: &String) {
+        let rename = |name: &mut String| {
+            if name == from {
+                *name = to.clone();
+            }
+        };
+
+        match self {
+            Self::NewBlock(plot_provider, ..)
+            | Self::PasteBlocks(plot_provider, ..)
+            | Self::MoveBlock(plot_provider, ..)
+            | Self::MoveWaypoint(plot_provider, ..)
+            | Self::NewConnection(plot_provider, ..)
+            | Self::WaypointToConnection(plot_provider, ..)
+            | Self::AddSegment(plot_provider, ..)
+            | Self::ChangeBorderColor(plot_provider, ..)
+            | Self::SetLabel(plot_provider, ..)
+            | Self::TransformBlocks(plot_provider, ..)
+            | Self::RouteWires(plot_provider, ..)
+            | Self::ConfigureBlock(plot_provider, ..)
+            | Self::NewAnnotation(plot_provider, ..)
+            | Self::MoveAnnotation(plot_provider, ..)
+            | Self::EditAnnotation(plot_provider, ..)
+            | Self::DeleteSelection(plot_provider, ..) => {
+                if let PlotProvider::Module(_, name) = plot_provider {
+                    rename(name);
+                }
+            }
+            Self::EditModuleInterface(_, module_name, _, _, changed, ..) => {
+                rename(module_name);
+                changed.iter_mut().for_each(|(descriptor, _)| {
+                    if let PlotDescriptor::Module(name) = descriptor {
+                        rename(name);
+                    }
+                });
+            }
+            Self::SetPinNames(_, module_name, ..)
+            | Self::SetModuleDecoration(_, module_name, ..)
+            | Self::SetModuleShape(_, module_name, ..) => rename(module_name),
+            Self::CreateModule(..) | Self::DeleteModule(..) | Self::RenameModule(..) => (),
+        }
+    }
+
     fn exec(&mut self, app: &Application) {
         match self {
             Self::NewBlock(plot_provider, block) => {
@@ -119,18 +265,22 @@ impl Action {
                     if let Some(block) = plot.get_block_mut(*block_id) {
                         block.set_position(*to);
                     }
+                    plot.reroute_block(*block_id);
                 });
                 app.imp().rerender_editor();
             }
-            Self::MoveWaypoint(plot_provider, segment_id, _from, to) => {
-                plot_provider.with_mut(|plot| {
-                    if let Some(waypoint) = plot
-                        .get_connection_mut(segment_id.connection_id())
-                        .and_then(|c| c.get_segment_mut(segment_id.location()))
-                    {
+            Self::MoveWaypoint(plot_provider, segment_id, _from, to, was_routed) => {
+                if let Some(routed) = plot_provider.with_mut(|plot| {
+                    let connection = plot.get_connection_mut(segment_id.connection_id())?;
+                    // wires edited by hand are no longer touched by the auto-router
+                    let routed = connection.set_routed(segment_id.location()[0], false);
+                    if let Some(waypoint) = connection.get_segment_mut(segment_id.location()) {
                         waypoint.set_position(*to);
                     }
-                });
+                    Some(routed)
+                }) {
+                    *was_routed = routed.unwrap_or_default();
+                }
                 app.imp().rerender_editor();
             }
             Self::NewConnection(plot_provider, connection) => {
@@ -210,7 +360,93 @@ impl Action {
 
                 app.imp().rerender_editor();
             }
-            Self::DeleteSelection(plot_provider, blocks, connections, incoming) => {
+            Self::SetLabel(plot_provider, block_id, new, old) => {
+                if let Some(Some(previous)) = plot_provider.with_mut(|plot| {
+                    plot.get_block_mut(*block_id).map(|block| {
+                        let previous = block.label().cloned();
+                        block.set_label(new.clone());
+                        previous
+                    })
+                }) {
+                    *old = previous;
+                }
+                app.imp().rerender_editor();
+            }
+            Self::TransformBlocks(plot_provider, transformation, block_ids, old) => {
+                if let Some(previous) = plot_provider.with_mut(|plot| {
+                    block_ids
+                        .iter()
+                        .filter_map(|block_id| {
+                            plot.get_block_mut(*block_id).map(|block| {
+                                let previous = (block.orientation(), block.position());
+                                block.transform(*transformation);
+                                previous
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                }) {
+                    *old = previous;
+                }
+                plot_provider.with_mut(|plot| {
+                    block_ids
+                        .iter()
+                        .for_each(|block_id| plot.reroute_block(*block_id))
+                });
+                app.imp().rerender_editor();
+            }
+            Self::RouteWires(plot_provider, block_ids, old) => {
+                if let Some(previous) = plot_provider.with_mut(|plot| {
+                    let connection_ids = block_ids
+                        .iter()
+                        .filter_map(|block_id| plot.get_block(*block_id))
+                        .flat_map(|block| block.connected_to())
+                        .collect::<HashSet<_>>();
+
+                    connection_ids
+                        .into_iter()
+                        .filter_map(|connection_id| {
+                            let previous = plot.get_connection(&connection_id)?.clone();
+                            let mut connection = previous.clone();
+                            plot.route_connection(&mut connection);
+                            plot.connections_mut().insert(connection_id, connection);
+                            Some(previous)
+                        })
+                        .collect::<Vec<_>>()
+                }) {
+                    *old = previous;
+                }
+                app.imp().rerender_editor();
+            }
+            Self::ConfigureBlock(plot_provider, block_id, new, old, changed) => {
+                if let Some((previous, connections)) =
+                    plot_provider.with_mut(|plot| plot.configure_block(*block_id, new.as_slice()))
+                {
+                    *old = previous;
+                    *changed = connections;
+                }
+                app.imp().rerender_editor();
+            }
+            Self::NewAnnotation(plot_provider, annotation) => {
+                plot_provider.with_mut(|plot| plot.add_annotation(annotation.clone()));
+                app.imp().rerender_editor();
+            }
+            Self::MoveAnnotation(plot_provider, annotation_id, _from, to) => {
+                plot_provider.with_mut(|plot| {
+                    if let Some(annotation) = plot.get_annotation_mut(*annotation_id) {
+                        annotation.set_position(*to);
+                    }
+                });
+                app.imp().rerender_editor();
+            }
+            Self::EditAnnotation(plot_provider, annotation_id, new, _old) => {
+                plot_provider.with_mut(|plot| {
+                    if let Some(annotation) = plot.get_annotation_mut(*annotation_id) {
+                        annotation.set_text(new.clone());
+                    }
+                });
+                app.imp().rerender_editor();
+            }
+            Self::DeleteSelection(plot_provider, blocks, connections, incoming, annotations) => {
                 //println!("delete connections: {connections:?} incoming: {incoming:?}");
                 *incoming = plot_provider
                     .with_mut(|plot| {
@@ -218,6 +454,10 @@ impl Action {
                             plot.remove_connection(connection.id());
                         }
 
+                        for annotation in annotations.iter() {
+                            plot.remove_annotation(annotation.id());
+                        }
+
                         let mut incoming = vec![];
                         for block in blocks.iter() {
                             incoming.append(&mut plot.delete_block(block.id()))
@@ -229,6 +469,9 @@ impl Action {
                 blocks
                     .iter_mut()
                     .for_each(|block| block.set_highlighted(false));
+                annotations
+                    .iter_mut()
+                    .for_each(|annotation| annotation.set_highlighted(false));
                 app.imp().rerender_editor();
             }
             Self::CreateModule(project, module) => {
@@ -243,6 +486,45 @@ impl Action {
                 }
                 project.lock().unwrap().remove_module(module.name());
             }
+            Self::SetPinNames(project, module_name, new, old) => {
+                let previous = project.lock().unwrap().set_pin_names(
+                    module_name,
+                    new.0.clone(),
+                    new.1.clone(),
+                );
+                if let Some(previous) = previous {
+                    *old = previous;
+                }
+                app.imp().rerender_editor();
+            }
+            Self::EditModuleInterface(project, module_name, new, old, changed, shape) => {
+                let resized = project
+                    .lock()
+                    .unwrap()
+                    .resize_module(module_name, new.0, new.1);
+                if let Some((previous, connections, previous_shape)) = resized {
+                    *old = previous;
+                    *changed = connections;
+                    *shape = previous_shape;
+                }
+                app.imp().rerender_editor();
+            }
+            Self::RenameModule(project, from, to) => rename_module(app, project, from, to),
+            Self::SetModuleDecoration(project, module_name, new, old) => {
+                if let Some(previous) = set_module_decoration(app, project, module_name, new) {
+                    *old = previous;
+                }
+            }
+            Self::SetModuleShape(project, module_name, new, old) => {
+                let previous = project
+                    .lock()
+                    .unwrap()
+                    .set_module_shape(module_name, new.clone());
+                if let Some(previous) = previous {
+                    *old = previous;
+                }
+                app.imp().rerender_editor();
+            }
         }
     }
 
@@ -269,16 +551,17 @@ impl Action {
                     if let Some(block) = plot.get_block_mut(*block_id) {
                         block.set_position(*from);
                     }
+                    plot.reroute_block(*block_id);
                 });
                 app.imp().rerender_editor();
             }
-            Self::MoveWaypoint(plot_provider, segment_id, from, _to) => {
+            Self::MoveWaypoint(plot_provider, segment_id, from, _to, was_routed) => {
                 plot_provider.with_mut(|plot| {
-                    if let Some(waypoint) = plot
-                        .get_connection_mut(segment_id.connection_id())
-                        .and_then(|c| c.get_segment_mut(segment_id.location()))
-                    {
-                        waypoint.set_position(*from);
+                    if let Some(connection) = plot.get_connection_mut(segment_id.connection_id()) {
+                        connection.set_routed(segment_id.location()[0], *was_routed);
+                        if let Some(waypoint) = connection.get_segment_mut(segment_id.location()) {
+                            waypoint.set_position(*from);
+                        }
                     }
                 });
                 app.imp().rerender_editor();
@@ -344,9 +627,68 @@ impl Action {
 
                 app.imp().rerender_editor();
             }
-            Self::DeleteSelection(plot_provider, blocks, connections, incoming) => {
+            Self::SetLabel(plot_provider, block_id, _new, old) => {
+                plot_provider.with_mut(|plot| {
+                    if let Some(block) = plot.get_block_mut(*block_id) {
+                        block.set_label(old.clone());
+                    }
+                });
+                app.imp().rerender_editor();
+            }
+            Self::TransformBlocks(plot_provider, _transformation, block_ids, old) => {
+                plot_provider.with_mut(|plot| {
+                    block_ids
+                        .iter()
+                        .zip(old)
+                        .for_each(|(block_id, (orientation, position))| {
+                            if let Some(block) = plot.get_block_mut(*block_id) {
+                                block.set_orientation(*orientation);
+                                block.set_position(*position);
+                            }
+                        });
+                    block_ids
+                        .iter()
+                        .for_each(|block_id| plot.reroute_block(*block_id));
+                });
+                app.imp().rerender_editor();
+            }
+            Self::RouteWires(plot_provider, _block_ids, old) => {
+                plot_provider.with_mut(|plot| plot.restore_connections(old));
+                app.imp().rerender_editor();
+            }
+            Self::ConfigureBlock(plot_provider, block_id, _new, old, changed) => {
+                plot_provider.with_mut(|plot| {
+                    plot.configure_block(*block_id, old);
+                    plot.restore_connections(changed);
+                });
+                app.imp().rerender_editor();
+            }
+            Self::NewAnnotation(plot_provider, annotation) => {
+                plot_provider.with_mut(|plot| plot.remove_annotation(annotation.id()));
+                app.imp().rerender_editor();
+            }
+            Self::MoveAnnotation(plot_provider, annotation_id, from, _to) => {
+                plot_provider.with_mut(|plot| {
+                    if let Some(annotation) = plot.get_annotation_mut(*annotation_id) {
+                        annotation.set_position(*from);
+                    }
+                });
+                app.imp().rerender_editor();
+            }
+            Self::EditAnnotation(plot_provider, annotation_id, _new, old) => {
+                plot_provider.with_mut(|plot| {
+                    if let Some(annotation) = plot.get_annotation_mut(*annotation_id) {
+                        annotation.set_text(old.clone());
+                    }
+                });
+                app.imp().rerender_editor();
+            }
+            Self::DeleteSelection(plot_provider, blocks, connections, incoming, annotations) => {
                 println!("restore connections: {connections:?} incoming: {incoming:?}");
                 plot_provider.with_mut(|plot| {
+                    annotations
+                        .iter()
+                        .for_each(|annotation| plot.add_annotation(annotation.clone()));
                     blocks
                         .iter()
                         .for_each(|block| plot.add_block(block.clone()));
@@ -371,6 +713,96 @@ impl Action {
                 }
                 project.lock().unwrap().add_module(module.clone());
             }
+            Self::SetPinNames(project, module_name, _new, old) => {
+                project
+                    .lock()
+                    .unwrap()
+                    .set_pin_names(module_name, old.0.clone(), old.1.clone());
+                app.imp().rerender_editor();
+            }
+            Self::EditModuleInterface(project, module_name, _new, old, changed, shape) => {
+                let mut project = project.lock().unwrap();
+                project.resize_module(module_name, old.0, old.1);
+                if shape.is_some() {
+                    project.set_module_shape(module_name, shape.clone());
+                }
+                for (descriptor, connections) in changed {
+                    if let Some(plot) = project.plot_by_descriptor_mut(descriptor) {
+                        plot.restore_connections(connections);
+                    }
+                }
+                drop(project);
+                app.imp().rerender_editor();
+            }
+            Self::RenameModule(project, from, to) => rename_module(app, project, to, from),
+            Self::SetModuleDecoration(project, module_name, _new, old) => {
+                set_module_decoration(app, project, module_name, old);
+            }
+            Self::SetModuleShape(project, module_name, _new, old) => {
+                project
+                    .lock()
+                    .unwrap()
+                    .set_module_shape(module_name, old.clone());
+                app.imp().rerender_editor();
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::Project;
+    use std::sync::{Arc, Mutex};
+
+    fn module_names(stack: &ActionStack) -> Vec<&String> {
+        stack
+            .actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::SetLabel(PlotProvider::Module(_, name), ..) => Some(name),
+                Action::SetPinNames(_, name, ..) => Some(name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rename_then_undo() {
+        let project: ProjectRef = Arc::new(Mutex::new(Project::default()));
+        let (from, to) = (String::from("Adder"), String::from("Full Adder"));
+        let other = String::from("Counter");
+
+        let mut stack = ActionStack::default();
+        stack.actions = vec![
+            Action::SetLabel(
+                PlotProvider::Module(project.clone(), from.clone()),
+                Id::new(),
+                None,
+                None,
+            ),
+            Action::SetLabel(
+                PlotProvider::Module(project.clone(), other.clone()),
+                Id::new(),
+                None,
+                None,
+            ),
+            Action::SetPinNames(
+                project.clone(),
+                from.clone(),
+                (vec![], vec![]),
+                (vec![], vec![]),
+            ),
+            Action::RenameModule(project.clone(), from.clone(), to.clone()),
+        ];
+
+        stack.rename_module(&from, &to);
+        assert_eq!(module_names(&stack), [&to, &other, &to]);
+        assert!(
+            matches!(&stack.actions[3], Action::RenameModule(_, a, b) if *a == from && *b == to)
+        );
+
+        stack.rename_module(&to, &from);
+        assert_eq!(module_names(&stack), [&from, &other, &from]);
+    }
+}
@@ -33,6 +33,12 @@ impl Clipboard {
         if let Clipboard::Blocks(blocks, connections) = self {
             let mut data = (blocks.to_owned(), connections.to_owned());
             data.prepare_pasting(position);
+            if let Some(project) = plot_provider.project() {
+                let project = project.lock().unwrap();
+                data.0
+                    .iter_mut()
+                    .for_each(|block| project.link_image(block));
+            }
             plot_provider.with_mut(|plot| {
                 plot.unhighlight();
                 plot.set_selection(Selection::Many(
@@ -1,9 +1,30 @@
 use super::{selection::Selectable, *};
-use crate::application::user_settings::UserSettingsKey::ThemeKey;
-use crate::application::user_settings::UserSettingsValue::ThemeValue;
-use crate::{export::ModuleFile, fatal::*, project::Project, simulator::Simulator, FileExtension};
+use crate::application::user_settings::UserSettingsKey::{GateStyleKey, ThemeKey};
+use crate::application::user_settings::UserSettingsValue::{GateStyleValue, ThemeValue};
+use crate::{
+    export::{
+        blif::BlifFile,
+        image::{ImageFile, ImageOptions},
+        json::JsonNetlistFile,
+        logisim::LogisimFile,
+        print::{PrintOptions, Printout},
+        verilog::VerilogFile,
+        vhdl::VhdlFile,
+        ModuleFile,
+    },
+    fatal::*,
+    project::Project,
+    renderer::{
+        self,
+        vector::{Vector2, VectorCast},
+        GateStyle,
+    },
+    simulator::{Simulator, Transformation},
+    FileExtension,
+};
 use adw::ColorScheme;
 use serde::{Deserialize, Serialize};
+use std::{cell::Cell, path::Path, rc::Rc};
 
 #[derive(Default, Clone, Copy, Serialize, Deserialize)]
 pub enum Theme {
@@ -91,7 +112,7 @@ impl<'a> From<&GAction<'a>> for gio::SimpleAction {
 }
 
 lazy_static! {
-    pub(super) static ref ACTIONS: [GAction<'static>; 22] = [
+    pub(super) static ref ACTIONS: [GAction<'static>; 44] = [
         GAction::new(
             "quit",
             &["<primary>Q", "<primary>W"],
@@ -185,6 +206,56 @@ lazy_static! {
             None,
             Application::gaction_set_selection_color
         ),
+        GAction::new(
+            "rotate-clockwise",
+            &["<primary>R"],
+            None,
+            None,
+            Application::gaction_rotate_clockwise
+        ),
+        GAction::new(
+            "rotate-counter-clockwise",
+            &["<primary><shift>R"],
+            None,
+            None,
+            Application::gaction_rotate_counter_clockwise
+        ),
+        GAction::new(
+            "mirror",
+            &["<primary>M"],
+            None,
+            None,
+            Application::gaction_mirror
+        ),
+        GAction::new(
+            "route-wires",
+            &[],
+            None,
+            None,
+            Application::gaction_route_wires
+        ),
+        GAction::new(
+            "configure-block",
+            &[],
+            None,
+            None,
+            Application::gaction_configure_block
+        ),
+        GAction::new("set-label", &[], None, None, Application::gaction_set_label),
+        GAction::new(
+            "new-annotation",
+            &[],
+            None,
+            None,
+            Application::gaction_new_annotation
+        ),
+        GAction::new(
+            "edit-annotation",
+            &[],
+            None,
+            None,
+            Application::gaction_edit_annotation
+        ),
         GAction::new(
             "delete-module",
             &[],
@@ -199,6 +270,41 @@ lazy_static! {
             None,
             Application::gaction_edit_module
         ),
+        GAction::new(
+            "edit-pin-names",
+            &[],
+            Some(glib::VariantTy::STRING),
+            None,
+            Application::gaction_edit_pin_names
+        ),
+        GAction::new(
+            "edit-module-interface",
+            &[],
+            Some(glib::VariantTy::STRING),
+            None,
+            Application::gaction_edit_module_interface
+        ),
+        GAction::new(
+            "rename-module",
+            &[],
+            Some(glib::VariantTy::STRING),
+            None,
+            Application::gaction_rename_module
+        ),
+        GAction::new(
+            "edit-module-appearance",
+            &[],
+            Some(glib::VariantTy::STRING),
+            None,
+            Application::gaction_edit_module_appearance
+        ),
+        GAction::new(
+            "edit-module-shape",
+            &[],
+            Some(glib::VariantTy::STRING),
+            None,
+            Application::gaction_edit_module_shape
+        ),
         GAction::new(
             "search-module",
             &["<primary>F"],
@@ -213,6 +319,13 @@ lazy_static! {
             Some((glib::VariantTy::BYTE, Theme::SystemPreference.to_variant())),
             Application::gaction_change_theme
         ),
+        GAction::new(
+            "change-gate-style",
+            &[],
+            None,
+            Some((glib::VariantTy::BYTE, GateStyle::Iec.to_variant())),
+            Application::gaction_change_gate_style
+        ),
         GAction::new(
             "change-tick-speed",
             &[],
@@ -236,6 +349,62 @@ lazy_static! {
             None,
             None,
             Application::gaction_import_module
+        ),
+        GAction::new(
+            "import-verilog",
+            &[],
+            None,
+            None,
+            Application::gaction_import_verilog
+        ),
+        GAction::new(
+            "import-logisim",
+            &[],
+            None,
+            None,
+            Application::gaction_import_logisim
+        ),
+        GAction::new(
+            "export-verilog",
+            &[],
+            None,
+            None,
+            Application::gaction_export_verilog
+        ),
+        GAction::new(
+            "export-vhdl",
+            &[],
+            None,
+            None,
+            Application::gaction_export_vhdl
+        ),
+        GAction::new(
+            "export-json",
+            &[],
+            None,
+            None,
+            Application::gaction_export_json
+        ),
+        GAction::new(
+            "export-blif",
+            &[],
+            None,
+            None,
+            Application::gaction_export_blif
+        ),
+        GAction::new(
+            "export-image",
+            &["<primary><shift>E"],
+            None,
+            None,
+            Application::gaction_export_image
+        ),
+        GAction::new(
+            "print",
+            &["<primary>P"],
+            None,
+            None,
+            Application::gaction_print
         )
     ];
 }
@@ -271,9 +440,13 @@ impl Application {
         self.open_new();
     }
 
+    fn gaction_import_logisim(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.import_logisim();
+    }
+
     fn gaction_delete_block(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
         if let Some(plot_provider) = self.imp().current_plot() {
-            let (blocks, connections) = plot_provider
+            let (blocks, connections, annotations) = plot_provider
                 .with_mut(|plot| {
                     (
                         plot.selected()
@@ -297,6 +470,15 @@ impl Application {
                                 _ => None,
                             })
                             .collect(),
+                        plot.selected()
+                            .iter()
+                            .filter_map(|selected| {
+                                selected
+                                    .annotation_id()
+                                    .and_then(|id| plot.get_annotation(id))
+                                    .cloned()
+                            })
+                            .collect(),
                     )
                 })
                 .unwrap_or_default();
@@ -305,6 +487,7 @@ impl Application {
                 blocks,
                 connections,
                 vec![],
+                annotations,
             ));
         }
     }
@@ -338,6 +521,7 @@ impl Application {
     fn gaction_select_all(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
         self.imp().with_current_plot_mut(|plot| plot.select_all());
         self.imp().rerender_editor();
+        self.imp().refresh_properties();
     }
 
     fn gaction_set_selection_color(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
@@ -346,6 +530,68 @@ impl Application {
         }
     }
 
+    fn gaction_rotate_clockwise(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.transform_selection(Transformation::RotateClockwise);
+    }
+
+    fn gaction_rotate_counter_clockwise(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.transform_selection(Transformation::RotateCounterClockwise);
+    }
+
+    fn gaction_mirror(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.transform_selection(Transformation::Mirror);
+    }
+
+    fn gaction_route_wires(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        if let Some(plot_provider) = self.imp().current_plot()
+            && let Some(block_ids) = plot_provider.with(|plot| plot.selection().blocks())
+            && !block_ids.is_empty()
+        {
+            self.new_action(Action::RouteWires(plot_provider, block_ids, vec![]));
+        }
+    }
+
+    fn transform_selection(&self, transformation: Transformation) {
+        if let Some(plot_provider) = self.imp().current_plot()
+            && let Some(block_ids) = plot_provider.with(|plot| plot.selection().blocks())
+            && !block_ids.is_empty()
+        {
+            self.new_action(Action::TransformBlocks(
+                plot_provider,
+                transformation,
+                block_ids,
+                vec![],
+            ));
+        }
+    }
+
+    fn gaction_configure_block(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        if let Some(window) = self.active_window() {
+            dialogs::run(self, window, (), dialogs::configure_block);
+        }
+    }
+
+    fn gaction_set_label(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        if let Some(window) = self.active_window() {
+            dialogs::run(self, window, (), dialogs::set_label);
+        }
+    }
+
+    fn gaction_new_annotation(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        if let Some(view) = self.imp().current_circuit_view()
+            && let Some(window) = self.active_window()
+        {
+            let position = VectorCast::cast(view.mouse_world_position());
+            dialogs::run(self, window, position, dialogs::new_annotation);
+        }
+    }
+
+    fn gaction_edit_annotation(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        if let Some(window) = self.active_window() {
+            dialogs::run(self, window, (), dialogs::edit_annotation);
+        }
+    }
+
     fn gaction_delete_module(self, _: &gio::SimpleAction, parameter: Option<&glib::Variant>) {
         let module_name = parameter
             .expect("Could not get module name target.")
@@ -365,6 +611,69 @@ impl Application {
         self.imp().edit_module(module_name);
     }
 
+    fn gaction_edit_pin_names(self, _: &gio::SimpleAction, parameter: Option<&glib::Variant>) {
+        let module_name = parameter
+            .expect("Could not get module name target.")
+            .get::<String>()
+            .unwrap();
+
+        if let Some(window) = self.active_window() {
+            dialogs::run(self, window, module_name, dialogs::pin_names);
+        }
+    }
+
+    fn gaction_edit_module_interface(
+        self,
+        _: &gio::SimpleAction,
+        parameter: Option<&glib::Variant>,
+    ) {
+        let module_name = parameter
+            .expect("Could not get module name target.")
+            .get::<String>()
+            .unwrap();
+
+        if let Some(window) = self.active_window() {
+            dialogs::run(self, window, module_name, dialogs::edit_module_interface);
+        }
+    }
+
+    fn gaction_rename_module(self, _: &gio::SimpleAction, parameter: Option<&glib::Variant>) {
+        let module_name = parameter
+            .expect("Could not get module name target.")
+            .get::<String>()
+            .unwrap();
+
+        if let Some(window) = self.active_window() {
+            dialogs::run(self, window, module_name, dialogs::rename_module);
+        }
+    }
+
+    fn gaction_edit_module_appearance(
+        self,
+        _: &gio::SimpleAction,
+        parameter: Option<&glib::Variant>,
+    ) {
+        let module_name = parameter
+            .expect("Could not get module name target.")
+            .get::<String>()
+            .unwrap();
+
+        if let Some(window) = self.active_window() {
+            dialogs::run(self, window, module_name, dialogs::edit_module_appearance);
+        }
+    }
+
+    fn gaction_edit_module_shape(self, _: &gio::SimpleAction, parameter: Option<&glib::Variant>) {
+        let module_name = parameter
+            .expect("Could not get module name target.")
+            .get::<String>()
+            .unwrap();
+
+        if let Some(window) = self.active_window() {
+            dialogs::run(self, window, module_name, dialogs::edit_module_shape);
+        }
+    }
+
     fn gaction_search_module(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
         self.imp()
             .window()
@@ -396,6 +705,30 @@ impl Application {
         action.set_state(&new.to_variant());
     }
 
+    fn gaction_change_gate_style(
+        self,
+        action: &gio::SimpleAction,
+        parameter: Option<&glib::Variant>,
+    ) {
+        let new: GateStyle = parameter
+            .expect("could not get gate style parameter")
+            .get::<u8>()
+            .expect("the parameter needs to be of type `u8`")
+            .into();
+
+        GateStyle::set(new);
+
+        let mut user_settings = self.imp().user_settings().borrow_mut();
+        user_settings.set_setting(GateStyleKey, GateStyleValue(new));
+        if let Err(err) = user_settings.save_config() {
+            warn!("Could not save to config: {err}");
+        }
+        drop(user_settings);
+
+        action.set_state(&new.to_variant());
+        self.imp().rerender_editor();
+    }
+
     fn gaction_change_tps(self, action: &gio::SimpleAction, parameter: Option<&glib::Variant>) {
         let new = parameter
             .expect("could not get theme parameter")
@@ -454,17 +787,227 @@ impl Application {
         export_dialog.show();
     }
 
+    fn gaction_export_verilog(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.export_project::<VerilogFile>("Export As Verilog", false, |project, file, _| {
+            VerilogFile::export(project, file)
+        });
+    }
+
+    fn gaction_export_vhdl(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.export_project::<VhdlFile>("Export As VHDL", false, |project, file, _| {
+            VhdlFile::export(project, file)
+        });
+    }
+
+    fn gaction_export_json(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.export_project::<JsonNetlistFile>(
+            "Export Netlist As JSON",
+            true,
+            JsonNetlistFile::export,
+        );
+    }
+
+    fn gaction_export_blif(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.export_project::<BlifFile>("Export Netlist As BLIF", true, BlifFile::export);
+    }
+
+    fn gaction_export_image(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.export_image();
+    }
+
+    // asks for an image file and how it should look, then renders the current plot into it
+    fn export_image(&self) {
+        let window = self.active_window().unwrap();
+        let export_dialog = gtk::FileChooserNative::builder()
+            .transient_for(&window)
+            .modal(true)
+            .title("Export Image")
+            .action(gtk::FileChooserAction::Save)
+            .accept_label("Save")
+            .filter(&ImageFile::file_filter())
+            .cancel_label("Cancel")
+            .build();
+
+        export_dialog.add_choice("theme", "Theme", &["light", "dark"], &["Light", "Dark"]);
+        export_dialog.set_choice(
+            "theme",
+            match adw::StyleManager::default().is_dark() {
+                true => "dark",
+                false => "light",
+            },
+        );
+        export_dialog.add_choice("transparent", "Transparent background", &[], &[]);
+        export_dialog.add_choice("selection", "Selection only", &[], &[]);
+        export_dialog.set_current_name(&format!("circuit.{}", ImageFile::FILE_EXTENSION));
+
+        export_dialog.connect_response({
+            let file_chooser = RefCell::new(Some(export_dialog.clone()));
+            glib::clone!(@weak self as app, @weak window => move |_, response| {
+                if let Some(file_chooser) = file_chooser.take() {
+                    if response != gtk::ResponseType::Accept {
+                        return;
+                    }
+                    if let Some(file) = file_chooser.files().snapshot().into_iter().next() {
+                        let file: gio::File = file
+                            .downcast()
+                            .expect("unexpected type returned from file chooser");
+                        let choice = |id: &str| file_chooser.choice(id).map(|choice| choice.to_string());
+                        let options = ImageOptions {
+                            theme: match choice("theme").as_deref() {
+                                Some("light") => renderer::Theme::LIGHT,
+                                _ => renderer::Theme::DARK,
+                            },
+                            transparent: choice("transparent").as_deref() == Some("true"),
+                            selection_only: choice("selection").as_deref() == Some("true"),
+                        };
+                        let result = app
+                            .imp()
+                            .with_current_plot(|plot| ImageFile::export(plot, &file, &options))
+                            .unwrap_or_else(|| Err(String::from("No circuit is open")));
+                        if let Err(msg) = result {
+                            dialogs::run(app, window, msg, dialogs::basic_error);
+                        }
+                    }
+                } else {
+                    warn!("got file chooser response more than once");
+                }
+            })
+        });
+
+        export_dialog.show();
+    }
+
+    fn gaction_print(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.print();
+    }
+
+    // prints the main plot and all module plots using the system's print dialog
+    fn print(&self) {
+        let window = self.active_window().unwrap();
+        let file_name = self.imp().file_name();
+        let project_name = Path::new(&file_name)
+            .file_stem()
+            .map_or(file_name.clone(), |stem| stem.to_string_lossy().to_string());
+
+        let printout =
+            match Printout::new(&self.imp().project().lock().unwrap(), project_name.clone()) {
+                Ok(printout) => Rc::new(RefCell::new(printout)),
+                Err(msg) => {
+                    dialogs::run(self.clone(), window, msg, dialogs::basic_error);
+                    return;
+                }
+            };
+        let options = Rc::new(Cell::new(PrintOptions::default()));
+
+        let operation = gtk::PrintOperation::new();
+        operation.set_job_name(&project_name);
+        operation.set_unit(gtk::Unit::Points);
+        operation.set_custom_tab_label(Some("Layout"));
+        operation.connect_create_custom_widget(glib::clone!(@strong options => move |_| {
+            Some(PrintOptions::widget(options.clone()).upcast())
+        }));
+        operation.connect_begin_print(
+            glib::clone!(@strong printout, @strong options => move |operation, context| {
+                let pages = printout
+                    .borrow_mut()
+                    .paginate(options.get(), Vector2(context.width(), context.height()));
+                operation.set_n_pages(pages as i32);
+            }),
+        );
+        operation.connect_draw_page(
+            glib::clone!(@strong printout => move |_, context, page_nr| {
+                let result = printout.borrow().draw_page(
+                    &context.cairo_context(),
+                    Vector2(context.width(), context.height()),
+                    page_nr as usize,
+                );
+                if let Err(err) = result {
+                    error!("Error printing page {}: {err}", page_nr + 1);
+                }
+            }),
+        );
+
+        if let Err(err) = operation.run(gtk::PrintOperationAction::PrintDialog, Some(&window)) {
+            dialogs::run(self.clone(), window, err.to_string(), dialogs::basic_error);
+        }
+    }
+
+    // asks for a file to export the whole project to using the given exporter;
+    // if `flattening` is set, the user can choose to flatten the module hierarchy
+    fn export_project<F: FileExtension>(
+        &self,
+        title: &str,
+        flattening: bool,
+        export: fn(&Project, &gio::File, bool) -> Result<(), String>,
+    ) {
+        let window = self.active_window().unwrap();
+        let export_dialog = gtk::FileChooserNative::builder()
+            .transient_for(&window)
+            .modal(true)
+            .title(title)
+            .action(gtk::FileChooserAction::Save)
+            .accept_label("Save")
+            .filter(&F::file_filter())
+            .cancel_label("Cancel")
+            .build();
+
+        if flattening {
+            export_dialog.add_choice("flatten", "Flatten module hierarchy", &[], &[]);
+        }
+        export_dialog.set_current_name(&format!("main.{}", F::FILE_EXTENSION));
+        export_dialog.connect_response({
+            let file_chooser = RefCell::new(Some(export_dialog.clone()));
+            glib::clone!(@weak self as app, @weak window => move |_, response| {
+                if let Some(file_chooser) = file_chooser.take() {
+                    if response != gtk::ResponseType::Accept {
+                        return;
+                    }
+                    if let Some(file) = file_chooser.files().snapshot().into_iter().next() {
+                        let file: gio::File = file
+                            .downcast()
+                            .expect("unexpected type returned from file chooser");
+                        if !file.query_exists(gio::Cancellable::NONE) {
+                            file.create(gio::FileCreateFlags::NONE, gio::Cancellable::NONE).unwrap_or_die();
+                        }
+                        let flatten = file_chooser.choice("flatten").as_deref() == Some("true");
+                        let result = export(&app.imp().project().lock().unwrap(), &file, flatten);
+                        if let Err(msg) = result {
+                            dialogs::run(app, window, msg, dialogs::basic_error);
+                        }
+                    }
+                } else {
+                    warn!("got file chooser response more than once");
+                }
+            })
+        });
+
+        export_dialog.show();
+    }
+
     fn gaction_import_module(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.import_modules::<ModuleFile>("Import Module", ModuleFile::import);
+    }
+
+    fn gaction_import_verilog(self, _: &gio::SimpleAction, _: Option<&glib::Variant>) {
+        self.import_modules::<VerilogFile>("Import Verilog Netlist", VerilogFile::import);
+    }
+
+    // asks for files to read modules from using the given importer and adds them to the project
+    fn import_modules<F: FileExtension>(
+        &self,
+        title: &str,
+        import: fn(&gio::File) -> Result<ModuleFile, String>,
+    ) {
         let window = self.active_window().unwrap();
 
         let open_dialog = gtk::FileChooserNative::builder()
             .transient_for(&window)
             .modal(true)
-            .title("Import Module")
+            .title(title)
             .action(gtk::FileChooserAction::Open)
             .accept_label("Open")
             .cancel_label("Cancel")
-            .filter(&ModuleFile::file_filter())
+            .filter(&F::file_filter())
             .build();
 
         open_dialog.connect_response({
@@ -479,7 +1022,7 @@ impl Application {
                             .downcast()
                             .expect("unexpected type returned from file chooser");
                         let app = app.clone();
-                        if let Err(message) = ModuleFile::import(&file)
+                        if let Err(message) = import(&file)
                             .and_then(|mod_file| mod_file.merge(&app)) {
                                 let window = app.active_window().unwrap();
                                 dialogs::run(app, window, message, dialogs::basic_error);
@@ -613,6 +1156,67 @@ impl Application {
         }));
     }
 
+    // replaces the current project with one converted from a Logisim circuit file
+    pub(super) fn import_logisim(&self) {
+        self.close_current_file(glib::clone!(@weak self as app => move |response| {
+            match response {
+                "Cancel" => return,
+                "No" =>  {},
+                "Yes" => {
+                    if let Err(err) = app.imp().save(|_| ()) {
+                        let message = format!("Error saving to '{}': {}", app.imp().file_name(), err);
+                        error!("{}", message);
+                        if let Some(window) = app.active_window() {
+                            dialogs::run(app, window, message, dialogs::basic_error);
+                        }
+                        return;
+                    }
+                }
+                _ => panic!("unexpected response \"{}\"", response)
+            };
+
+            let window = app.active_window().unwrap();
+
+            let open_dialog = gtk::FileChooserNative::builder()
+                .transient_for(&window)
+                .modal(true)
+                .title("Import Logisim Circuit")
+                .action(gtk::FileChooserAction::Open)
+                .accept_label("Open")
+                .cancel_label("Cancel")
+                .filter(&LogisimFile::file_filter())
+                .build();
+
+            open_dialog.connect_response({
+                let file_chooser = RefCell::new(Some(open_dialog.clone()));
+                glib::clone!(@weak app, @weak window => move |_, response| {
+                    if let Some(file_chooser) = file_chooser.take() {
+                        if response != gtk::ResponseType::Accept {
+                            return;
+                        }
+                        if let Some(file) = file_chooser.file() {
+                            match LogisimFile::import(&file) {
+                                // the imported project is saved as a new file
+                                Ok((project, summary)) => {
+                                    app.imp().set_project(project, None);
+                                    if !summary.is_empty() {
+                                        dialogs::run(app, window, summary, dialogs::import_summary);
+                                    }
+                                }
+                                Err(error) => dialogs::run(app, window, format!("Error importing `{}`: {}", file.path().unwrap().to_str().unwrap(), error), dialogs::basic_error),
+                            }
+                        }
+                    }
+                    else {
+                        warn!("got file chooser response after window was freed");
+                    }
+                })
+            });
+
+            open_dialog.show();
+        }));
+    }
+
     pub(super) fn save_as(&self, then: fn(&Application)) {
         let window = self.active_window().unwrap();
 
@@ -7,9 +7,9 @@ pub mod template;
 pub mod user_settings;
 
 use crate::application::gactions::Theme;
-use crate::application::user_settings::UserSettingsKey::ThemeKey;
-use crate::application::user_settings::UserSettingsValue::ThemeValue;
-use crate::{application::clipboard::Clipboard, config, ui::dialogs};
+use crate::application::user_settings::UserSettingsKey::{GateStyleKey, ThemeKey};
+use crate::application::user_settings::UserSettingsValue::{GateStyleValue, ThemeValue};
+use crate::{application::clipboard::Clipboard, config, renderer::GateStyle, ui::dialogs};
 use action::*;
 use adw::traits::MessageDialogExt;
 use gtk::{gio, glib, prelude::*, subclass::prelude::*};
@@ -99,6 +99,7 @@ impl Application {
                 blocks,
                 connections,
                 vec![],
+                vec![],
             )),
             Clipboard::Module(_) => todo!(),
             Clipboard::Empty => {}
@@ -161,12 +162,26 @@ impl Application {
                 let theme_variant = match self.imp().user_settings().borrow().get_setting(ThemeKey)
                 {
                     Some(ThemeValue(custom_theme)) => custom_theme.to_variant(),
-                    None => Theme::SystemPreference.to_variant(),
+                    _ => Theme::SystemPreference.to_variant(),
                 };
 
                 action.set_state(&theme_variant);
             }
 
+            if gaction.name() == "change-gate-style" {
+                let style = match self
+                    .imp()
+                    .user_settings()
+                    .borrow()
+                    .get_setting(GateStyleKey)
+                {
+                    Some(GateStyleValue(style)) => *style,
+                    _ => GateStyle::default(),
+                };
+
+                action.set_state(&style.to_variant());
+            }
+
             action.connect_activate(glib::clone!(
                 @weak self as app => move |action, parameter| callback(app, action, parameter)
             ));
@@ -2,7 +2,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     renderer::{vector::Vector2, Renderable, COLOR_THEME},
-    simulator::{render_block_connector, render_line, Block, BlockID, Plot, SegmentID},
+    simulator::{
+        render_block_connector, render_line, AnnotationID, Block, BlockID, Connector, Plot,
+        SegmentID,
+    },
 };
 use std::cmp;
 
@@ -10,6 +13,7 @@ use std::cmp;
 pub enum Selectable {
     Block(BlockID),
     Waypoint(SegmentID),
+    Annotation(AnnotationID),
 }
 
 impl Selectable {
@@ -19,6 +23,13 @@ impl Selectable {
             _ => None,
         }
     }
+
+    pub fn annotation_id(&self) -> Option<AnnotationID> {
+        match self {
+            Self::Annotation(annotation_id) => Some(*annotation_id),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -79,8 +90,19 @@ impl Renderable for Selection {
                     .stroke()
                     .map(|_| ())
             }
-            Self::Connection(_, start, end) => {
-                render_line(false, *start, *end, renderer)?;
+            Self::Connection(source, start, end) => {
+                let direction = match source {
+                    ConnectionSource::Block(block_id, port) => data
+                        .get_block(*block_id)
+                        .map(|block| block.connector_direction(Connector::Output(*port))),
+                    ConnectionSource::Waypoint(_) => None,
+                };
+                render_line(
+                    false,
+                    (*start, direction.unwrap_or(Vector2(1, 0))),
+                    (*end, Vector2(-1, 0)),
+                    renderer,
+                )?;
                 render_block_connector(*end, false, false, renderer)
             }
             Self::MoveBlock(block) => block.render(renderer, data),
@@ -1,11 +1,11 @@
 use crate::application::gactions;
 use crate::application::user_settings::UserSettings;
-use crate::application::user_settings::UserSettingsKey::ThemeKey;
-use crate::application::user_settings::UserSettingsValue::ThemeValue;
+use crate::application::user_settings::UserSettingsKey::{GateStyleKey, ThemeKey};
+use crate::application::user_settings::UserSettingsValue::{GateStyleValue, ThemeValue};
 use crate::{
     fatal::*,
     project::*,
-    renderer::Theme,
+    renderer::{GateStyle, Theme},
     simulator::*,
     ui::{circuit_view::CircuitView, dialogs, main_window::MainWindow},
 };
@@ -66,6 +66,10 @@ impl ApplicationTemplate {
 
         let color_scheme = Into::<ColorScheme>::into(theme);
         adw::StyleManager::default().set_color_scheme(color_scheme);
+
+        if let Some(GateStyleValue(style)) = user_settings.get_setting(GateStyleKey) {
+            GateStyle::set(*style);
+        }
     }
 
     pub fn save(&self, then: fn(&Application)) -> Result<(), String> {
@@ -154,6 +158,13 @@ impl ApplicationTemplate {
         }
     }
 
+    // re-reads the selected block after the selection or the project changed
+    pub fn refresh_properties(&self) {
+        if let Some(window) = self.window.borrow().as_ref() {
+            window.properties().refresh();
+        }
+    }
+
     pub fn undo_button(&self) -> gtk::Button {
         self.window
             .borrow()
@@ -1,4 +1,4 @@
-use crate::application::gactions::Theme;
+use crate::{application::gactions::Theme, renderer::GateStyle};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::HashMap;
@@ -8,11 +8,13 @@ use std::path::PathBuf;
 #[derive(Serialize, Deserialize, Hash, PartialEq, Eq)]
 pub enum UserSettingsKey {
     ThemeKey,
+    GateStyleKey,
 }
 
 #[derive(Serialize, Deserialize)]
 pub enum UserSettingsValue {
     ThemeValue(Theme),
+    GateStyleValue(GateStyle),
 }
 
 pub struct UserSettings {
@@ -86,6 +88,10 @@ impl UserSettings {
             UserSettingsKey::ThemeKey,
             UserSettingsValue::ThemeValue(Theme::SystemPreference),
         );
+        self.user_settings.insert(
+            UserSettingsKey::GateStyleKey,
+            UserSettingsValue::GateStyleValue(GateStyle::default()),
+        );
         self.save_config()?;
         Ok(())
     }
@@ -0,0 +1,236 @@
+use super::netlist::{self, Cell, CellKind, Design, NetID, Netlist};
+use crate::{project::Project, FileExtension};
+
+use gtk::gio;
+use std::fmt::Write;
+
+// net tied to logic low, used for unconnected inputs; `$` never appears in generated names
+const ZERO: &str = "$zero";
+
+pub struct BlifFile;
+
+impl FileExtension for BlifFile {
+    const FILE_EXTENSION: &'static str = "blif";
+    const FILE_PATTERN: &'static str = "*.blif";
+
+    fn file_filter() -> gtk::FileFilter {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("BLIF files"));
+        filter.add_pattern(Self::FILE_PATTERN);
+        filter
+    }
+}
+
+impl BlifFile {
+    // writes the main plot as first model, followed by one model per custom module
+    pub fn export(project: &Project, file: &gio::File, flatten: bool) -> Result<(), String> {
+        netlist::export_to(project, file, |design| Self::generate(design, flatten))
+    }
+
+    pub fn generate(design: &Design, flatten: bool) -> Result<String, std::fmt::Error> {
+        let mut out = String::from("# Generated by LogicRs\n");
+        match flatten {
+            true => Self::write_model(&mut out, design, &design.flatten())?,
+            false => {
+                // the first model of a file is the top-level one
+                for netlist in [&design.top].into_iter().chain(design.modules.iter().rev()) {
+                    Self::write_model(&mut out, design, netlist)?;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn net(netlist: &Netlist, net: NetID) -> String {
+        netlist.net_name(net, |name, bit| format!("{name}[{bit}]"))
+    }
+
+    fn input(netlist: &Netlist, cell: &Cell, index: usize) -> String {
+        cell.inputs
+            .get(index)
+            .copied()
+            .flatten()
+            .map(|net| Self::net(netlist, net))
+            .unwrap_or_else(|| String::from(ZERO))
+    }
+
+    fn write_model(out: &mut String, design: &Design, netlist: &Netlist) -> std::fmt::Result {
+        let bits = |port: &netlist::Port| {
+            (0..port.width())
+                .map(|bit| match port.width() {
+                    1 => port.name.clone(),
+                    _ => format!("{}[{bit}]", port.name),
+                })
+                .collect::<Vec<_>>()
+        };
+        let inputs = netlist.inputs().flat_map(bits).collect::<Vec<_>>();
+        let outputs = netlist.outputs().flat_map(bits).collect::<Vec<_>>();
+
+        writeln!(out, "\n.model {}", netlist.name)?;
+        if !inputs.is_empty() {
+            writeln!(out, ".inputs {}", inputs.join(" "))?;
+        }
+        if !outputs.is_empty() {
+            writeln!(out, ".outputs {}", outputs.join(" "))?;
+        }
+
+        let uses_zero = netlist
+            .cells
+            .iter()
+            .any(|cell| cell.inputs.iter().any(Option::is_none))
+            || netlist
+                .outputs()
+                .any(|port| port.bits.iter().any(Option::is_none));
+        if uses_zero {
+            writeln!(out, ".names {ZERO}")?;
+        }
+
+        for cell in netlist.cells.iter() {
+            match cell.kind() {
+                CellKind::Instance => Self::write_subckt(out, design, netlist, cell)?,
+                CellKind::Fixed { value, device } => {
+                    if device {
+                        writeln!(
+                            out,
+                            "# {}: {} fixed to its current value",
+                            cell.name, cell.module
+                        )?;
+                    }
+                    for (index, net) in cell.outputs.iter().enumerate() {
+                        Self::constant(out, &Self::net(netlist, *net), (value >> index) & 1 == 1)?;
+                    }
+                }
+                CellKind::Register | CellKind::Builtin => Self::write_builtin(out, netlist, cell)?,
+                CellKind::Unsupported => {
+                    writeln!(out, "# {}: {} is not exported", cell.name, cell.module)?
+                }
+            }
+        }
+
+        // output ports are buffers of the nets driving them
+        for (port, name) in netlist
+            .outputs()
+            .flat_map(|port| port.bits.iter())
+            .zip(outputs.iter())
+        {
+            let source = port
+                .map(|net| Self::net(netlist, net))
+                .unwrap_or_else(|| String::from(ZERO));
+            writeln!(out, ".names {source} {name}\n1 1")?;
+        }
+
+        writeln!(out, ".end")
+    }
+
+    // single-output logic function given by the rows of its on-set
+    fn names(out: &mut String, inputs: &[String], output: &str, rows: &[&str]) -> std::fmt::Result {
+        writeln!(out, ".names {} {output}", inputs.join(" "))?;
+        rows.iter().try_for_each(|row| writeln!(out, "{row} 1"))
+    }
+
+    fn constant(out: &mut String, output: &str, value: bool) -> std::fmt::Result {
+        match value {
+            true => writeln!(out, ".names {output}\n1"),
+            false => writeln!(out, ".names {output}"),
+        }
+    }
+
+    fn write_builtin(out: &mut String, netlist: &Netlist, cell: &Cell) -> std::fmt::Result {
+        let input = |index| Self::input(netlist, cell, index);
+        let output = |index: usize| Self::net(netlist, cell.outputs[index]);
+        let name = &cell.name;
+        let two = [input(0), input(1)];
+
+        match cell.module.as_str() {
+            "And" => Self::names(out, &two, &output(0), &["11"]),
+            "Nand" => Self::names(out, &two, &output(0), &["0-", "-0"]),
+            "Or" => Self::names(out, &two, &output(0), &["1-", "-1"]),
+            "Nor" => Self::names(out, &two, &output(0), &["00"]),
+            "Xor" => Self::names(out, &two, &output(0), &["10", "01"]),
+            "Xnor" => Self::names(out, &two, &output(0), &["00", "11"]),
+            "Not" => Self::names(out, &[input(0)], &output(0), &["0"]),
+            "Mux" => Self::names(
+                out,
+                &[input(0), input(1), input(2)],
+                &output(0),
+                &["1-0", "-11"],
+            ),
+            "Demux" => {
+                Self::names(out, &two, &output(0), &["10"])?;
+                Self::names(out, &two, &output(1), &["11"])
+            }
+            "D Latch" => writeln!(out, ".latch {} {} ah {} 0", input(0), output(0), input(1)),
+            // level-sensitive latches with feedback become latches without a control signal
+            "SR Latch" => Self::feedback_latch(out, cell, &two, &output(0), &["10-", "-01"]),
+            "SR Nand Latch" => {
+                Self::feedback_latch(out, cell, &two, &output(0), &["0--", "111"])?;
+                Self::names(out, &[output(0)], &output(1), &["0"])
+            }
+            "JK Latch" => Self::feedback_latch(out, cell, &two, &output(0), &["10-", "110", "001"]),
+            "D Flip-Flop" => {
+                writeln!(out, ".latch {} {} re {} 0", input(0), output(0), input(1))?;
+                Self::names(out, &[output(0)], &output(1), &["0"])
+            }
+            "T Flip-Flop" => {
+                let next = format!("${name}_next");
+                Self::names(out, &[input(0), output(0)], &next, &["10", "01"])?;
+                writeln!(out, ".latch {next} {} re {} 0", output(0), input(1))?;
+                Self::names(out, &[output(0)], &output(1), &["0"])
+            }
+            "Random" => {
+                writeln!(
+                    out,
+                    "# {name}: Random cannot be expressed in BLIF, tied to 0"
+                )?;
+                (0..cell.outputs.len())
+                    .try_for_each(|index| Self::constant(out, &output(index), false))
+            }
+            module => unreachable!("`{module}` is not a register or builtin"),
+        }
+    }
+
+    // next state as function of the two inputs and the current state, stored in a generic latch
+    fn feedback_latch(
+        out: &mut String,
+        cell: &Cell,
+        inputs: &[String; 2],
+        output: &str,
+        rows: &[&str],
+    ) -> std::fmt::Result {
+        let next = format!("${}_next", cell.name);
+        Self::names(
+            out,
+            &[inputs[0].clone(), inputs[1].clone(), output.to_string()],
+            &next,
+            rows,
+        )?;
+        writeln!(out, ".latch {next} {output} 0")
+    }
+
+    fn write_subckt(
+        out: &mut String,
+        design: &Design,
+        netlist: &Netlist,
+        cell: &Cell,
+    ) -> std::fmt::Result {
+        let Some(module) = design.module(&cell.module) else {
+            return writeln!(out, "# {}: unknown module `{}`", cell.name, cell.module);
+        };
+
+        let inputs = module
+            .inputs()
+            .enumerate()
+            .map(|(index, port)| format!("{}={}", port.name, Self::input(netlist, cell, index)));
+        let outputs = module
+            .outputs()
+            .zip(cell.outputs.iter())
+            .map(|(port, net)| format!("{}={}", port.name, Self::net(netlist, *net)));
+
+        writeln!(
+            out,
+            ".subckt {} {}",
+            module.name,
+            inputs.chain(outputs).collect::<Vec<_>>().join(" ")
+        )
+    }
+}
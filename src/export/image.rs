@@ -0,0 +1,155 @@
+use crate::{
+    application::editor::GRID_SIZE,
+    renderer::{vector::*, CairoRenderer, Renderer, Theme},
+    simulator::Plot,
+    FileExtension,
+};
+
+use gtk::{cairo, gio, prelude::FileExt};
+use std::fs::File;
+
+// empty space around the exported contents
+const MARGIN: i32 = 2 * GRID_SIZE;
+// PNG images get rendered at twice the size to stay sharp in documents and slides
+const PNG_SCALE: f64 = 2.;
+
+pub struct ImageFile;
+
+impl FileExtension for ImageFile {
+    const FILE_EXTENSION: &'static str = "svg";
+    const FILE_PATTERN: &'static str = "*.svg";
+
+    fn file_filter() -> gtk::FileFilter {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Images (SVG, PNG, PDF)"));
+        filter.add_pattern(Self::FILE_PATTERN);
+        filter.add_pattern("*.png");
+        filter.add_pattern("*.pdf");
+        filter
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ImageFormat {
+    Svg,
+    Png,
+    Pdf,
+}
+
+impl ImageFormat {
+    fn from_file(file: &gio::File) -> Option<Self> {
+        let path = file.path()?;
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "svg" => Some(Self::Svg),
+            "png" => Some(Self::Png),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+}
+
+pub struct ImageOptions {
+    pub theme: Theme,
+    pub transparent: bool,
+    pub selection_only: bool,
+}
+
+impl ImageFile {
+    // renders the plot (or its selected part) to the file; the format is chosen by the file extension
+    pub fn export(plot: &Plot, file: &gio::File, options: &ImageOptions) -> Result<(), String> {
+        let format = ImageFormat::from_file(file).ok_or_else(|| {
+            String::from(
+                "Unknown image format; file names have to end with `.svg`, `.png` or `.pdf`",
+            )
+        })?;
+
+        let mut plot = match options.selection_only {
+            true => plot.excerpt(),
+            false => plot.clone(),
+        };
+        plot.clear_highlights();
+
+        let (min, max) = plot.bounds().ok_or_else(|| match options.selection_only {
+            true => String::from("Nothing is selected"),
+            false => String::from("The circuit is empty"),
+        })?;
+
+        options
+            .theme
+            .with(|| Self::write(&plot, file, format, min, max, !options.transparent))?;
+
+        info!(
+            "Exported image to `{}`",
+            file.path().unwrap().to_str().unwrap()
+        );
+        Ok(())
+    }
+
+    fn write(
+        plot: &Plot,
+        file: &gio::File,
+        format: ImageFormat,
+        min: Vector2<i32>,
+        max: Vector2<i32>,
+        background: bool,
+    ) -> Result<(), String> {
+        let path = file.path().unwrap();
+        let origin = min - Vector2(MARGIN, MARGIN);
+        let size = max - origin + Vector2(MARGIN, MARGIN);
+
+        match format {
+            ImageFormat::Svg => {
+                let surface = cairo::SvgSurface::new(size.0 as f64, size.1 as f64, Some(&path))
+                    .map_err(|err| err.to_string())?;
+                Self::draw(plot, &surface, origin, size, 1., background)?;
+                surface.finish();
+            }
+            ImageFormat::Pdf => {
+                let surface = cairo::PdfSurface::new(size.0 as f64, size.1 as f64, &path)
+                    .map_err(|err| err.to_string())?;
+                Self::draw(plot, &surface, origin, size, 1., background)?;
+                surface.finish();
+            }
+            ImageFormat::Png => {
+                let surface = cairo::ImageSurface::create(
+                    cairo::Format::ARgb32,
+                    (size.0 as f64 * PNG_SCALE).ceil() as i32,
+                    (size.1 as f64 * PNG_SCALE).ceil() as i32,
+                )
+                .map_err(|err| err.to_string())?;
+                Self::draw(plot, &surface, origin, size, PNG_SCALE, background)?;
+
+                let mut f = File::create(&path).map_err(|err| err.to_string())?;
+                surface
+                    .write_to_png(&mut f)
+                    .map_err(|err| err.to_string())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(
+        plot: &Plot,
+        surface: &cairo::Surface,
+        origin: Vector2<i32>,
+        size: Vector2<i32>,
+        scale: f64,
+        background: bool,
+    ) -> Result<(), String> {
+        let context = cairo::Context::new(surface).map_err(|err| err.to_string())?;
+        let mut renderer = CairoRenderer::new();
+        renderer
+            .set_scale(scale)
+            .set_size(Vector2(
+                (size.0 as f64 * scale).ceil() as i32,
+                (size.1 as f64 * scale).ceil() as i32,
+            ))
+            .translate(Vector2::cast(origin) * (-scale).into());
+
+        renderer
+            .render_plot(plot, &context, background)
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
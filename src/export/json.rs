@@ -0,0 +1,153 @@
+use super::netlist::{self, Cell, CellKind, Design, Direction, NetID, Netlist};
+use crate::{project::Project, simulator::Decoration, FileExtension};
+
+use gtk::gio;
+use serde::Serialize;
+
+// identifies files written by this exporter, increased on incompatible changes of the layout
+const FORMAT: &str = "logicrs-netlist";
+const VERSION: u32 = 1;
+
+pub struct JsonNetlistFile;
+
+impl FileExtension for JsonNetlistFile {
+    const FILE_EXTENSION: &'static str = "json";
+    const FILE_PATTERN: &'static str = "*.json";
+
+    fn file_filter() -> gtk::FileFilter {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("JSON netlist files"));
+        filter.add_pattern(Self::FILE_PATTERN);
+        filter
+    }
+}
+
+#[derive(Serialize)]
+struct JsonDesign<'a> {
+    format: &'static str,
+    version: u32,
+    top: &'a str,
+    modules: Vec<JsonModule<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonModule<'a> {
+    name: &'a str,
+    ports: Vec<JsonPort<'a>>,
+    cells: Vec<JsonCell<'a>>,
+    nets: Vec<JsonNet>,
+}
+
+#[derive(Serialize)]
+struct JsonPort<'a> {
+    name: &'a str,
+    direction: &'static str,
+    bits: &'a Vec<Option<NetID>>,
+}
+
+#[derive(Serialize)]
+struct JsonCell<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    kind: &'a str,
+    builtin: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    inputs: &'a Vec<Option<NetID>>,
+    outputs: &'a Vec<NetID>,
+}
+
+#[derive(Serialize)]
+struct JsonNet {
+    id: NetID,
+    name: String,
+}
+
+impl JsonNetlistFile {
+    // writes the netlists of all modules and the main plot, see DOCS.md for the layout
+    pub fn export(project: &Project, file: &gio::File, flatten: bool) -> Result<(), String> {
+        netlist::export_to(project, file, |design| Self::generate(design, flatten))
+    }
+
+    pub fn generate(design: &Design, flatten: bool) -> Result<String, std::fmt::Error> {
+        let flattened;
+        let netlists = match flatten {
+            true => {
+                flattened = design.flatten();
+                vec![&flattened]
+            }
+            false => design.modules.iter().chain([&design.top]).collect(),
+        };
+
+        let json = JsonDesign {
+            format: FORMAT,
+            version: VERSION,
+            top: &design.top.name,
+            modules: netlists
+                .into_iter()
+                .map(|netlist| Self::module(design, netlist))
+                .collect(),
+        };
+        serde_json::to_string_pretty(&json)
+            .map(|text| text + "\n")
+            .map_err(|_| std::fmt::Error)
+    }
+
+    fn module<'a>(design: &'a Design, netlist: &'a Netlist) -> JsonModule<'a> {
+        JsonModule {
+            name: &netlist.name,
+            ports: netlist
+                .ports
+                .iter()
+                .map(|port| JsonPort {
+                    name: &port.name,
+                    direction: match port.direction {
+                        Direction::Input => "input",
+                        Direction::Output => "output",
+                    },
+                    bits: &port.bits,
+                })
+                .collect(),
+            cells: netlist
+                .cells
+                .iter()
+                .map(|cell| Self::cell(design, cell))
+                .collect(),
+            nets: (0..netlist.nets.len())
+                .map(|id| JsonNet {
+                    id,
+                    name: netlist.net_name(id, |name, bit| format!("{name}[{bit}]")),
+                })
+                .collect(),
+        }
+    }
+
+    fn cell<'a>(design: &'a Design, cell: &'a Cell) -> JsonCell<'a> {
+        let value = match cell.kind() {
+            CellKind::Fixed { value, .. } => Some(value),
+            _ => None,
+        };
+        let seed = match cell.decoration {
+            Decoration::Random(_, seed) => Some(seed),
+            _ => None,
+        };
+
+        JsonCell {
+            name: &cell.name,
+            // instances refer to their module by its name in the netlist
+            kind: match cell.builtin {
+                true => &cell.module,
+                false => design
+                    .module(&cell.module)
+                    .map_or(&cell.module, |module| &module.name),
+            },
+            builtin: cell.builtin,
+            value,
+            seed,
+            inputs: &cell.inputs,
+            outputs: &cell.outputs,
+        }
+    }
+}
@@ -0,0 +1,1394 @@
+use super::xml::{self, Element};
+use crate::{
+    application::editor::GRID_SIZE,
+    id::Id,
+    project::Project,
+    renderer::vector::Vector2,
+    simulator::{
+        builtin::BUILTINS, Annotation, Block, BlockID, Connection, Module, Plot, Port, Segment,
+        Transformation,
+    },
+    FileExtension,
+};
+
+use gtk::{gio, prelude::FileExt};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fs,
+};
+
+// Logisim places everything on a grid of 10 units
+const LOGISIM_GRID: i32 = 10;
+const MARGIN: i32 = 2 * GRID_SIZE;
+// space left of a module's circuit for its Input block
+const IO_SPACE: i32 = 6 * GRID_SIZE;
+
+type Point = (i32, i32);
+
+pub struct LogisimFile;
+
+impl FileExtension for LogisimFile {
+    const FILE_EXTENSION: &'static str = "circ";
+    const FILE_PATTERN: &'static str = "*.circ";
+
+    fn file_filter() -> gtk::FileFilter {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Logisim circuit files"));
+        filter.add_pattern(Self::FILE_PATTERN);
+        filter
+    }
+}
+
+impl LogisimFile {
+    // reads a Logisim(-evolution) project: the main circuit becomes the main plot and all other
+    // circuits become custom modules; also returns a summary of everything that was left out
+    pub fn import(file: &gio::File) -> Result<(Project, Vec<String>), String> {
+        let path = file.path().unwrap();
+        let source = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+        let result = import(&source)?;
+
+        info!("Imported Logisim project `{}`", path.to_str().unwrap());
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Facing {
+    East,
+    South,
+    West,
+    North,
+}
+
+impl Facing {
+    fn parse(value: Option<&str>, default: Self) -> Self {
+        match value {
+            Some("east") => Self::East,
+            Some("south") => Self::South,
+            Some("west") => Self::West,
+            Some("north") => Self::North,
+            _ => default,
+        }
+    }
+
+    // clockwise quarter turns starting from east
+    fn turns(self) -> u8 {
+        match self {
+            Self::East => 0,
+            Self::South => 1,
+            Self::West => 2,
+            Self::North => 3,
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Self::East => Self::West,
+            Self::South => Self::North,
+            Self::West => Self::East,
+            Self::North => Self::South,
+        }
+    }
+
+    // turns an offset of a component facing east into one of a component facing this way
+    fn rotate(self, (x, y): Point) -> Point {
+        match self {
+            Self::East => (x, y),
+            Self::South => (-y, x),
+            Self::West => (-x, -y),
+            Self::North => (y, -x),
+        }
+    }
+
+    // transformation turning a block that faces `default` into this direction
+    fn transformation(self, default: Self) -> Option<Transformation> {
+        match (self.turns() + 4 - default.turns()) % 4 {
+            0 => None,
+            1 => Some(Transformation::RotateClockwise),
+            2 => Some(Transformation::Mirror),
+            _ => Some(Transformation::RotateCounterClockwise),
+        }
+    }
+}
+
+fn parse_point(value: &str) -> Option<Point> {
+    let (x, y) = value
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+fn offset(origin: Point, (x, y): Point) -> Point {
+    (origin.0 + x, origin.1 + y)
+}
+
+// attribute values of a component or circuit, long values are stored as element text
+fn attributes(element: &Element) -> HashMap<&str, &str> {
+    element
+        .children("a")
+        .filter_map(|a| {
+            let value = a.attribute("val").unwrap_or(a.text.as_str());
+            a.attribute("name").map(|name| (name, value))
+        })
+        .collect()
+}
+
+fn parse_number(value: &str) -> Option<u128> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn width(attributes: &HashMap<&str, &str>) -> u32 {
+    attributes
+        .get("width")
+        .and_then(|width| width.parse().ok())
+        .unwrap_or(1)
+}
+
+struct Pin {
+    location: Point,
+    facing: Facing,
+    output: bool,
+    label: String,
+}
+
+impl Pin {
+    fn parse(element: &Element) -> Option<Self> {
+        let attributes = attributes(element);
+        Some(Self {
+            location: parse_point(element.attribute("loc")?)?,
+            facing: Facing::parse(attributes.get("facing").copied(), Facing::East),
+            output: attributes.get("output") == Some(&"true")
+                || attributes.get("type") == Some(&"output"),
+            label: attributes.get("label").unwrap_or(&"").to_string(),
+        })
+    }
+}
+
+// positions of a subcircuit's pins relative to the anchor of an instance facing east
+#[derive(Default)]
+struct Appearance {
+    offsets: HashMap<Point, Point>,
+    // pins on the west side of the default Logisim-evolution appearance; their distance to the
+    // anchor depends on the width of the pin labels and is recovered from the attached wires
+    west: Vec<(Point, i32)>,
+}
+
+impl Appearance {
+    // pins are placed on the side opposite to their facing, 10 units apart
+    fn classic(pins: &[Pin]) -> Self {
+        let mut edges: [Vec<&Pin>; 4] = Default::default();
+        for pin in pins {
+            edges[pin.facing.opposite().turns() as usize].push(pin);
+        }
+        for (turns, edge) in edges.iter_mut().enumerate() {
+            match turns % 2 {
+                0 => edge.sort_by_key(|pin| (pin.location.1, pin.location.0)),
+                _ => edge.sort_by_key(|pin| pin.location),
+            }
+        }
+
+        let count = |facing: Facing| edges[facing.turns() as usize].len() as i32;
+        let (east, south, west, north) = (
+            count(Facing::East),
+            count(Facing::South),
+            count(Facing::West),
+            count(Facing::North),
+        );
+        let (vertical, horizontal) = (north.max(south), east.max(west));
+
+        let edge_offset = |facing: i32, opposite: i32, others: i32| {
+            let most = facing.max(opposite);
+            let start = match most {
+                0 | 1 if others == 0 => 15,
+                3.. if others == 0 => 5,
+                _ => 10,
+            };
+            start + LOGISIM_GRID * ((most - facing) / 2)
+        };
+        let dimension = |most: i32, others: i32| match (most, others) {
+            (..=2, _) => 30,
+            (_, 0) => LOGISIM_GRID * most,
+            _ => LOGISIM_GRID * most + LOGISIM_GRID,
+        };
+
+        let offset_north = edge_offset(north, south, horizontal);
+        let offset_south = edge_offset(south, north, horizontal);
+        let offset_east = edge_offset(east, west, vertical);
+        let offset_west = edge_offset(west, east, vertical);
+        let width = dimension(vertical, horizontal);
+        let height = dimension(horizontal, vertical);
+
+        let anchor = if east > 0 {
+            (width, offset_east)
+        } else if north > 0 {
+            (offset_north, 0)
+        } else if west > 0 {
+            (0, offset_west)
+        } else if south > 0 {
+            (offset_south, height)
+        } else {
+            (0, 0)
+        };
+
+        let mut offsets = HashMap::new();
+        for (turns, edge) in edges.iter().enumerate() {
+            for (index, pin) in edge.iter().enumerate() {
+                let step = LOGISIM_GRID * index as i32;
+                let position = match turns {
+                    0 => (width, offset_east + step),
+                    1 => (offset_south + step, height),
+                    2 => (0, offset_west + step),
+                    _ => (offset_north + step, 0),
+                };
+                offsets.insert(pin.location, (position.0 - anchor.0, position.1 - anchor.1));
+            }
+        }
+
+        Self {
+            offsets,
+            west: vec![],
+        }
+    }
+
+    // inputs on the west and outputs on the east side, 20 units apart
+    fn evolution(pins: &[Pin]) -> Self {
+        let sorted = |output: bool| {
+            let mut pins = pins
+                .iter()
+                .filter(|pin| pin.output == output)
+                .map(|pin| pin.location)
+                .collect::<Vec<_>>();
+            pins.sort_by_key(|(x, y)| (*y, *x));
+            pins
+        };
+        let (inputs, outputs) = (sorted(false), sorted(true));
+        let step = 2 * LOGISIM_GRID;
+
+        let mut appearance = Self::default();
+        for (index, location) in outputs.iter().enumerate() {
+            appearance
+                .offsets
+                .insert(*location, (0, step * index as i32));
+        }
+        for (index, location) in inputs.into_iter().enumerate() {
+            match outputs.is_empty() {
+                true => {
+                    appearance
+                        .offsets
+                        .insert(location, (0, step * index as i32));
+                }
+                false => appearance.west.push((location, step * index as i32)),
+            }
+        }
+        appearance
+    }
+
+    // appearance drawn by the user, with the ports and the anchor marked
+    fn custom(element: &Element) -> Option<Self> {
+        let center = |element: &Element| -> Option<Point> {
+            let get = |name| element.attribute(name)?.parse::<i32>().ok();
+            Some((
+                get("x")? + get("width").unwrap_or(0) / 2,
+                get("y")? + get("height").unwrap_or(0) / 2,
+            ))
+        };
+
+        let anchor = center(element.child("circ-anchor")?)?;
+        let offsets = element
+            .children("circ-port")
+            .filter_map(|port| {
+                let position = center(port)?;
+                let pin = parse_point(port.attribute("pin")?)?;
+                Some((pin, (position.0 - anchor.0, position.1 - anchor.1)))
+            })
+            .collect();
+
+        Some(Self {
+            offsets,
+            west: vec![],
+        })
+    }
+}
+
+// the pins of a circuit used as a subcircuit
+struct Interface {
+    name: String,
+    inputs: Vec<Pin>,
+    outputs: Vec<Pin>,
+    // possible appearances, the one matching the wires best is used for each instance
+    appearances: Vec<Appearance>,
+}
+
+impl Interface {
+    fn new(name: String, circuit: &Element) -> Result<Self, String> {
+        let mut pins = vec![];
+        for comp in circuit.children("comp") {
+            if comp.attribute("name") != Some("Pin") {
+                continue;
+            }
+            match Pin::parse(comp) {
+                // multi-bit pins are reported with the circuit's other components
+                Some(_) if width(&attributes(comp)) > 1 => (),
+                Some(pin) => pins.push(pin),
+                None => return Err(format!("line {}: invalid pin", comp.line)),
+            }
+        }
+
+        let attributes = attributes(circuit);
+        let appearances = match circuit.child("appearance").and_then(Appearance::custom) {
+            Some(custom) => vec![custom],
+            None => match attributes.get("appearance").copied() {
+                Some("classic") => vec![Appearance::classic(&pins)],
+                Some(_) => vec![Appearance::evolution(&pins)],
+                None => vec![Appearance::classic(&pins), Appearance::evolution(&pins)],
+            },
+        };
+
+        let (mut outputs, mut inputs): (Vec<_>, Vec<_>) =
+            pins.into_iter().partition(|pin| pin.output);
+        inputs.sort_by_key(|pin| (pin.location.1, pin.location.0));
+        outputs.sort_by_key(|pin| (pin.location.1, pin.location.0));
+        if inputs.len().max(outputs.len()) > Block::MAX_CONNECTIONS as usize {
+            return Err(format!(
+                "Circuit `{name}` has more than {} input or output pins",
+                Block::MAX_CONNECTIONS
+            ));
+        }
+
+        Ok(Self {
+            name,
+            inputs,
+            outputs,
+            appearances,
+        })
+    }
+
+    fn module(&self) -> Module {
+        let mut module = Module::new(
+            self.name.clone(),
+            self.inputs.len() as u8,
+            self.outputs.len() as u8,
+        );
+        let names = |pins: &[Pin]| pins.iter().map(|pin| pin.label.clone()).collect();
+        module.set_pin_names(names(&self.inputs), names(&self.outputs));
+        module.add_io_blocks();
+        module
+    }
+
+    // absolute pin positions of an instance, picking the appearance most wires attach to
+    fn place(
+        &self,
+        location: Point,
+        facing: Facing,
+        wired: &HashSet<Point>,
+    ) -> HashMap<Point, Point> {
+        let absolute = |offset: Point| self::offset(location, facing.rotate(offset));
+
+        let mut best: Option<(usize, HashMap<Point, Point>)> = None;
+        for appearance in self.appearances.iter() {
+            let widths = match appearance.west.is_empty() {
+                true => vec![0],
+                false => (2..=60).map(|units| units * LOGISIM_GRID).collect(),
+            };
+            for width in widths {
+                let pins = appearance
+                    .offsets
+                    .iter()
+                    .map(|(pin, offset)| (*pin, absolute(*offset)))
+                    .chain(
+                        appearance
+                            .west
+                            .iter()
+                            .map(|(pin, dy)| (*pin, absolute((-width, *dy)))),
+                    )
+                    .collect::<HashMap<_, _>>();
+                let score = pins.values().filter(|point| wired.contains(point)).count();
+                if best.as_ref().is_none_or(|(best, _)| score > *best) {
+                    best = Some((score, pins));
+                }
+            }
+        }
+        best.map(|(_, pins)| pins).unwrap_or_default()
+    }
+}
+
+// components that could not be imported, by circuit and component, with the number of occurrences
+#[derive(Default)]
+struct Summary(BTreeMap<(String, String, &'static str), usize>);
+
+impl Summary {
+    fn add(&mut self, circuit: &str, component: &str, reason: &'static str) {
+        *self
+            .0
+            .entry((circuit.to_string(), component.to_string(), reason))
+            .or_default() += 1;
+    }
+
+    fn lines(self) -> Vec<String> {
+        self.0
+            .into_iter()
+            .map(|((circuit, component, reason), count)| match count {
+                1 => format!("{circuit}: {component} ({reason})"),
+                _ => format!("{circuit}: {count} × {component} ({reason})"),
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Terminal {
+    Input(u8),
+    Output(u8),
+}
+
+// a pin of a block at a position of the Logisim circuit
+struct Endpoint {
+    point: Point,
+    block: usize,
+    terminal: Terminal,
+    // module pins are kept as waypoints, as their Input/Output blocks are placed elsewhere
+    waypoint: bool,
+}
+
+struct Placement {
+    block: Block,
+    center: Option<Point>,
+    facing: Option<(Facing, Facing)>,
+}
+
+// union-find over the connected points of a circuit
+#[derive(Default)]
+struct Nets(HashMap<Point, Point>);
+
+impl Nets {
+    fn find(&mut self, point: Point) -> Point {
+        let parent = *self.0.entry(point).or_insert(point);
+        if parent == point {
+            return point;
+        }
+        let root = self.find(parent);
+        self.0.insert(point, root);
+        root
+    }
+
+    fn union(&mut self, a: Point, b: Point) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.0.insert(a, b);
+        }
+    }
+}
+
+struct Builder<'a> {
+    circuit: &'a str,
+    libraries: &'a HashMap<&'a str, &'a str>,
+    interfaces: &'a HashMap<String, Interface>,
+    modules: &'a HashMap<String, Module>,
+    legacy: bool,
+    summary: &'a mut Summary,
+
+    // wire graph, edges through tunnels and buffers are marked as jumps
+    graph: HashMap<Point, Vec<(Point, bool)>>,
+    wired: HashSet<Point>,
+    points: HashMap<Point, usize>,
+    nets: Nets,
+
+    placements: Vec<Placement>,
+    endpoints: Vec<Endpoint>,
+    links: Vec<((usize, u8), (usize, u8))>,
+    annotations: Vec<(String, Point)>,
+}
+
+impl<'a> Builder<'a> {
+    fn new(
+        circuit: &'a str,
+        libraries: &'a HashMap<&'a str, &'a str>,
+        interfaces: &'a HashMap<String, Interface>,
+        modules: &'a HashMap<String, Module>,
+        legacy: bool,
+        summary: &'a mut Summary,
+    ) -> Self {
+        Self {
+            circuit,
+            libraries,
+            interfaces,
+            modules,
+            legacy,
+            summary,
+            graph: HashMap::new(),
+            wired: HashSet::new(),
+            points: HashMap::new(),
+            nets: Nets::default(),
+            placements: vec![],
+            endpoints: vec![],
+            links: vec![],
+            annotations: vec![],
+        }
+    }
+
+    fn connect(&mut self, a: Point, b: Point, jump: bool) {
+        self.graph.entry(a).or_default().push((b, jump));
+        self.graph.entry(b).or_default().push((a, jump));
+        self.nets.union(a, b);
+    }
+
+    // adds the wires, split wherever another wire ends on them
+    fn wires(&mut self, circuit: &Element) -> Result<(), String> {
+        let mut wires = vec![];
+        for wire in circuit.children("wire") {
+            let get = |name| wire.attribute(name).and_then(parse_point);
+            let (Some(from), Some(to)) = (get("from"), get("to")) else {
+                return Err(format!("line {}: invalid wire", wire.line));
+            };
+            wires.push((from, to));
+            self.wired.extend([from, to]);
+        }
+
+        for (from, to) in wires {
+            let mut stops = self
+                .wired
+                .iter()
+                .filter(|(x, y)| {
+                    let between = |value: i32, a: i32, b: i32| value > a.min(b) && value < a.max(b);
+                    (from.0 == to.0 && *x == from.0 && between(*y, from.1, to.1))
+                        || (from.1 == to.1 && *y == from.1 && between(*x, from.0, to.0))
+                })
+                .copied()
+                .collect::<Vec<_>>();
+            stops.sort_by_key(|point| (point.0 - from.0).abs() + (point.1 - from.1).abs());
+
+            let mut previous = from;
+            for point in stops.into_iter().chain([to]) {
+                self.connect(previous, point, false);
+                previous = point;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_block(
+        &mut self,
+        module: &Module,
+        center: Option<Point>,
+        facing: Option<(Facing, Facing)>,
+        label: Option<&str>,
+    ) -> usize {
+        let mut block = Block::new(&module, Vector2(0, 0), None);
+        if let Some(label) = label {
+            block.set_label(Some(label.to_string()));
+        }
+        self.placements.push(Placement {
+            block,
+            center,
+            facing,
+        });
+        self.placements.len() - 1
+    }
+
+    fn builtin(
+        &mut self,
+        name: &str,
+        center: Point,
+        facing: Option<(Facing, Facing)>,
+        label: Option<&str>,
+    ) -> usize {
+        let module = BUILTINS.get(name).unwrap().module();
+        self.add_block(module, Some(center), facing, label)
+    }
+
+    fn endpoint(&mut self, point: Point, block: usize, terminal: Terminal) {
+        self.endpoints.push(Endpoint {
+            point,
+            block,
+            terminal,
+            waypoint: false,
+        });
+    }
+
+    // center of a component with a single connection, whose body lies behind it
+    fn behind(location: Point, facing: Facing) -> Point {
+        offset(location, facing.rotate((-LOGISIM_GRID, 0)))
+    }
+
+    fn center(points: &[Point]) -> Point {
+        let (xs, ys): (Vec<_>, Vec<_>) = points.iter().copied().unzip();
+        let middle = |values: Vec<i32>| {
+            (values.iter().min().unwrap_or(&0) + values.iter().max().unwrap_or(&0)) / 2
+        };
+        (middle(xs), middle(ys))
+    }
+
+    fn gate_kind(name: &str) -> Option<&'static str> {
+        match name {
+            "AND Gate" => Some("And"),
+            "NAND Gate" => Some("Nand"),
+            "OR Gate" => Some("Or"),
+            "NOR Gate" => Some("Nor"),
+            "XOR Gate" => Some("Xor"),
+            "XNOR Gate" => Some("Xnor"),
+            _ => None,
+        }
+    }
+
+    // input positions of a gate facing east relative to its output, see Logisim's `AbstractGate`
+    fn gate_inputs(
+        kind: &str,
+        inputs: i32,
+        size: i32,
+        negated: impl Fn(i32) -> bool,
+    ) -> Vec<Point> {
+        let bonus = match kind {
+            "Xor" | "Xnor" => 10,
+            _ => 0,
+        } + match kind {
+            "Nand" | "Nor" | "Xnor" => 10,
+            _ => 0,
+        };
+
+        let (start, distance, lower) = match (inputs, size) {
+            (..=3, ..40) => (-5, 10, 10),
+            (..=3, 40..60) | (..=2, _) => (-10, 20, 20),
+            (..=3, _) => (-15, 30, 30),
+            (4, 60..) => (-5, 20, 0),
+            _ => (-5, 10, 10),
+        };
+
+        (0..inputs)
+            .map(|index| {
+                let dy = match inputs % 2 {
+                    1 => start * (inputs - 1) + distance * index,
+                    _ if index >= inputs / 2 => start * inputs + distance * index + lower,
+                    _ => start * inputs + distance * index,
+                };
+                let dx = size + bonus + if negated(index) { 10 } else { 0 };
+                (-dx, dy)
+            })
+            .collect()
+    }
+
+    fn gate(
+        &mut self,
+        kind: &'static str,
+        location: Point,
+        facing: Facing,
+        attributes: &HashMap<&str, &str>,
+    ) {
+        let size = match attributes.get("size").copied() {
+            Some("narrow") => 30,
+            Some("wide") => 70,
+            Some(size) => size.parse().unwrap_or(50),
+            None => 50,
+        };
+        let inputs = attributes
+            .get("inputs")
+            .and_then(|inputs| inputs.parse().ok())
+            .unwrap_or(if self.legacy { 5 } else { 2 });
+        let negated =
+            |index: i32| attributes.get(format!("negate{index}").as_str()) == Some(&"true");
+        if (0..inputs).any(negated) {
+            self.summary
+                .add(self.circuit, kind, "negated inputs are not inverted");
+        }
+
+        let points = Self::gate_inputs(kind, inputs, size, negated)
+            .into_iter()
+            .map(|point| offset(location, facing.rotate(point)))
+            .collect::<Vec<_>>();
+        let center = Self::center(&[&points[..], &[location]].concat());
+        // Logisim ignores unconnected gate inputs
+        let mut connected = points
+            .into_iter()
+            .filter(|point| self.wired.contains(point) || self.points.contains_key(point))
+            .collect::<Vec<_>>();
+
+        let orientation = Some((facing, Facing::East));
+        let label = attributes
+            .get("label")
+            .copied()
+            .filter(|label| !label.is_empty());
+        if connected.len() <= 2 {
+            // a gate with a single input passes it on
+            let kind = match (kind, connected.len()) {
+                ("Xor", 1) => "Or",
+                ("Xnor", 1) => "Nor",
+                _ => kind,
+            };
+            let block = self.builtin(kind, center, orientation, label);
+            if connected.len() == 1 {
+                connected.push(connected[0]);
+            }
+            for (index, point) in connected.into_iter().enumerate() {
+                self.endpoint(point, block, Terminal::Input(index as u8));
+            }
+            self.endpoint(location, block, Terminal::Output(0));
+            return;
+        }
+
+        // gates with more inputs become a chain of two-input gates
+        let base = match kind {
+            "Nand" => "And",
+            "Nor" => "Or",
+            "Xnor" => "Xor",
+            kind => kind,
+        };
+        let stages = connected.len() - 1;
+        let mut previous = None;
+        for (stage, point) in connected.iter().skip(1).enumerate() {
+            let shift = facing.rotate((-(LOGISIM_GRID * 6) * (stages - 1 - stage) as i32, 0));
+            let block = match stage + 1 == stages {
+                true => self.builtin(kind, offset(center, shift), orientation, label),
+                false => self.builtin(base, offset(center, shift), orientation, None),
+            };
+            match previous {
+                None => self.endpoint(connected[0], block, Terminal::Input(0)),
+                Some(previous) => self.links.push(((previous, 0), (block, 0))),
+            }
+            self.endpoint(*point, block, Terminal::Input(1));
+            previous = Some(block);
+        }
+        self.endpoint(location, previous.unwrap(), Terminal::Output(0));
+    }
+
+    // pin positions of a flip-flop: data, clock, q, !q, reset, set
+    fn flip_flop_pins(location: Point, classic: bool) -> [Point; 6] {
+        let pins = match classic {
+            true => [(-40, 0), (-40, 20), (0, 0), (0, 20), (-10, 30), (-30, 30)],
+            false => [(0, 10), (0, 50), (60, 10), (60, 50), (30, 60), (30, 0)],
+        };
+        pins.map(|pin| offset(location, pin))
+    }
+
+    fn flip_flop(&mut self, kind: &str, location: Point, attributes: &HashMap<&str, &str>) {
+        let count = |pins: &[Point; 6]| pins.iter().filter(|pin| self.wired.contains(pin)).count();
+        let pins = match attributes.get("appearance").copied() {
+            Some("classic") => Self::flip_flop_pins(location, true),
+            Some(_) => Self::flip_flop_pins(location, false),
+            None => {
+                let (classic, evolution) = (
+                    Self::flip_flop_pins(location, true),
+                    Self::flip_flop_pins(location, false),
+                );
+                match count(&evolution) > count(&classic) {
+                    true => evolution,
+                    false => classic,
+                }
+            }
+        };
+
+        if pins[4..].iter().any(|pin| self.wired.contains(pin)) {
+            self.summary.add(
+                self.circuit,
+                kind,
+                "asynchronous set and reset are not connected",
+            );
+        }
+        if attributes
+            .get("trigger")
+            .is_some_and(|trigger| *trigger != "rising")
+        {
+            self.summary
+                .add(self.circuit, kind, "triggers on the rising edge instead");
+        }
+
+        let label = attributes
+            .get("label")
+            .copied()
+            .filter(|label| !label.is_empty());
+        let block = self.builtin(kind, Self::center(&pins[..4]), None, label);
+        self.endpoint(pins[0], block, Terminal::Input(0));
+        self.endpoint(pins[1], block, Terminal::Input(1));
+        self.endpoint(pins[2], block, Terminal::Output(0));
+        self.endpoint(pins[3], block, Terminal::Output(1));
+    }
+
+    fn subcircuit(&mut self, name: &str, location: Point, attributes: &HashMap<&str, &str>) {
+        let (interfaces, modules) = (self.interfaces, self.modules);
+        let (Some(interface), Some(module)) = (interfaces.get(name), modules.get(name)) else {
+            self.summary
+                .add(self.circuit, name, "circuit cannot be used as a subcircuit");
+            return;
+        };
+
+        let facing = Facing::parse(attributes.get("facing").copied(), Facing::East);
+        let pins = interface.place(location, facing, &self.wired);
+        let points = pins.values().copied().collect::<Vec<_>>();
+        let center = match points.is_empty() {
+            true => location,
+            false => Self::center(&points),
+        };
+        let label = attributes
+            .get("label")
+            .copied()
+            .filter(|label| !label.is_empty());
+        let block = self.add_block(module, Some(center), Some((facing, Facing::East)), label);
+
+        for (index, pin) in interface.inputs.iter().enumerate() {
+            if let Some(point) = pins.get(&pin.location) {
+                self.endpoint(*point, block, Terminal::Input(index as u8));
+            }
+        }
+        for (index, pin) in interface.outputs.iter().enumerate() {
+            if let Some(point) = pins.get(&pin.location) {
+                self.endpoint(*point, block, Terminal::Output(index as u8));
+            }
+        }
+    }
+
+    // pin of the circuit itself, connected to the module's Input/Output block or a Switch/Lamp
+    fn pin(&mut self, pin: Pin, interface: Option<&Interface>) {
+        let label = Some(pin.label.as_str()).filter(|label| !label.is_empty());
+        let Some(interface) = interface else {
+            let center = Self::behind(pin.location, pin.facing);
+            match pin.output {
+                true => {
+                    let block =
+                        self.builtin("Lamp", center, Some((pin.facing, Facing::West)), label);
+                    self.endpoint(pin.location, block, Terminal::Input(0));
+                }
+                false => {
+                    let block =
+                        self.builtin("Switch", center, Some((pin.facing, Facing::East)), label);
+                    self.endpoint(pin.location, block, Terminal::Output(0));
+                }
+            }
+            return;
+        };
+
+        let (pins, block, terminal): (_, _, fn(u8) -> Terminal) = match pin.output {
+            true => (&interface.outputs, 1, Terminal::Input),
+            false => (&interface.inputs, 0, Terminal::Output),
+        };
+        if let Some(index) = pins.iter().position(|other| other.location == pin.location) {
+            self.endpoints.push(Endpoint {
+                point: pin.location,
+                block,
+                terminal: terminal(index as u8),
+                waypoint: true,
+            });
+        }
+    }
+
+    fn component(
+        &mut self,
+        comp: &Element,
+        interface: Option<&Interface>,
+        tunnels: &mut HashMap<String, Vec<Point>>,
+    ) -> Result<(), String> {
+        let Some(name) = comp.attribute("name") else {
+            return Err(format!("line {}: component without a name", comp.line));
+        };
+        let Some(location) = comp.attribute("loc").and_then(parse_point) else {
+            return Err(format!("line {}: invalid location of `{name}`", comp.line));
+        };
+        let attributes = attributes(comp);
+        let facing = |default| Facing::parse(attributes.get("facing").copied(), default);
+        let label = attributes
+            .get("label")
+            .copied()
+            .filter(|label| !label.is_empty());
+
+        let library = match comp.attribute("lib") {
+            Some(lib) => match self.libraries.get(lib) {
+                Some(library) => *library,
+                None => return Err(format!("line {}: unknown library `{lib}`", comp.line)),
+            },
+            None => {
+                self.subcircuit(name, location, &attributes);
+                return Ok(());
+            }
+        };
+
+        if width(&attributes) > 1 {
+            self.summary
+                .add(self.circuit, name, "multi-bit components are not supported");
+            return Ok(());
+        }
+
+        match (library, name) {
+            ("#Gates", "NOT Gate") => {
+                let size = match attributes.get("size").copied() {
+                    Some("narrow") | Some("20") => 20,
+                    _ => 30,
+                };
+                let facing = facing(Facing::East);
+                let input = offset(location, facing.rotate((-size, 0)));
+                let block = self.builtin(
+                    "Not",
+                    Self::center(&[input, location]),
+                    Some((facing, Facing::East)),
+                    label,
+                );
+                self.endpoint(input, block, Terminal::Input(0));
+                self.endpoint(location, block, Terminal::Output(0));
+            }
+            ("#Gates", "Buffer") => {
+                let input = offset(location, facing(Facing::East).rotate((-20, 0)));
+                self.connect(input, location, true);
+            }
+            ("#Gates", gate) if let Some(kind) = Self::gate_kind(gate) => {
+                self.gate(kind, location, facing(Facing::East), &attributes)
+            }
+            ("#Wiring", "Pin") => {
+                if let Some(pin) = Pin::parse(comp) {
+                    self.pin(pin, interface);
+                }
+            }
+            // tunnels without a label are not connected to anything
+            ("#Wiring", "Tunnel") => {
+                if let Some(label) = label {
+                    tunnels.entry(label.to_string()).or_default().push(location);
+                }
+            }
+            ("#Wiring", "Constant" | "Power" | "Ground") => {
+                let high = match name {
+                    "Constant" => attributes
+                        .get("value")
+                        .map_or(Some(1), |value| parse_number(value))
+                        .is_some_and(|value| value & 1 == 1),
+                    name => name == "Power",
+                };
+                let center = match name {
+                    "Constant" => Self::behind(location, facing(Facing::East)),
+                    "Power" => offset(location, facing(Facing::North).rotate((LOGISIM_GRID, 0))),
+                    _ => offset(location, facing(Facing::South).rotate((LOGISIM_GRID, 0))),
+                };
+                let block = self.builtin(if high { "High" } else { "Low" }, center, None, label);
+                self.endpoint(location, block, Terminal::Output(0));
+            }
+            ("#I/O", "LED") => {
+                let facing = facing(Facing::West);
+                let block = self.builtin(
+                    "Lamp",
+                    Self::behind(location, facing),
+                    Some((facing, Facing::West)),
+                    label,
+                );
+                self.endpoint(location, block, Terminal::Input(0));
+            }
+            ("#I/O", "Button") => {
+                let facing = facing(Facing::East);
+                let block = self.builtin(
+                    "Button",
+                    Self::behind(location, facing),
+                    Some((facing, Facing::East)),
+                    label,
+                );
+                self.endpoint(location, block, Terminal::Output(0));
+            }
+            ("#Memory", "D Flip-Flop" | "T Flip-Flop") => {
+                self.flip_flop(name, location, &attributes)
+            }
+            ("#Base", "Text") => {
+                if let Some(text) = attributes.get("text").filter(|text| !text.is_empty()) {
+                    self.annotations.push((text.to_string(), location));
+                }
+            }
+            _ => self.summary.add(self.circuit, name, "not supported"),
+        }
+        Ok(())
+    }
+
+    // waypoints along the wires from `from` to every reachable point
+    fn paths(&self, from: Point) -> HashMap<Point, (Point, bool)> {
+        let mut parents = HashMap::from([(from, (from, false))]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(point) = queue.pop_front() {
+            for (next, jump) in self.graph.get(&point).into_iter().flatten() {
+                if !parents.contains_key(next) {
+                    parents.insert(*next, (point, *jump));
+                    queue.push_back(*next);
+                }
+            }
+        }
+        parents
+    }
+
+    fn waypoints(
+        parents: &HashMap<Point, (Point, bool)>,
+        from: &Endpoint,
+        to: &Endpoint,
+    ) -> Vec<Point> {
+        let mut path = vec![];
+        let mut point = to.point;
+        if to.waypoint {
+            path.push(point);
+        }
+        // only the wires after the last tunnel are kept
+        while let Some((parent, jump)) = parents.get(&point)
+            && *parent != point
+        {
+            if *jump {
+                return Self::simplify(path);
+            }
+            point = *parent;
+            if point != from.point || from.waypoint {
+                path.push(point);
+            }
+        }
+        Self::simplify(path)
+    }
+
+    // reverses a path and drops the points between two others on the same straight line
+    fn simplify(mut path: Vec<Point>) -> Vec<Point> {
+        path.reverse();
+        path.dedup();
+        let mut simplified: Vec<Point> = vec![];
+        for point in path {
+            if let [.., a, b] = simplified[..]
+                && ((a.0 == b.0 && b.0 == point.0) || (a.1 == b.1 && b.1 == point.1))
+            {
+                simplified.pop();
+            }
+            simplified.push(point);
+        }
+        simplified
+    }
+
+    fn build(
+        mut self,
+        circuit: &Element,
+        module: Option<&Module>,
+        interface: Option<&Interface>,
+    ) -> Result<Plot, String> {
+        self.wires(circuit)?;
+
+        // connection points of all components, to find out which gate inputs are used
+        for comp in circuit.children("comp") {
+            if let Some(location) = comp.attribute("loc").and_then(parse_point) {
+                *self.points.entry(location).or_default() += 1;
+            }
+        }
+
+        if let Some(module) = module
+            && let Some((input_block, output_block)) = module.io_blocks()
+        {
+            for id in [input_block, output_block] {
+                let block = module.plot().unwrap().get_block(id).unwrap().clone();
+                self.placements.push(Placement {
+                    block,
+                    center: None,
+                    facing: None,
+                });
+            }
+        }
+
+        let mut tunnels = HashMap::new();
+        for comp in circuit.children("comp") {
+            self.component(comp, interface, &mut tunnels)?;
+        }
+        for points in tunnels.values() {
+            for pair in points.windows(2) {
+                self.connect(pair[0], pair[1], true);
+            }
+        }
+        for endpoint in self.endpoints.iter() {
+            self.nets.find(endpoint.point);
+        }
+
+        // translate the circuit to the top left corner of the plot and scale it to the grid
+        let points = self
+            .graph
+            .keys()
+            .chain(self.endpoints.iter().map(|endpoint| &endpoint.point))
+            .chain(self.annotations.iter().map(|(_, point)| point))
+            .chain(
+                self.placements
+                    .iter()
+                    .filter_map(|placement| placement.center.as_ref()),
+            )
+            .copied()
+            .collect::<Vec<_>>();
+        let minimum = (
+            points.iter().map(|point| point.0).min().unwrap_or(0),
+            points.iter().map(|point| point.1).min().unwrap_or(0),
+        );
+        let maximum_x = points.iter().map(|point| point.0).max().unwrap_or(0);
+        let left = MARGIN + if interface.is_some() { IO_SPACE } else { 0 };
+        let to_plot = |(x, y): Point| {
+            Vector2(
+                left + (x - minimum.0) * GRID_SIZE / LOGISIM_GRID,
+                MARGIN + (y - minimum.1) * GRID_SIZE / LOGISIM_GRID,
+            )
+        };
+        let snap = |value: i32| (value + GRID_SIZE / 2).div_euclid(GRID_SIZE) * GRID_SIZE;
+
+        let mut plot = Plot::new();
+        let mut block_ids = vec![];
+        let right = to_plot((maximum_x, 0)).0 + 4 * GRID_SIZE;
+        for (index, mut placement) in std::mem::take(&mut self.placements).into_iter().enumerate() {
+            if let Some((facing, default)) = placement.facing
+                && let Some(transformation) = facing.transformation(default)
+            {
+                placement.block.transform(transformation);
+            }
+            let position = match placement.center {
+                Some(center) => {
+                    let bounds = placement.block.bounds();
+                    let center = to_plot(center);
+                    Vector2(snap(center.0 - bounds.0 / 2), snap(center.1 - bounds.1 / 2))
+                }
+                None if index == 0 => Vector2(MARGIN, MARGIN),
+                None => Vector2(right, MARGIN),
+            };
+            placement.block.set_position(position);
+            block_ids.push(placement.block.id());
+            plot.add_block(placement.block);
+        }
+
+        for (text, point) in self.annotations.iter() {
+            plot.add_annotation(Annotation::new(text.clone(), to_plot(*point)));
+        }
+
+        // group the endpoints by net, every net is driven by its first output
+        let mut nets: BTreeMap<Point, Vec<usize>> = BTreeMap::new();
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            nets.entry(self.nets.find(endpoint.point))
+                .or_default()
+                .push(index);
+        }
+
+        let mut destinations: BTreeMap<(usize, u8), Vec<Segment>> = BTreeMap::new();
+        for ((from, from_port), (to, to_port)) in self.links.iter() {
+            destinations
+                .entry((*from, *from_port))
+                .or_default()
+                .push(Segment::Block(block_ids[*to], *to_port));
+        }
+
+        for members in nets.values() {
+            let (drivers, sinks): (Vec<_>, Vec<_>) = members
+                .iter()
+                .map(|index| &self.endpoints[*index])
+                .partition(|endpoint| matches!(endpoint.terminal, Terminal::Output(_)));
+            let Some(driver) = drivers.first() else {
+                continue;
+            };
+            if drivers.len() > 1 {
+                self.summary.add(
+                    self.circuit,
+                    "wire",
+                    "driven by several outputs, only the first one is connected",
+                );
+            }
+            let Terminal::Output(port) = driver.terminal else {
+                unreachable!()
+            };
+
+            let parents = self.paths(driver.point);
+            let mut tree = Trie::default();
+            for sink in sinks {
+                let Terminal::Input(input) = sink.terminal else {
+                    unreachable!()
+                };
+                let waypoints = Self::waypoints(&parents, driver, sink)
+                    .into_iter()
+                    .map(to_plot)
+                    .collect::<Vec<_>>();
+                tree.insert(&waypoints, Segment::Block(block_ids[sink.block], input));
+            }
+            destinations
+                .entry((driver.block, port))
+                .or_default()
+                .extend(tree.segments());
+        }
+
+        for ((block, port), segments) in destinations {
+            if !segments.is_empty() {
+                plot.add_connection(Connection::new(
+                    Port::Output(block_ids[block], port),
+                    segments,
+                ));
+            }
+        }
+
+        Ok(plot)
+    }
+}
+
+// waypoints shared by the wires of a connection up to where they branch off
+#[derive(Default)]
+struct Trie {
+    children: Vec<(Vector2<i32>, Trie)>,
+    leaves: Vec<Segment>,
+}
+
+impl Trie {
+    fn insert(&mut self, waypoints: &[Vector2<i32>], leaf: Segment) {
+        let Some((first, rest)) = waypoints.split_first() else {
+            self.leaves.push(leaf);
+            return;
+        };
+
+        let index = match self
+            .children
+            .iter()
+            .position(|(position, _)| position == first)
+        {
+            Some(index) => index,
+            None => {
+                self.children.push((*first, Trie::default()));
+                self.children.len() - 1
+            }
+        };
+        self.children[index].1.insert(rest, leaf);
+    }
+
+    fn segments(self) -> Vec<Segment> {
+        self.children
+            .into_iter()
+            .map(|(position, child)| {
+                let segments = child
+                    .segments()
+                    .into_iter()
+                    .map(|segment| (Id::new(), segment))
+                    .collect();
+                Segment::Waypoint(segments, position, false)
+            })
+            .chain(self.leaves)
+            .collect()
+    }
+}
+
+// names of subcircuits used by a circuit
+fn dependencies(circuit: &Element) -> impl Iterator<Item = &str> {
+    circuit
+        .children("comp")
+        .filter(|comp| comp.attribute("lib").is_none())
+        .filter_map(|comp| comp.attribute("name"))
+}
+
+fn import(source: &str) -> Result<(Project, Vec<String>), String> {
+    let root = xml::parse(source)?;
+    if root.name != "project" {
+        return Err(String::from("The file is not a Logisim project"));
+    }
+
+    // the original Logisim (up to version 2.7) used different defaults than Logisim-evolution
+    let legacy = root
+        .attribute("source")
+        .and_then(|version| {
+            let mut numbers = version.split('.').map(|number| number.parse::<u32>().ok());
+            Some((numbers.next()??, numbers.next()??))
+        })
+        .is_some_and(|version| version < (2, 8));
+
+    let libraries = root
+        .children("lib")
+        .filter_map(|lib| Some((lib.attribute("name")?, lib.attribute("desc")?)))
+        .collect::<HashMap<_, _>>();
+
+    let circuits = root
+        .children("circuit")
+        .filter_map(|circuit| Some((circuit.attribute("name")?, circuit)))
+        .collect::<Vec<_>>();
+    let main_name = root
+        .child("main")
+        .and_then(|main| main.attribute("name"))
+        .or_else(|| circuits.first().map(|(name, _)| *name))
+        .ok_or_else(|| String::from("The file does not contain any circuits"))?;
+
+    // the main circuit becomes a module only if it is used as a subcircuit
+    let used = circuits
+        .iter()
+        .flat_map(|(_, circuit)| dependencies(circuit))
+        .collect::<HashSet<_>>();
+
+    let mut interfaces = HashMap::new();
+    let mut modules = HashMap::new();
+    for (name, circuit) in circuits.iter() {
+        if *name == main_name && !used.contains(name) {
+            continue;
+        }
+        // circuits named like a builtin module get a suffix
+        let module_name = match BUILTINS.contains_key(name) {
+            true => format!("{name} (Logisim)"),
+            false => name.to_string(),
+        };
+        let interface = Interface::new(module_name, circuit)?;
+        modules.insert(name.to_string(), interface.module());
+        if interfaces.insert(name.to_string(), interface).is_some() {
+            return Err(format!("Circuit `{name}` is defined twice"));
+        }
+    }
+
+    let mut summary = Summary::default();
+    let mut project = Project::default();
+    let mut built = vec![];
+    for (name, circuit) in circuits.iter() {
+        if let Some(module) = modules.get(*name) {
+            let plot = Builder::new(
+                name,
+                &libraries,
+                &interfaces,
+                &modules,
+                legacy,
+                &mut summary,
+            )
+            .build(circuit, Some(module), interfaces.get(*name))?;
+            let mut module = module.clone();
+            *module.plot_mut().unwrap() = plot;
+            built.push(module);
+        }
+        if *name == main_name {
+            *project.main_plot_mut() = Builder::new(
+                name,
+                &libraries,
+                &interfaces,
+                &modules,
+                legacy,
+                &mut summary,
+            )
+            .build(circuit, None, None)?;
+        }
+    }
+
+    for module in built {
+        project.add_module(module);
+    }
+    project
+        .iter_plots_mut()
+        .for_each(|plot| plot.update_all_blocks());
+
+    Ok((project, summary.lines()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn main_circuit() {
+        let (project, summary) = import(include_str!("../../tests/fixtures/nand.circ")).unwrap();
+        assert_eq!(summary, ["main: Probe (not supported)"]);
+
+        // input pins become switches and the output pin a lamp
+        let plot = project.main_plot();
+        let count = |name: &str| {
+            plot.blocks()
+                .values()
+                .filter(|block| block.module_id() == name)
+                .count()
+        };
+        assert_eq!(plot.blocks().len(), 5);
+        assert_eq!(count("Switch"), 2);
+        assert_eq!(count("And"), 1);
+        assert_eq!(count("Not"), 1);
+        assert_eq!(count("Lamp"), 1);
+        assert_eq!(plot.connections().len(), 4);
+
+        let annotations = plot.annotations().values().collect::<Vec<_>>();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].text(), "A & B → Y");
+    }
+
+    #[test]
+    fn not_a_project() {
+        assert!(import("<circuit name=\"main\"/>").is_err());
+        assert!(import("<project/>").is_err());
+    }
+}
@@ -1,3 +1,14 @@
+pub mod blif;
+pub mod image;
+pub mod json;
+pub mod logisim;
+pub mod netlist;
+pub mod print;
+pub mod verilog;
+pub mod verilog_import;
+pub mod vhdl;
+pub mod xml;
+
 use crate::{application::Application, project::Project, simulator::Module, FileExtension};
 
 use gtk::{gio, prelude::FileExt, subclass::prelude::ObjectSubclassIsExt};
@@ -27,6 +38,10 @@ impl FileExtension for ModuleFile {
 }
 
 impl ModuleFile {
+    pub fn new(main_name: String, modules: HashMap<String, Module>) -> Self {
+        Self { main_name, modules }
+    }
+
     pub fn from_existing(project: &Project, mod_name: String) -> Option<Self> {
         project.module(&mod_name).map(|module| {
             let mut mod_file = Self {
@@ -95,6 +110,7 @@ impl ModuleFile {
                 window.add_module_to_ui(app, &module);
                 project.add_existing_module(module);
             }
+            project.link_images();
             return Ok(());
         }
 
@@ -0,0 +1,902 @@
+use crate::{
+    project::Project,
+    simulator::{Block, BlockID, Decoration, Plot, Port as PlotPort},
+};
+use gtk::{gio, prelude::FileExt};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::OpenOptions,
+    io::Write,
+};
+
+// builtins of the main plot that become input and output ports of the top-level design
+pub const TOP_INPUTS: [&str; 5] = [
+    "Switch",
+    "Button",
+    "Numeric Input",
+    "Hex Keypad",
+    "Keyboard",
+];
+pub const TOP_OUTPUTS: [&str; 3] = ["Lamp", "Pixel Display", "Terminal"];
+
+// identifiers that are reserved in at least one of the supported hardware description languages
+const RESERVED: [&str; 196] = [
+    "abs",
+    "access",
+    "after",
+    "alias",
+    "all",
+    "always",
+    "and",
+    "architecture",
+    "array",
+    "assert",
+    "assign",
+    "attribute",
+    "automatic",
+    "begin",
+    "block",
+    "body",
+    "buf",
+    "buffer",
+    "bufif0",
+    "bufif1",
+    "bus",
+    "case",
+    "casex",
+    "casez",
+    "cmos",
+    "component",
+    "configuration",
+    "constant",
+    "context",
+    "deassign",
+    "default",
+    "defparam",
+    "disable",
+    "disconnect",
+    "downto",
+    "edge",
+    "else",
+    "elsif",
+    "end",
+    "endcase",
+    "endfunction",
+    "endgenerate",
+    "endmodule",
+    "endprimitive",
+    "endspecify",
+    "endtable",
+    "endtask",
+    "entity",
+    "event",
+    "exit",
+    "file",
+    "for",
+    "force",
+    "forever",
+    "fork",
+    "function",
+    "generate",
+    "generic",
+    "genvar",
+    "group",
+    "guarded",
+    "highz0",
+    "highz1",
+    "ieee",
+    "if",
+    "ifnone",
+    "impure",
+    "in",
+    "inertial",
+    "initial",
+    "inout",
+    "input",
+    "integer",
+    "is",
+    "join",
+    "label",
+    "large",
+    "library",
+    "linkage",
+    "literal",
+    "localparam",
+    "loop",
+    "macromodule",
+    "map",
+    "medium",
+    "mod",
+    "module",
+    "nand",
+    "negedge",
+    "new",
+    "next",
+    "nmos",
+    "nor",
+    "not",
+    "notif0",
+    "notif1",
+    "null",
+    "of",
+    "on",
+    "open",
+    "or",
+    "others",
+    "out",
+    "output",
+    "package",
+    "parameter",
+    "pmos",
+    "port",
+    "posedge",
+    "postponed",
+    "primitive",
+    "procedure",
+    "process",
+    "protected",
+    "pull0",
+    "pull1",
+    "pulldown",
+    "pullup",
+    "pure",
+    "range",
+    "rcmos",
+    "real",
+    "realtime",
+    "record",
+    "reg",
+    "register",
+    "reject",
+    "release",
+    "rem",
+    "repeat",
+    "report",
+    "return",
+    "rising_edge",
+    "rnmos",
+    "rol",
+    "ror",
+    "rpmos",
+    "rtran",
+    "rtranif0",
+    "rtranif1",
+    "scalared",
+    "select",
+    "severity",
+    "shared",
+    "signal",
+    "signed",
+    "sla",
+    "sll",
+    "small",
+    "specify",
+    "specparam",
+    "sra",
+    "srl",
+    "std",
+    "std_logic",
+    "std_logic_vector",
+    "strong0",
+    "strong1",
+    "subtype",
+    "supply0",
+    "supply1",
+    "table",
+    "task",
+    "then",
+    "time",
+    "to",
+    "tran",
+    "tranif0",
+    "tranif1",
+    "transport",
+    "tri",
+    "tri0",
+    "tri1",
+    "triand",
+    "trior",
+    "trireg",
+    "type",
+    "unaffected",
+    "units",
+    "unsigned",
+    "until",
+    "use",
+    "variable",
+    "vectored",
+    "wait",
+    "wand",
+    "weak0",
+    "weak1",
+    "when",
+    "while",
+    "wire",
+    "with",
+    "wor",
+    "work",
+    "xnor",
+    "xor",
+];
+
+// hands out unique identifiers that are valid in every supported language
+pub struct Namer {
+    used: HashSet<String>,
+}
+
+impl Default for Namer {
+    fn default() -> Self {
+        Self {
+            used: RESERVED.iter().map(|word| word.to_string()).collect(),
+        }
+    }
+}
+
+impl Namer {
+    fn sanitize(raw: &str) -> String {
+        let mut name = String::with_capacity(raw.len());
+        for c in raw.chars() {
+            if c.is_ascii_alphanumeric() {
+                name.push(c.to_ascii_lowercase());
+            } else if !name.is_empty() && !name.ends_with('_') {
+                name.push('_');
+            }
+        }
+
+        let name = name.trim_end_matches('_');
+        match name.chars().next() {
+            None => String::from("n"),
+            Some(c) if c.is_ascii_digit() => format!("n_{name}"),
+            _ => name.to_string(),
+        }
+    }
+
+    pub fn name(&mut self, raw: &str) -> String {
+        let base = Self::sanitize(raw);
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while !self.used.insert(name.clone()) {
+            name = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        name
+    }
+}
+
+pub type NetID = usize;
+
+// destination of the inputs of a block
+enum Sink {
+    Cell(usize),
+    Port(usize),
+    // single pin of a module's output block (port, input index)
+    Pin(usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Net {
+    // internal signal driven by a cell output
+    Wire(String),
+    // single bit of an input port (port index, bit index)
+    Port(usize, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Input,
+    Output,
+}
+
+#[derive(Debug, Clone)]
+pub struct Port {
+    pub name: String,
+    pub direction: Direction,
+    // nets of all bits, least significant bit first; `None` for unconnected output bits
+    pub bits: Vec<Option<NetID>>,
+}
+
+impl Port {
+    pub fn width(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cell {
+    pub name: String,
+    pub module: String,
+    pub builtin: bool,
+    pub decoration: Decoration,
+    // nets of all inputs; `None` for unconnected inputs, which are tied to logic low
+    pub inputs: Vec<Option<NetID>>,
+    pub outputs: Vec<NetID>,
+}
+
+// how a cell is exported, shared by all backends so that they only differ in syntax
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    // instance of a custom module
+    Instance,
+    // builtin driving a constant value, bit `n` on output `n`; input devices inside of modules
+    // are `device`s fixed to their current value
+    Fixed { value: u128, device: bool },
+    // latch or flip-flop, its first output holds the stored state and starts out low
+    Register,
+    // any other builtin with a hardware equivalent, every backend writes all of them
+    Builtin,
+    // output devices inside of modules have no hardware equivalent
+    Unsupported,
+}
+
+impl Cell {
+    pub fn kind(&self) -> CellKind {
+        if !self.builtin {
+            return CellKind::Instance;
+        }
+
+        match self.module.as_str() {
+            "Low" => CellKind::Fixed {
+                value: 0,
+                device: false,
+            },
+            "High" => CellKind::Fixed {
+                value: 1,
+                device: false,
+            },
+            "Constant" => CellKind::Fixed {
+                value: self.decoration.output(),
+                device: false,
+            },
+            "Switch" | "Button" => CellKind::Fixed {
+                value: self.decoration.is_active() as u128,
+                device: true,
+            },
+            "Numeric Input" | "Hex Keypad" | "Keyboard" => CellKind::Fixed {
+                value: self.decoration.output(),
+                device: true,
+            },
+            "D Latch" | "SR Latch" | "SR Nand Latch" | "JK Latch" | "D Flip-Flop"
+            | "T Flip-Flop" => CellKind::Register,
+            "And" | "Nand" | "Or" | "Nor" | "Xor" | "Xnor" | "Not" | "Mux" | "Demux" | "Random" => {
+                CellKind::Builtin
+            }
+            _ => CellKind::Unsupported,
+        }
+    }
+}
+
+// flattened view of a single plot: ports, block instances and the nets between them
+#[derive(Debug, Clone)]
+pub struct Netlist {
+    pub name: String,
+    pub ports: Vec<Port>,
+    pub cells: Vec<Cell>,
+    pub nets: Vec<Net>,
+}
+
+impl Netlist {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            ports: vec![],
+            cells: vec![],
+            nets: vec![],
+        }
+    }
+
+    fn add_net(&mut self, net: Net) -> NetID {
+        self.nets.push(net);
+        self.nets.len() - 1
+    }
+
+    // identifier used to refer to a net inside the netlist's body
+    pub fn net_name(&self, net: NetID, index: impl Fn(&str, usize) -> String) -> String {
+        match &self.nets[net] {
+            Net::Wire(name) => name.clone(),
+            Net::Port(port, _) if self.ports[*port].width() == 1 => self.ports[*port].name.clone(),
+            Net::Port(port, bit) => index(&self.ports[*port].name, *bit),
+        }
+    }
+
+    // removes the given nets, which must not be in use anymore, and renumbers the remaining ones
+    fn remove_nets(&mut self, removed: &HashSet<NetID>) {
+        let mut ids = vec![None; self.nets.len()];
+        let mut nets = vec![];
+        for (id, net) in std::mem::take(&mut self.nets).into_iter().enumerate() {
+            if !removed.contains(&id) {
+                ids[id] = Some(nets.len());
+                nets.push(net);
+            }
+        }
+        self.nets = nets;
+
+        let renumber = |net: &mut Option<NetID>| *net = net.and_then(|net| ids[net]);
+        for port in self.ports.iter_mut() {
+            port.bits.iter_mut().for_each(renumber);
+        }
+        for cell in self.cells.iter_mut() {
+            cell.inputs.iter_mut().for_each(renumber);
+            cell.outputs
+                .iter_mut()
+                .for_each(|net| *net = ids[*net].expect("cell outputs are never removed"));
+        }
+    }
+
+    // nets holding the state of register cells
+    pub fn registers(&self) -> HashSet<NetID> {
+        self.cells
+            .iter()
+            .filter(|cell| cell.kind() == CellKind::Register)
+            .filter_map(|cell| cell.outputs.first().copied())
+            .collect()
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = &Port> {
+        self.ports
+            .iter()
+            .filter(|port| port.direction == Direction::Input)
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = &Port> {
+        self.ports
+            .iter()
+            .filter(|port| port.direction == Direction::Output)
+    }
+
+    fn from_plot(
+        project: &Project,
+        name: String,
+        plot: &Plot,
+        pin_names: Option<(&Vec<String>, &Vec<String>)>,
+        io_blocks: Option<(BlockID, BlockID)>,
+    ) -> Result<Self, String> {
+        let mut netlist = Self::new(name);
+        let mut namer = Namer::default();
+        let mut drivers: HashMap<(BlockID, u8), NetID> = HashMap::new();
+        // all blocks whose inputs still have to be resolved
+        let mut sinks: Vec<(&Block, Sink)> = vec![];
+
+        // sort blocks in reading order to get deterministic output
+        let mut blocks = plot.blocks().values().collect::<Vec<_>>();
+        blocks.sort_by_key(|block| {
+            let position = block.position();
+            (position.1, position.0, block.id())
+        });
+
+        let pin_name = |names: Option<&Vec<String>>, prefix: &str, index: usize| {
+            names
+                .and_then(|names| names.get(index))
+                .filter(|name| !name.trim().is_empty())
+                .cloned()
+                .unwrap_or_else(|| format!("{prefix}{index}"))
+        };
+
+        let is_top_port = |block: &Block, names: &[&str]| {
+            io_blocks.is_none() && names.contains(&block.module_id().as_str())
+        };
+
+        // input ports
+        for block in blocks.iter() {
+            if io_blocks.is_some_and(|(input, _)| input == block.id()) {
+                for index in 0..block.outputs().len() {
+                    let port = netlist.ports.len();
+                    let net = netlist.add_net(Net::Port(port, 0));
+                    netlist.ports.push(Port {
+                        name: namer.name(&pin_name(pin_names.map(|p| p.0), "in", index)),
+                        direction: Direction::Input,
+                        bits: vec![Some(net)],
+                    });
+                    drivers.insert((block.id(), index as u8), net);
+                }
+            } else if is_top_port(block, &TOP_INPUTS) {
+                let port = netlist.ports.len();
+                let bits = (0..block.outputs().len())
+                    .map(|bit| {
+                        let net = netlist.add_net(Net::Port(port, bit));
+                        drivers.insert((block.id(), bit as u8), net);
+                        Some(net)
+                    })
+                    .collect();
+                netlist.ports.push(Port {
+                    name: namer.name(block.label().unwrap_or(block.module_id())),
+                    direction: Direction::Input,
+                    bits,
+                });
+            }
+        }
+
+        // output ports
+        for block in blocks.iter() {
+            if io_blocks.is_some_and(|(_, output)| output == block.id()) {
+                for index in 0..block.inputs().len() {
+                    sinks.push((block, Sink::Pin(netlist.ports.len(), index)));
+                    netlist.ports.push(Port {
+                        name: namer.name(&pin_name(pin_names.map(|p| p.1), "out", index)),
+                        direction: Direction::Output,
+                        bits: vec![None],
+                    });
+                }
+            } else if is_top_port(block, &TOP_OUTPUTS) {
+                sinks.push((block, Sink::Port(netlist.ports.len())));
+                netlist.ports.push(Port {
+                    name: namer.name(block.label().unwrap_or(block.module_id())),
+                    direction: Direction::Output,
+                    bits: vec![None; block.inputs().len()],
+                });
+            }
+        }
+
+        // cells
+        for block in blocks.iter() {
+            if io_blocks.is_some_and(|(input, output)| input == block.id() || output == block.id())
+                || is_top_port(block, &TOP_INPUTS)
+                || is_top_port(block, &TOP_OUTPUTS)
+            {
+                continue;
+            }
+
+            let module = project
+                .module(block.module_id())
+                .ok_or_else(|| format!("Module `{}` does not exist", block.module_id()))?;
+            let name = namer.name(block.label().unwrap_or(block.module_id()));
+            let outputs = (0..block.outputs().len())
+                .map(|index| {
+                    let pin = match module.builtin() {
+                        true if block.outputs().len() == 1 => String::from("out"),
+                        true => format!("out{index}"),
+                        false => pin_name(Some(module.output_names()), "out", index),
+                    };
+                    let net = netlist.add_net(Net::Wire(namer.name(&format!("{name}_{pin}"))));
+                    drivers.insert((block.id(), index as u8), net);
+                    net
+                })
+                .collect();
+
+            sinks.push((block, Sink::Cell(netlist.cells.len())));
+            netlist.cells.push(Cell {
+                name,
+                module: module.name().clone(),
+                builtin: module.builtin(),
+                decoration: block.decoration().clone(),
+                inputs: vec![None; block.inputs().len()],
+                outputs,
+            });
+        }
+
+        // resolve the nets connected to all inputs
+        for (block, target) in sinks {
+            let nets = block
+                .inputs()
+                .iter()
+                .map(|connection| {
+                    connection
+                        .and_then(|id| plot.get_connection(&id))
+                        .and_then(|connection| match connection.origin() {
+                            PlotPort::Output(origin, index) => drivers.get(&(origin, index)),
+                            PlotPort::Input(..) => None,
+                        })
+                        .copied()
+                })
+                .collect::<Vec<_>>();
+
+            match target {
+                Sink::Cell(cell) => netlist.cells[cell].inputs = nets,
+                Sink::Port(port) => netlist.ports[port].bits = nets,
+                Sink::Pin(port, index) => netlist.ports[port].bits = vec![nets[index]],
+            }
+        }
+
+        Ok(netlist)
+    }
+}
+
+// netlists of all custom modules of a project, ordered so that every module
+// follows the modules it instantiates, and of the main plot as top-level design
+pub struct Design {
+    pub modules: Vec<Netlist>,
+    pub top: Netlist,
+    names: HashMap<String, usize>,
+}
+
+impl Design {
+    pub fn new(project: &Project, top_name: &str) -> Result<Self, String> {
+        let mut namer = Namer::default();
+        let top_name = namer.name(top_name);
+
+        let mut order = vec![];
+        let mut visited = HashSet::new();
+        let mut custom = project
+            .modules()
+            .values()
+            .filter(|module| !module.builtin())
+            .map(|module| module.name())
+            .collect::<Vec<_>>();
+        custom.sort();
+        custom
+            .into_iter()
+            .for_each(|name| Self::visit(project, name, &mut visited, &mut order));
+
+        let mut design = Self {
+            modules: vec![],
+            top: Netlist::new(String::new()),
+            names: HashMap::new(),
+        };
+
+        for name in order {
+            let module = project.module(&name).unwrap();
+            let plot = module
+                .plot()
+                .ok_or_else(|| format!("Module `{name}` has no circuit"))?;
+            let netlist = Netlist::from_plot(
+                project,
+                namer.name(&name),
+                plot,
+                Some((module.input_names(), module.output_names())),
+                module.io_blocks(),
+            )?;
+            design.names.insert(name, design.modules.len());
+            design.modules.push(netlist);
+        }
+
+        design.top = Netlist::from_plot(project, top_name, project.main_plot(), None, None)?;
+        Ok(design)
+    }
+
+    // depth-first search placing dependencies before the modules using them
+    fn visit(
+        project: &Project,
+        name: &String,
+        visited: &mut HashSet<String>,
+        order: &mut Vec<String>,
+    ) {
+        if !visited.insert(name.clone()) {
+            return;
+        }
+
+        if let Some(plot) = project.module(name).and_then(|module| module.plot()) {
+            let mut dependencies = plot
+                .blocks()
+                .values()
+                .map(|block| block.module_id())
+                .filter(|id| project.module(id).is_some_and(|module| !module.builtin()))
+                .collect::<Vec<_>>();
+            dependencies.sort();
+            dependencies
+                .into_iter()
+                .for_each(|dependency| Self::visit(project, dependency, visited, order));
+        }
+
+        order.push(name.clone());
+    }
+
+    // netlist of the custom module with the given (unsanitized) name
+    pub fn module(&self, name: &String) -> Option<&Netlist> {
+        self.names.get(name).map(|index| &self.modules[*index])
+    }
+
+    // top-level netlist with the contents of all module instances inlined, leaving only builtin
+    // cells; inlined cells and nets are prefixed with the name of their instance and a `.`
+    pub fn flatten(&self) -> Netlist {
+        let mut flattened: Vec<Netlist> = Vec::with_capacity(self.modules.len());
+        for module in self.modules.iter() {
+            let netlist = self.inline(module, &flattened);
+            flattened.push(netlist);
+        }
+        self.inline(&self.top, &flattened)
+    }
+
+    // replaces all instances in the netlist by the given flattened netlists of their modules
+    fn inline(&self, netlist: &Netlist, flattened: &[Netlist]) -> Netlist {
+        let mut result = Netlist {
+            name: netlist.name.clone(),
+            ports: netlist.ports.clone(),
+            cells: vec![],
+            nets: netlist.nets.clone(),
+        };
+        // nets driven by an instance output, replaced by the net driving the module's output pin
+        let mut aliases: HashMap<NetID, Option<NetID>> = HashMap::new();
+
+        for cell in netlist.cells.iter() {
+            let Some(module) = self
+                .names
+                .get(&cell.module)
+                .filter(|_| !cell.builtin)
+                .map(|index| &flattened[*index])
+            else {
+                result.cells.push(cell.clone());
+                continue;
+            };
+
+            // nets of the module in the surrounding netlist, `None` for unconnected inputs
+            let nets = module
+                .nets
+                .iter()
+                .map(|net| match net {
+                    Net::Port(port, _) => {
+                        let input = module.ports[..*port]
+                            .iter()
+                            .filter(|port| port.direction == Direction::Input)
+                            .count();
+                        cell.inputs.get(input).copied().flatten()
+                    }
+                    Net::Wire(name) => {
+                        Some(result.add_net(Net::Wire(format!("{}.{name}", cell.name))))
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            result.cells.extend(module.cells.iter().map(|inner| {
+                Cell {
+                    name: format!("{}.{}", cell.name, inner.name),
+                    inputs: inner
+                        .inputs
+                        .iter()
+                        .map(|net| net.and_then(|net| nets[net]))
+                        .collect(),
+                    outputs: inner
+                        .outputs
+                        .iter()
+                        .map(|net| nets[*net].expect("cell outputs are always wires"))
+                        .collect(),
+                    ..inner.clone()
+                }
+            }));
+
+            for (index, port) in module.outputs().enumerate() {
+                if let Some(net) = cell.outputs.get(index) {
+                    aliases.insert(*net, port.bits[0].and_then(|net| nets[net]));
+                }
+            }
+        }
+
+        // follow aliases through instances passing their inputs straight to their outputs
+        let resolve = |mut net: Option<NetID>| {
+            for _ in 0..=aliases.len() {
+                match net.and_then(|id| aliases.get(&id)) {
+                    Some(alias) => net = *alias,
+                    None => break,
+                }
+            }
+            net
+        };
+        result
+            .cells
+            .iter_mut()
+            .for_each(|cell| cell.inputs.iter_mut().for_each(|net| *net = resolve(*net)));
+        result
+            .ports
+            .iter_mut()
+            .filter(|port| port.direction == Direction::Output)
+            .for_each(|port| port.bits.iter_mut().for_each(|net| *net = resolve(*net)));
+
+        result.remove_nets(&aliases.into_keys().collect());
+        result
+    }
+}
+
+// builds the design of a project and writes the output of a backend to a file,
+// the top-level design is named after the file
+pub fn export_to(
+    project: &Project,
+    file: &gio::File,
+    generate: impl Fn(&Design) -> Result<String, std::fmt::Error>,
+) -> Result<(), String> {
+    let path = file.path().unwrap();
+    info!("Exporting to `{}`...", path.to_str().unwrap());
+
+    let top_name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("main");
+    let design = Design::new(project, top_name)?;
+    let text = generate(&design).map_err(|err| err.to_string())?;
+
+    let mut f = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(&path)
+        .map_err(|err| err.to_string())?;
+    let bytes_written = f.write(text.as_bytes()).map_err(|err| err.to_string())?;
+
+    info!(
+        "Wrote {bytes_written} bytes to `{}` successfully",
+        path.to_str().unwrap()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        export::verilog::VerilogFile,
+        renderer::vector::Vector2,
+        simulator::{Connection, Module},
+    };
+
+    // adds a block to the main plot, one row below the previous one
+    fn place(project: &mut Project, module: &str, label: &str) -> BlockID {
+        let module = project.module(&module.to_string()).unwrap().clone();
+        let plot = project.main_plot_mut();
+        let mut block = Block::new(&&module, Vector2(0, plot.blocks().len() as i32 * 100), None);
+        block.set_label(Some(label.to_string()));
+        let id = block.id();
+        plot.add_block(block);
+        id
+    }
+
+    fn connect(plot: &mut Plot, origin: BlockID, output: u8, destination: BlockID, input: u8) {
+        plot.add_connection(Connection::new_basic(origin, output, destination, input));
+    }
+
+    #[test]
+    fn reserved_names() {
+        let mut namer = Namer::default();
+        assert_eq!(namer.name("module"), "module_2");
+        assert_eq!(namer.name("Module"), "module_3");
+        assert_eq!(namer.name("signal"), "signal_2");
+        assert_eq!(namer.name("3 bit adder"), "n_3_bit_adder");
+        assert_eq!(namer.name("--"), "n");
+    }
+
+    #[test]
+    fn unconnected_inputs() {
+        let mut project = Project::default();
+        let switch = place(&mut project, "Switch", "a");
+        let and = place(&mut project, "And", "");
+        let lamp = place(&mut project, "Lamp", "begin");
+        connect(project.main_plot_mut(), switch, 0, and, 0);
+        connect(project.main_plot_mut(), and, 0, lamp, 0);
+
+        let design = Design::new(&project, "top").unwrap();
+        let top = &design.top;
+        let ports = top.ports.iter().map(|port| &port.name).collect::<Vec<_>>();
+        assert_eq!(ports, ["a", "begin_2"]);
+
+        // the unlabeled gate is named after its module, which is a Verilog keyword
+        assert_eq!(top.cells.len(), 1);
+        assert_eq!(top.cells[0].name, "and_2");
+        assert_eq!(top.cells[0].inputs, [top.ports[0].bits[0], None]);
+
+        let verilog = VerilogFile::generate(&design).unwrap();
+        assert!(verilog.contains("    and and_2 (and_2_out, a, 1'b0);"));
+        assert!(verilog.contains("    assign begin_2 = and_2_out;"));
+    }
+
+    #[test]
+    fn flatten_pass_through() {
+        let mut project = Project::default();
+        let name = String::from("pass");
+        project.add_module(Module::new(name.clone(), 1, 1));
+        let (input, output) = project.module(&name).unwrap().io_blocks().unwrap();
+        connect(project.plot_mut(&name).unwrap(), input, 0, output, 0);
+
+        let switch = place(&mut project, "Switch", "a");
+        let pass = place(&mut project, "pass", "");
+        let not = place(&mut project, "Not", "");
+        let lamp = place(&mut project, "Lamp", "y");
+        connect(project.main_plot_mut(), switch, 0, pass, 0);
+        connect(project.main_plot_mut(), pass, 0, not, 0);
+        connect(project.main_plot_mut(), not, 0, lamp, 0);
+
+        let design = Design::new(&project, "top").unwrap();
+        let module = design.module(&name).unwrap();
+        assert!(module.cells.is_empty());
+        assert_eq!(
+            module.outputs().next().unwrap().bits,
+            module.inputs().next().unwrap().bits
+        );
+
+        // the instance disappears and the gate is driven by the input port directly
+        let flattened = design.flatten();
+        assert_eq!(flattened.cells.len(), 1);
+        let not = &flattened.cells[0];
+        assert_eq!(not.module, "Not");
+        assert_eq!(not.inputs, [Some(0)]);
+        assert_eq!(
+            flattened.nets,
+            [Net::Port(0, 0), Net::Wire(String::from("not_2_out"))]
+        );
+        assert_eq!(flattened.ports[1].bits, [Some(not.outputs[0])]);
+    }
+}
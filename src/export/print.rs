@@ -0,0 +1,285 @@
+use crate::{
+    project::Project,
+    renderer::{vector::*, CairoRenderer, Renderer, Theme, COLOR_THEME, MINIMUM_SCALE},
+    simulator::Plot,
+};
+
+use gtk::{cairo, prelude::*};
+use std::{cell::Cell, rc::Rc};
+
+// space between the printable area and the drawn circuit, in points
+const MARGIN: f64 = 10.;
+const TITLE_BLOCK_HEIGHT: f64 = 36.;
+const TITLE_FONT_SIZE: f64 = 12.;
+const SUBTITLE_FONT_SIZE: f64 = 9.;
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum PrintLayout {
+    // shrink every circuit onto a single page
+    #[default]
+    FitToPage,
+    // print every circuit at its original size, spread over as many pages as needed
+    Tiled,
+}
+
+#[derive(Clone, Copy)]
+pub struct PrintOptions {
+    pub layout: PrintLayout,
+    pub title_block: bool,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            layout: PrintLayout::default(),
+            title_block: true,
+        }
+    }
+}
+
+impl PrintOptions {
+    // widget for the custom tab of the print dialog, keeps `options` up to date
+    pub fn widget(options: Rc<Cell<Self>>) -> gtk::Widget {
+        let current = options.get();
+        let fit_to_page = gtk::CheckButton::with_mnemonic("_Fit each circuit onto one page");
+        let tiled = gtk::CheckButton::with_mnemonic("_Tile circuits across pages at full size");
+        tiled.set_group(Some(&fit_to_page));
+        fit_to_page.set_active(current.layout == PrintLayout::FitToPage);
+        tiled.set_active(current.layout == PrintLayout::Tiled);
+
+        let title_block = gtk::CheckButton::with_mnemonic("Print a title _block on every page");
+        title_block.set_active(current.title_block);
+
+        fit_to_page.connect_toggled({
+            let options = options.clone();
+            move |button| {
+                options.set(Self {
+                    layout: match button.is_active() {
+                        true => PrintLayout::FitToPage,
+                        false => PrintLayout::Tiled,
+                    },
+                    ..options.get()
+                })
+            }
+        });
+        title_block.connect_toggled(move |button| {
+            options.set(Self {
+                title_block: button.is_active(),
+                ..options.get()
+            })
+        });
+
+        let layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        layout.append(&fit_to_page);
+        layout.append(&tiled);
+        layout.append(&title_block);
+        layout.upcast()
+    }
+}
+
+// one circuit to be printed
+struct Sheet {
+    name: String,
+    plot: Plot,
+    min: Vector2<f64>,
+    size: Vector2<f64>,
+}
+
+struct Page {
+    sheet: usize,
+    scale: f64,
+    // top-left corner of the page's drawing area in plot coordinates
+    origin: Vector2<f64>,
+    // column and row of the tile, if the sheet spans multiple pages
+    tile: Option<(usize, usize)>,
+}
+
+pub struct Printout {
+    project_name: String,
+    sheets: Vec<Sheet>,
+    pages: Vec<Page>,
+    options: PrintOptions,
+}
+
+impl Printout {
+    // collects the main plot and the plots of all custom modules, sorted by name
+    pub fn new(project: &Project, project_name: String) -> Result<Self, String> {
+        let mut modules = project
+            .modules()
+            .iter()
+            .filter_map(|(name, module)| module.plot().map(|plot| (name, plot)))
+            .collect::<Vec<_>>();
+        modules.sort_by(|a, b| a.0.cmp(b.0));
+
+        let sheets = [(String::from("Main"), project.main_plot())]
+            .into_iter()
+            .chain(
+                modules
+                    .into_iter()
+                    .map(|(name, plot)| (format!("Module: {name}"), plot)),
+            )
+            .filter_map(|(name, plot)| {
+                let mut plot = plot.clone();
+                plot.clear_highlights();
+                plot.bounds().map(|(min, max)| Sheet {
+                    name,
+                    plot,
+                    min: Vector2::cast(min),
+                    size: Vector2::cast(max - min),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if sheets.is_empty() {
+            return Err(String::from("There is nothing to print"));
+        }
+
+        Ok(Self {
+            project_name,
+            sheets,
+            pages: Vec::new(),
+            options: PrintOptions::default(),
+        })
+    }
+
+    // size of the area left for drawing the circuit on a page of the given size
+    fn drawing_area(&self, page_size: Vector2<f64>) -> Vector2<f64> {
+        let title_block = match self.options.title_block {
+            true => TITLE_BLOCK_HEIGHT + MARGIN,
+            false => 0.,
+        };
+        Vector2(
+            (page_size.0 - 2. * MARGIN).max(1.),
+            (page_size.1 - 2. * MARGIN - title_block).max(1.),
+        )
+    }
+
+    // lays out all sheets onto pages, returns the number of pages
+    pub fn paginate(&mut self, options: PrintOptions, page_size: Vector2<f64>) -> usize {
+        self.options = options;
+        let area = self.drawing_area(page_size);
+
+        self.pages = self
+            .sheets
+            .iter()
+            .enumerate()
+            .flat_map(|(i, sheet)| match options.layout {
+                PrintLayout::FitToPage => {
+                    let scale = (area.0 / sheet.size.0)
+                        .min(area.1 / sheet.size.1)
+                        .clamp(MINIMUM_SCALE, 1.);
+                    // center the circuit on the page
+                    let offset =
+                        (area - sheet.size * scale.into()) / Vector2(2. * scale, 2. * scale);
+                    vec![Page {
+                        sheet: i,
+                        scale,
+                        origin: sheet.min - offset,
+                        tile: None,
+                    }]
+                }
+                PrintLayout::Tiled => {
+                    let columns = (sheet.size.0 / area.0).ceil().max(1.) as usize;
+                    let rows = (sheet.size.1 / area.1).ceil().max(1.) as usize;
+                    (0..rows)
+                        .flat_map(|row| (0..columns).map(move |column| (column, row)))
+                        .map(|(column, row)| Page {
+                            sheet: i,
+                            scale: 1.,
+                            origin: sheet.min
+                                + Vector2(column as f64 * area.0, row as f64 * area.1),
+                            tile: (columns * rows > 1).then_some((column + 1, row + 1)),
+                        })
+                        .collect::<Vec<_>>()
+                }
+            })
+            .collect();
+
+        self.pages.len()
+    }
+
+    pub fn draw_page(
+        &self,
+        context: &cairo::Context,
+        page_size: Vector2<f64>,
+        page_nr: usize,
+    ) -> Result<(), cairo::Error> {
+        let Some(page) = self.pages.get(page_nr) else {
+            return Ok(());
+        };
+        let sheet = &self.sheets[page.sheet];
+        let area = self.drawing_area(page_size);
+
+        // paper is white, so always print with the light theme
+        Theme::LIGHT.with(|| {
+            if self.options.title_block {
+                self.draw_title_block(context, page_size, page_nr, page)?;
+            }
+
+            context.save()?;
+            context.rectangle(MARGIN, MARGIN, area.0, area.1);
+            context.clip();
+            context.translate(MARGIN, MARGIN);
+
+            let mut renderer = CairoRenderer::new();
+            renderer
+                .set_scale(page.scale)
+                .set_size(Vector2(area.0.ceil() as i32, area.1.ceil() as i32))
+                .translate(page.origin * (-page.scale).into());
+            renderer.render_plot(&sheet.plot, context, false)?;
+
+            context.restore()
+        })
+    }
+
+    fn draw_title_block(
+        &self,
+        context: &cairo::Context,
+        page_size: Vector2<f64>,
+        page_nr: usize,
+        page: &Page,
+    ) -> Result<(), cairo::Error> {
+        let sheet = &self.sheets[page.sheet];
+        let position = Vector2(MARGIN, page_size.1 - MARGIN - TITLE_BLOCK_HEIGHT);
+        let size = Vector2(page_size.0 - 2. * MARGIN, TITLE_BLOCK_HEIGHT);
+
+        let page_label = match page.tile {
+            Some((column, row)) => format!(
+                "Page {} of {} (column {column}, row {row})",
+                page_nr + 1,
+                self.pages.len()
+            ),
+            None => format!("Page {} of {}", page_nr + 1, self.pages.len()),
+        };
+
+        let renderer = CairoRenderer::for_context(context.clone());
+        renderer
+            .rectangle(Vector2::cast(position), Vector2::cast(size))
+            .set_line_width(1.)
+            .set_color(unsafe { &COLOR_THEME.block_fg_color })
+            .stroke()?;
+
+        renderer
+            .set_font_size(TITLE_FONT_SIZE)
+            .move_to(Vector2::cast(position + Vector2(8., 15.)))
+            .show_text(&self.project_name)?
+            .set_font_size(SUBTITLE_FONT_SIZE)
+            .move_to(Vector2::cast(position + Vector2(8., 29.)))
+            .show_text(&sheet.name)?;
+
+        let extents = context.text_extents(&page_label)?;
+        renderer
+            .move_to(Vector2::cast(
+                position + Vector2(size.0 - 8. - extents.x_advance(), 29.),
+            ))
+            .show_text(&page_label)
+            .map(|_| ())
+    }
+}
@@ -0,0 +1,308 @@
+use super::{
+    netlist::{self, Cell, CellKind, Design, Direction, Net, NetID, Netlist},
+    verilog_import, ModuleFile,
+};
+use crate::{
+    project::Project,
+    simulator::{builtin::random_state, Decoration},
+    FileExtension,
+};
+
+use gtk::{gio, prelude::FileExt};
+use std::{fmt::Write, fs};
+
+pub struct VerilogFile;
+
+impl FileExtension for VerilogFile {
+    const FILE_EXTENSION: &'static str = "v";
+    const FILE_PATTERN: &'static str = "*.v";
+
+    fn file_filter() -> gtk::FileFilter {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Verilog files"));
+        filter.add_pattern(Self::FILE_PATTERN);
+        filter
+    }
+}
+
+impl VerilogFile {
+    // writes all modules of the project and the main plot as top-level module named after the file
+    pub fn export(project: &Project, file: &gio::File) -> Result<(), String> {
+        netlist::export_to(project, file, Self::generate)
+    }
+
+    // reads a structural Verilog netlist, creating one module per Verilog module
+    pub fn import(file: &gio::File) -> Result<ModuleFile, String> {
+        let path = file.path().unwrap();
+        let source = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+        let mod_file = verilog_import::import(&source)?;
+
+        info!("Imported Verilog netlist `{}`", path.to_str().unwrap());
+        Ok(mod_file)
+    }
+
+    pub fn generate(design: &Design) -> Result<String, std::fmt::Error> {
+        let mut out = String::from("// Generated by LogicRs\n");
+        for netlist in design.modules.iter().chain([&design.top]) {
+            writeln!(out)?;
+            Self::write_module(&mut out, design, netlist)?;
+        }
+        Ok(out)
+    }
+
+    fn net(netlist: &Netlist, net: NetID) -> String {
+        netlist.net_name(net, |name, bit| format!("{name}[{bit}]"))
+    }
+
+    // expression driving the given input of a cell, unconnected inputs are tied to logic low
+    fn input(netlist: &Netlist, cell: &Cell, index: usize) -> String {
+        cell.inputs
+            .get(index)
+            .copied()
+            .flatten()
+            .map(|net| Self::net(netlist, net))
+            .unwrap_or_else(|| String::from("1'b0"))
+    }
+
+    fn bit(value: u128, index: usize) -> &'static str {
+        match (value >> index) & 1 {
+            0 => "1'b0",
+            _ => "1'b1",
+        }
+    }
+
+    fn write_module(out: &mut String, design: &Design, netlist: &Netlist) -> std::fmt::Result {
+        let ports = netlist
+            .ports
+            .iter()
+            .map(|port| {
+                let direction = match port.direction {
+                    Direction::Input => "input",
+                    Direction::Output => "output",
+                };
+                match port.width() {
+                    1 => format!("    {direction} wire {}", port.name),
+                    width => format!("    {direction} wire [{}:0] {}", width - 1, port.name),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match ports.is_empty() {
+            true => writeln!(out, "module {};", netlist.name)?,
+            false => writeln!(out, "module {} (\n{}\n);", netlist.name, ports.join(",\n"))?,
+        }
+
+        // outputs of registers are assigned in always blocks
+        let registers = netlist.registers();
+
+        let mut declared = false;
+        for (id, net) in netlist.nets.iter().enumerate() {
+            if let Net::Wire(name) = net {
+                match registers.contains(&id) {
+                    true => writeln!(out, "    reg {name} = 1'b0;")?,
+                    false => writeln!(out, "    wire {name};")?,
+                }
+                declared = true;
+            }
+        }
+
+        if declared && !netlist.cells.is_empty() {
+            writeln!(out)?;
+        }
+        for cell in netlist.cells.iter() {
+            declared = true;
+            match cell.kind() {
+                CellKind::Instance => Self::write_instance(out, design, netlist, cell)?,
+                CellKind::Fixed { value, device } => {
+                    if device {
+                        writeln!(
+                            out,
+                            "    // {}: {} fixed to its current value",
+                            cell.name, cell.module
+                        )?;
+                    }
+                    for (index, net) in cell.outputs.iter().enumerate() {
+                        writeln!(
+                            out,
+                            "    assign {} = {};",
+                            Self::net(netlist, *net),
+                            Self::bit(value, index)
+                        )?;
+                    }
+                }
+                CellKind::Register | CellKind::Builtin => Self::write_builtin(out, netlist, cell)?,
+                CellKind::Unsupported => {
+                    writeln!(out, "    // {}: {} is not exported", cell.name, cell.module)?
+                }
+            }
+        }
+
+        let assignments = netlist
+            .outputs()
+            .flat_map(|port| {
+                port.bits.iter().enumerate().map(|(bit, net)| {
+                    let target = match port.width() {
+                        1 => port.name.clone(),
+                        _ => format!("{}[{bit}]", port.name),
+                    };
+                    let source = net
+                        .map(|net| Self::net(netlist, net))
+                        .unwrap_or_else(|| String::from("1'b0"));
+                    format!("    assign {target} = {source};")
+                })
+            })
+            .collect::<Vec<_>>();
+        if !assignments.is_empty() {
+            if declared {
+                writeln!(out)?;
+            }
+            writeln!(out, "{}", assignments.join("\n"))?;
+        }
+
+        writeln!(out, "endmodule")
+    }
+
+    fn write_builtin(out: &mut String, netlist: &Netlist, cell: &Cell) -> std::fmt::Result {
+        let input = |index| Self::input(netlist, cell, index);
+        let output = |index: usize| Self::net(netlist, cell.outputs[index]);
+        let name = &cell.name;
+
+        match cell.module.as_str() {
+            gate @ ("And" | "Nand" | "Or" | "Nor" | "Xor" | "Xnor") => writeln!(
+                out,
+                "    {} {name} ({}, {}, {});",
+                gate.to_lowercase(),
+                output(0),
+                input(0),
+                input(1)
+            ),
+            "Not" => writeln!(out, "    not {name} ({}, {});", output(0), input(0)),
+            "Mux" => writeln!(
+                out,
+                "    assign {} = {} ? {} : {};",
+                output(0),
+                input(2),
+                input(1),
+                input(0)
+            ),
+            "Demux" => {
+                writeln!(out, "    assign {} = {} & ~{};", output(0), input(0), input(1))?;
+                writeln!(out, "    assign {} = {} & {};", output(1), input(0), input(1))
+            }
+            "D Latch" => writeln!(
+                out,
+                "    always @(*)\n        if ({}) {} <= {};",
+                input(1),
+                output(0),
+                input(0)
+            ),
+            "SR Latch" => writeln!(
+                out,
+                "    always @(*)\n        if ({r}) {q} <= 1'b0;\n        else if ({s}) {q} <= 1'b1;",
+                q = output(0),
+                s = input(0),
+                r = input(1)
+            ),
+            "SR Nand Latch" => {
+                writeln!(
+                    out,
+                    "    always @(*)\n        if (!{s}) {q} <= 1'b1;\n        else if (!{r}) {q} <= 1'b0;",
+                    q = output(0),
+                    s = input(0),
+                    r = input(1)
+                )?;
+                writeln!(out, "    assign {} = ~{};", output(1), output(0))
+            }
+            "JK Latch" => writeln!(
+                out,
+                "    // {name}: toggles once per change of J or K like the simulator, not synthesizable\n    always @({j} or {k})\n        if ({j} && {k}) {q} <= ~{q};\n        else if ({j}) {q} <= 1'b1;\n        else if ({k}) {q} <= 1'b0;",
+                q = output(0),
+                j = input(0),
+                k = input(1)
+            ),
+            "D Flip-Flop" => {
+                writeln!(
+                    out,
+                    "    always @(posedge {})\n        {} <= {};",
+                    input(1),
+                    output(0),
+                    input(0)
+                )?;
+                writeln!(out, "    assign {} = ~{};", output(1), output(0))
+            }
+            "T Flip-Flop" => {
+                writeln!(
+                    out,
+                    "    always @(posedge {clk})\n        if ({t}) {q} <= ~{q};",
+                    q = output(0),
+                    t = input(0),
+                    clk = input(1)
+                )?;
+                writeln!(out, "    assign {} = ~{};", output(1), output(0))
+            }
+            "Random" => {
+                let seed = match cell.decoration {
+                    Decoration::Random(_, seed) => seed,
+                    _ => 0,
+                };
+                writeln!(out, "    reg [63:0] {name} = 64'h{:016x};", random_state(seed))?;
+                match cell.inputs.first().copied().flatten() {
+                    Some(clock) => writeln!(
+                        out,
+                        "    always @(posedge {}) begin\n        {name} = {name} ^ ({name} << 13);\n        {name} = {name} ^ ({name} >> 7);\n        {name} = {name} ^ ({name} << 17);\n    end",
+                        Self::net(netlist, clock)
+                    )?,
+                    // the simulator draws a new value every tick, which has no clock to follow here
+                    None => writeln!(
+                        out,
+                        "    // {name}: no clock connected, keeps its first value instead of changing every tick"
+                    )?,
+                }
+                for index in 0..cell.outputs.len() {
+                    writeln!(out, "    assign {} = {name}[{index}];", output(index))?;
+                }
+                Ok(())
+            }
+            module => unreachable!("`{module}` is not a register or builtin"),
+        }
+    }
+
+    fn write_instance(
+        out: &mut String,
+        design: &Design,
+        netlist: &Netlist,
+        cell: &Cell,
+    ) -> std::fmt::Result {
+        let Some(module) = design.module(&cell.module) else {
+            return writeln!(
+                out,
+                "    // {}: unknown module `{}`",
+                cell.name, cell.module
+            );
+        };
+
+        let inputs = module.inputs().enumerate().map(|(index, port)| {
+            format!(
+                "        .{}({})",
+                port.name,
+                Self::input(netlist, cell, index)
+            )
+        });
+        let outputs = module.outputs().enumerate().map(|(index, port)| {
+            let net = cell
+                .outputs
+                .get(index)
+                .map(|net| Self::net(netlist, *net))
+                .unwrap_or_default();
+            format!("        .{}({net})", port.name)
+        });
+
+        writeln!(
+            out,
+            "    {} {} (\n{}\n    );",
+            module.name,
+            cell.name,
+            inputs.chain(outputs).collect::<Vec<_>>().join(",\n")
+        )
+    }
+}
@@ -0,0 +1,1653 @@
+use super::ModuleFile;
+use crate::{
+    application::editor::GRID_SIZE,
+    renderer::vector::Vector2,
+    simulator::{builtin::BUILTINS, Block, BlockID, Connection, Module, Plot, Port, Segment},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+// space between the columns and rows of auto-placed blocks
+const COLUMN_GAP: i32 = 4 * GRID_SIZE;
+const ROW_GAP: i32 = 2 * GRID_SIZE;
+const MARGIN: i32 = 2 * GRID_SIZE;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    // width (if sized) and value of a number literal, unknown bits (x, z) are read as 0
+    Number(Option<usize>, u128),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(name) => write!(f, "`{name}`"),
+            Self::Number(_, value) => write!(f, "`{value}`"),
+            Self::Symbol(symbol) => write!(f, "`{symbol}`"),
+        }
+    }
+}
+
+// longer symbols first, so that they take precedence over their prefixes
+const SYMBOLS: [&str; 23] = [
+    "~^", "^~", "<=", "(", ")", "[", "]", "{", "}", ",", ";", ":", ".", "=", "~", "&", "|", "^",
+    "?", "!", "#", "@", "*",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    let mut line = 1;
+
+    // skips everything up to and including the given terminator, returns false at the end of the source
+    let skip_until = |i: &mut usize, line: &mut usize, terminator: &[char]| {
+        while *i < chars.len() && !chars[*i..].starts_with(terminator) {
+            if chars[*i] == '\n' {
+                *line += 1;
+            }
+            *i += 1;
+        }
+        *i += terminator.len();
+        *i <= chars.len()
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+            }
+            c if c.is_whitespace() => i += 1,
+            // line comments may end with the source
+            '/' if next == Some('/') => {
+                skip_until(&mut i, &mut line, &['\n']);
+            }
+            '/' if next == Some('*') => {
+                let start = line;
+                i += 2;
+                if !skip_until(&mut i, &mut line, &['*', '/']) {
+                    return Err(format!("line {start}: unterminated comment"));
+                }
+            }
+            // attributes
+            '(' if next == Some('*') && chars.get(i + 2) != Some(&')') => {
+                let start = line;
+                i += 2;
+                if !skip_until(&mut i, &mut line, &['*', ')']) {
+                    return Err(format!("line {start}: unterminated attribute"));
+                }
+            }
+            // compiler directives
+            '`' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '\\' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && !chars[i].is_whitespace() {
+                    i += 1;
+                }
+                tokens.push((Token::Ident(chars[start..i].iter().collect()), line));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+                {
+                    i += 1;
+                }
+                tokens.push((Token::Ident(chars[start..i].iter().collect()), line));
+            }
+            c if c.is_ascii_digit() || c == '\'' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '_') {
+                    i += 1;
+                }
+                let decimal =
+                    chars[start..i]
+                        .iter()
+                        .filter(|c| **c != '_')
+                        .fold(0u128, |value, c| {
+                            value
+                                .wrapping_mul(10)
+                                .wrapping_add(c.to_digit(10).unwrap() as u128)
+                        });
+
+                if chars.get(i) != Some(&'\'') {
+                    tokens.push((Token::Number(None, decimal), line));
+                    continue;
+                }
+
+                i += 1;
+                if matches!(chars.get(i), Some('s' | 'S')) {
+                    i += 1;
+                }
+                let radix = match chars.get(i).map(|c| c.to_ascii_lowercase()) {
+                    Some('b') => 2,
+                    Some('o') => 8,
+                    Some('d') => 10,
+                    Some('h') => 16,
+                    _ => return Err(format!("line {line}: invalid number literal")),
+                };
+                i += 1;
+
+                let digits_start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '?')
+                {
+                    i += 1;
+                }
+                let mut value = 0u128;
+                for digit in chars[digits_start..i].iter().filter(|c| **c != '_') {
+                    let digit = match digit.to_ascii_lowercase() {
+                        'x' | 'z' | '?' => 0,
+                        digit => digit
+                            .to_digit(radix)
+                            .ok_or_else(|| format!("line {line}: invalid digit `{digit}`"))?,
+                    };
+                    value = value
+                        .wrapping_mul(radix as u128)
+                        .wrapping_add(digit as u128);
+                }
+
+                let width = (chars[start] != '\'').then_some(decimal as usize);
+                tokens.push((Token::Number(width, value), line));
+            }
+            _ => {
+                let rest = chars[i..].iter().take(2).collect::<String>();
+                let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) else {
+                    return Err(format!("line {line}: unexpected character `{c}`"));
+                };
+                i += symbol.len();
+                tokens.push((Token::Symbol(*symbol), line));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Input,
+    Output,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Ident(String),
+    Index(String, i64),
+    Range(String, i64, i64),
+    Number(Option<usize>, u128),
+    Concat(Vec<Expr>),
+    Repeat(usize, Vec<Expr>),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Connections {
+    Ordered(Vec<Option<Expr>>),
+    Named(Vec<(String, Option<Expr>)>),
+}
+
+#[derive(Debug)]
+enum Item {
+    Assign(Expr, Expr),
+    // gate primitive with its output(s) first
+    Gate(String, Vec<Expr>),
+    Instance(String, Connections),
+    // clock, rising edge, non-blocking assignments
+    Flop(Expr, bool, Vec<(Expr, Expr)>),
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Net {
+    direction: Option<Direction>,
+    // most and least significant bit index
+    range: Option<(i64, i64)>,
+}
+
+#[derive(Debug)]
+struct Definition {
+    name: String,
+    ports: Vec<String>,
+    nets: HashMap<String, Net>,
+    items: Vec<(Item, usize)>,
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map(|(_, line)| *line)
+            .unwrap_or(1)
+    }
+
+    fn error<T>(&self, message: impl fmt::Display) -> Result<T, String> {
+        Err(format!("line {}: {message}", self.line()))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(word)) if word == keyword)
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = self.is_symbol(symbol);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), String> {
+        match self.eat_symbol(symbol) {
+            true => Ok(()),
+            false => self.error(format!("expected `{symbol}`")),
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                self.position += 1;
+                Ok(name)
+            }
+            _ => self.error("expected an identifier"),
+        }
+    }
+
+    fn expect_number(&mut self) -> Result<u128, String> {
+        match self.next() {
+            Some(Token::Number(_, value)) => Ok(value),
+            _ => self.error("expected a number"),
+        }
+    }
+
+    // skips to the parenthesis matching an already consumed `(`
+    fn skip_parenthesized(&mut self) -> Result<(), String> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(Token::Symbol("(")) => depth += 1,
+                Some(Token::Symbol(")")) => depth -= 1,
+                None => return self.error("unexpected end of file"),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_statement(&mut self) -> Result<(), String> {
+        if !self.eat_keyword("begin") {
+            while !self.eat_symbol(";") {
+                if self.next().is_none() {
+                    return self.error("unexpected end of file");
+                }
+            }
+            return Ok(());
+        }
+
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(Token::Ident(word)) if word == "begin" => depth += 1,
+                Some(Token::Ident(word)) if word == "end" => depth -= 1,
+                None => return self.error("unexpected end of file"),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn definitions(&mut self) -> Result<Vec<Definition>, String> {
+        let mut definitions = vec![];
+        while let Some(token) = self.next() {
+            match token {
+                Token::Ident(word) if word == "module" => definitions.push(self.definition()?),
+                token => return self.error(format!("expected `module`, found {token}")),
+            }
+        }
+        Ok(definitions)
+    }
+
+    fn direction(&mut self) -> Result<Option<Direction>, String> {
+        if self.eat_keyword("input") {
+            Ok(Some(Direction::Input))
+        } else if self.eat_keyword("output") {
+            Ok(Some(Direction::Output))
+        } else if self.is_keyword("inout") {
+            self.error("bidirectional ports are not supported")
+        } else {
+            Ok(None)
+        }
+    }
+
+    // optional net type, signedness and bit range of a declaration
+    fn net_range(&mut self) -> Result<Option<(i64, i64)>, String> {
+        for keyword in ["wire", "reg", "logic", "signed"] {
+            self.eat_keyword(keyword);
+        }
+
+        if !self.eat_symbol("[") {
+            return Ok(None);
+        }
+        let msb = self.expect_number()? as i64;
+        self.expect_symbol(":")?;
+        let lsb = self.expect_number()? as i64;
+        self.expect_symbol("]")?;
+        Ok(Some((msb, lsb)))
+    }
+
+    fn definition(&mut self) -> Result<Definition, String> {
+        let mut definition = Definition {
+            name: self.expect_ident()?,
+            ports: vec![],
+            nets: HashMap::new(),
+            items: vec![],
+        };
+
+        if self.eat_symbol("#") {
+            self.expect_symbol("(")?;
+            self.skip_parenthesized()?;
+        }
+
+        if self.eat_symbol("(") && !self.eat_symbol(")") {
+            // ANSI-style port declarations carry on until the next direction
+            let mut declaration = None;
+            loop {
+                if let Some(direction) = self.direction()? {
+                    declaration = Some(Net {
+                        direction: Some(direction),
+                        range: self.net_range()?,
+                    });
+                }
+
+                let port = self.expect_ident()?;
+                if let Some(declaration) = declaration {
+                    definition.nets.insert(port.clone(), declaration);
+                }
+                definition.ports.push(port);
+
+                if !self.eat_symbol(",") {
+                    self.expect_symbol(")")?;
+                    break;
+                }
+            }
+        }
+        self.expect_symbol(";")?;
+
+        loop {
+            let line = self.line();
+            let Some(token) = self.next() else {
+                return self.error("missing `endmodule`");
+            };
+            let Token::Ident(word) = token else {
+                return self.error(format!("unexpected {token}"));
+            };
+
+            match word.as_str() {
+                "endmodule" => return Ok(definition),
+                "input" | "output" | "inout" => {
+                    self.position -= 1;
+                    let direction = self.direction()?;
+                    let range = self.net_range()?;
+                    loop {
+                        let net = definition.nets.entry(self.expect_ident()?).or_default();
+                        net.direction = direction;
+                        net.range = range;
+                        if !self.eat_symbol(",") {
+                            break;
+                        }
+                    }
+                    self.expect_symbol(";")?;
+                }
+                "wire" | "reg" | "logic" | "tri" | "supply0" | "supply1" => {
+                    let range = self.net_range()?;
+                    loop {
+                        let name = self.expect_ident()?;
+                        definition.nets.entry(name.clone()).or_default().range = range;
+
+                        let value = match word.as_str() {
+                            "supply0" => Some(Expr::Number(Some(1), 0)),
+                            "supply1" => Some(Expr::Number(Some(1), 1)),
+                            _ if self.eat_symbol("=") => Some(self.expr()?),
+                            _ => None,
+                        };
+                        // initial values of registers are ignored
+                        if let Some(value) = value
+                            && word != "reg"
+                        {
+                            definition
+                                .items
+                                .push((Item::Assign(Expr::Ident(name), value), line));
+                        }
+
+                        if !self.eat_symbol(",") {
+                            break;
+                        }
+                    }
+                    self.expect_symbol(";")?;
+                }
+                "assign" => {
+                    loop {
+                        let target = self.expr()?;
+                        self.expect_symbol("=")?;
+                        let value = self.expr()?;
+                        definition.items.push((Item::Assign(target, value), line));
+                        if !self.eat_symbol(",") {
+                            break;
+                        }
+                    }
+                    self.expect_symbol(";")?;
+                }
+                "and" | "or" | "nand" | "nor" | "xor" | "xnor" | "not" | "buf" => {
+                    // gate delays are irrelevant for the simulation
+                    if self.eat_symbol("#") {
+                        match self.eat_symbol("(") {
+                            true => self.skip_parenthesized()?,
+                            false => self.position += 1,
+                        }
+                    }
+
+                    loop {
+                        if matches!(self.peek(), Some(Token::Ident(_))) {
+                            self.position += 1;
+                        }
+                        self.expect_symbol("(")?;
+                        let mut terminals = vec![self.expr()?];
+                        while self.eat_symbol(",") {
+                            terminals.push(self.expr()?);
+                        }
+                        self.expect_symbol(")")?;
+                        definition
+                            .items
+                            .push((Item::Gate(word.clone(), terminals), line));
+
+                        if !self.eat_symbol(",") {
+                            break;
+                        }
+                    }
+                    self.expect_symbol(";")?;
+                }
+                "always" => {
+                    self.expect_symbol("@")?;
+                    self.expect_symbol("(")?;
+                    let rising = if self.eat_keyword("posedge") {
+                        true
+                    } else if self.eat_keyword("negedge") {
+                        false
+                    } else {
+                        return self.error("only edge-triggered `always` blocks are supported");
+                    };
+                    let clock = self.expr()?;
+                    self.expect_symbol(")")?;
+
+                    let mut assignments = vec![];
+                    self.flop_statement(&mut assignments)?;
+                    definition
+                        .items
+                        .push((Item::Flop(clock, rising, assignments), line));
+                }
+                "initial" => self.skip_statement()?,
+                "parameter" | "localparam" | "defparam" => {
+                    while !self.eat_symbol(";") {
+                        if self.next().is_none() {
+                            return self.error("unexpected end of file");
+                        }
+                    }
+                }
+                "function" | "task" | "generate" | "specify" | "primitive" => {
+                    return self.error(format!("`{word}` is not supported"))
+                }
+                _ => {
+                    if self.eat_symbol("#") {
+                        self.expect_symbol("(")?;
+                        self.skip_parenthesized()?;
+                    }
+
+                    loop {
+                        self.expect_ident()?;
+                        if self.is_symbol("[") {
+                            return self.error("arrays of instances are not supported");
+                        }
+                        self.expect_symbol("(")?;
+                        let connections = self.connections()?;
+                        definition
+                            .items
+                            .push((Item::Instance(word.clone(), connections), line));
+
+                        if !self.eat_symbol(",") {
+                            break;
+                        }
+                    }
+                    self.expect_symbol(";")?;
+                }
+            }
+        }
+    }
+
+    fn flop_statement(&mut self, assignments: &mut Vec<(Expr, Expr)>) -> Result<(), String> {
+        if self.eat_keyword("begin") {
+            if self.eat_symbol(":") {
+                self.expect_ident()?;
+            }
+            while !self.eat_keyword("end") {
+                self.flop_statement(assignments)?;
+            }
+            return Ok(());
+        }
+
+        if matches!(self.peek(), Some(Token::Ident(word)) if matches!(word.as_str(), "if" | "case" | "for"))
+        {
+            return self.error("only plain assignments are supported in `always` blocks");
+        }
+
+        let target = self.expr()?;
+        if !self.eat_symbol("<=") {
+            self.expect_symbol("=")?;
+        }
+        let value = self.expr()?;
+        self.expect_symbol(";")?;
+        assignments.push((target, value));
+        Ok(())
+    }
+
+    // port connections of an instance, after the opening parenthesis
+    fn connections(&mut self) -> Result<Connections, String> {
+        if self.eat_symbol(")") {
+            return Ok(Connections::Ordered(vec![]));
+        }
+
+        if self.is_symbol(".") {
+            let mut connections = vec![];
+            loop {
+                self.expect_symbol(".")?;
+                let port = self.expect_ident()?;
+                self.expect_symbol("(")?;
+                let expr = match self.eat_symbol(")") {
+                    true => None,
+                    false => {
+                        let expr = self.expr()?;
+                        self.expect_symbol(")")?;
+                        Some(expr)
+                    }
+                };
+                connections.push((port, expr));
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+            self.expect_symbol(")")?;
+            return Ok(Connections::Named(connections));
+        }
+
+        let mut connections = vec![];
+        loop {
+            match self.is_symbol(",") || self.is_symbol(")") {
+                true => connections.push(None),
+                false => connections.push(Some(self.expr()?)),
+            }
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        self.expect_symbol(")")?;
+        Ok(Connections::Ordered(connections))
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let condition = self.binary(0)?;
+        if !self.eat_symbol("?") {
+            return Ok(condition);
+        }
+        let then = self.expr()?;
+        self.expect_symbol(":")?;
+        let otherwise = self.expr()?;
+        Ok(Expr::Ternary(
+            Box::new(condition),
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
+    // binary operators by increasing precedence
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        const LEVELS: [&[&str]; 3] = [&["|"], &["^", "~^", "^~"], &["&"]];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(Token::Symbol(symbol)) = self.peek()
+            && LEVELS[level].contains(symbol)
+        {
+            let symbol = *symbol;
+            self.position += 1;
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(symbol, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        for symbol in ["~", "!"] {
+            if self.eat_symbol(symbol) {
+                return Ok(Expr::Unary(symbol, Box::new(self.unary()?)));
+            }
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(width, value)) => Ok(Expr::Number(width, value)),
+            Some(Token::Ident(name)) => {
+                if !self.eat_symbol("[") {
+                    return Ok(Expr::Ident(name));
+                }
+                let msb = self.expect_number()? as i64;
+                let expr = match self.eat_symbol(":") {
+                    true => Expr::Range(name, msb, self.expect_number()? as i64),
+                    false => Expr::Index(name, msb),
+                };
+                self.expect_symbol("]")?;
+                Ok(expr)
+            }
+            Some(Token::Symbol("(")) => {
+                let expr = self.expr()?;
+                self.expect_symbol(")")?;
+                Ok(expr)
+            }
+            Some(Token::Symbol("{")) => {
+                let first = self.expr()?;
+                if let Expr::Number(_, count) = first
+                    && self.eat_symbol("{")
+                {
+                    let items = self.concatenation()?;
+                    self.expect_symbol("}")?;
+                    return Ok(Expr::Repeat(count as usize, items));
+                }
+
+                let mut items = vec![first];
+                while self.eat_symbol(",") {
+                    items.push(self.expr()?);
+                }
+                self.expect_symbol("}")?;
+                Ok(Expr::Concat(items))
+            }
+            Some(token) => {
+                self.position -= 1;
+                self.error(format!("unexpected {token}"))
+            }
+            None => self.error("unexpected end of file"),
+        }
+    }
+
+    // items of a concatenation, after the opening brace
+    fn concatenation(&mut self) -> Result<Vec<Expr>, String> {
+        let mut items = vec![self.expr()?];
+        while self.eat_symbol(",") {
+            items.push(self.expr()?);
+        }
+        self.expect_symbol("}")?;
+        Ok(items)
+    }
+}
+
+// bit indices of a net, from the least to the most significant bit
+fn indices(range: Option<(i64, i64)>) -> Vec<i64> {
+    match range {
+        None => vec![0],
+        Some((msb, lsb)) if msb >= lsb => (lsb..=msb).collect(),
+        Some((msb, lsb)) => (msb..=lsb).rev().collect(),
+    }
+}
+
+// ports of a definition with their bit indices, in declaration order
+struct Interface {
+    ports: Vec<(String, Direction, Option<(i64, i64)>)>,
+}
+
+impl Interface {
+    fn new(definition: &Definition) -> Result<Self, String> {
+        let ports = definition
+            .ports
+            .iter()
+            .map(|port| match definition.nets.get(port) {
+                Some(Net {
+                    direction: Some(direction),
+                    range,
+                }) => Ok((port.clone(), *direction, *range)),
+                _ => Err(format!(
+                    "port `{port}` of module `{}` has no direction",
+                    definition.name
+                )),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { ports })
+    }
+
+    fn ports_in(
+        &self,
+        direction: Direction,
+    ) -> impl Iterator<Item = &(String, Direction, Option<(i64, i64)>)> {
+        self.ports
+            .iter()
+            .filter(move |(_, port_direction, _)| *port_direction == direction)
+    }
+
+    // (net, bit index, pin name) of all pins in the given direction
+    fn pins(&self, direction: Direction) -> Vec<(String, i64, String)> {
+        self.ports_in(direction)
+            .flat_map(|(name, _, range)| {
+                indices(*range).into_iter().map(move |index| match range {
+                    Some(_) => (name.clone(), index, format!("{name}[{index}]")),
+                    None => (name.clone(), index, name.clone()),
+                })
+            })
+            .collect()
+    }
+
+    fn width(&self, direction: Direction) -> usize {
+        self.ports_in(direction)
+            .map(|(_, _, range)| indices(*range).len())
+            .sum()
+    }
+}
+
+// output of a cell (cell index, output index)
+type Pin = (usize, u8);
+
+#[derive(Debug, Clone, Copy)]
+enum Bit {
+    Node(usize),
+    Pin(Pin),
+    Const(bool),
+}
+
+// resolved driver of an input
+enum Source {
+    Pin(Pin),
+    Const(bool),
+}
+
+struct Cell {
+    module: String,
+    inputs: Vec<Option<Bit>>,
+    outputs: u8,
+}
+
+// turns a parsed definition into a module, the first two cells are its Input and Output blocks
+struct Elaborator<'a> {
+    definition: &'a Definition,
+    interfaces: &'a HashMap<String, Interface>,
+    modules: &'a HashMap<String, Module>,
+    nodes: HashMap<(String, i64), usize>,
+    names: Vec<String>,
+    drivers: Vec<Option<Bit>>,
+    cells: Vec<Cell>,
+}
+
+impl<'a> Elaborator<'a> {
+    fn new(
+        definition: &'a Definition,
+        interfaces: &'a HashMap<String, Interface>,
+        modules: &'a HashMap<String, Module>,
+    ) -> Self {
+        Self {
+            definition,
+            interfaces,
+            modules,
+            nodes: HashMap::new(),
+            names: vec![],
+            drivers: vec![],
+            cells: vec![],
+        }
+    }
+
+    fn node(&mut self, name: &str, index: i64) -> usize {
+        if let Some(node) = self.nodes.get(&(name.to_string(), index)) {
+            return *node;
+        }
+
+        let node = self.drivers.len();
+        self.drivers.push(None);
+        self.names.push(
+            match self.definition.nets.get(name).and_then(|net| net.range) {
+                Some(_) => format!("{name}[{index}]"),
+                None => name.to_string(),
+            },
+        );
+        self.nodes.insert((name.to_string(), index), node);
+        node
+    }
+
+    fn nodes_of(&mut self, name: &str, range: Option<(i64, i64)>) -> Vec<usize> {
+        indices(range)
+            .into_iter()
+            .map(|index| self.node(name, index))
+            .collect()
+    }
+
+    fn drive(&mut self, node: usize, bit: Bit) -> Result<(), String> {
+        match self.drivers[node] {
+            Some(_) => Err(format!("`{}` has multiple drivers", self.names[node])),
+            None => {
+                self.drivers[node] = Some(bit);
+                Ok(())
+            }
+        }
+    }
+
+    fn cell(&mut self, module: &str, inputs: Vec<Option<Bit>>, outputs: u8) -> usize {
+        self.cells.push(Cell {
+            module: module.to_string(),
+            inputs,
+            outputs,
+        });
+        self.cells.len() - 1
+    }
+
+    fn gate(&mut self, module: &str, inputs: Vec<Bit>) -> Bit {
+        let cell = self.cell(module, inputs.into_iter().map(Some).collect(), 1);
+        Bit::Pin((cell, 0))
+    }
+
+    fn not(&mut self, bit: Bit) -> Bit {
+        match bit {
+            Bit::Const(value) => Bit::Const(!value),
+            bit => self.gate("Not", vec![bit]),
+        }
+    }
+
+    fn reduce_or(&mut self, bits: Vec<Bit>) -> Bit {
+        let mut bits = bits.into_iter();
+        let first = bits.next().unwrap_or(Bit::Const(false));
+        bits.fold(first, |acc, bit| self.gate("Or", vec![acc, bit]))
+    }
+
+    fn fit(mut bits: Vec<Bit>, width: usize) -> Vec<Bit> {
+        bits.resize(width, Bit::Const(false));
+        bits
+    }
+
+    // signals a (possibly negated) binary operator is built from
+    fn operator(symbol: &str) -> (&'static str, &'static str) {
+        match symbol {
+            "&" => ("And", "Nand"),
+            "|" => ("Or", "Nor"),
+            "^" => ("Xor", "Xnor"),
+            _ => ("Xnor", "Xor"),
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Vec<Bit>, String> {
+        Ok(match expr {
+            Expr::Ident(name) => {
+                let range = self.definition.nets.get(name).and_then(|net| net.range);
+                self.nodes_of(name, range)
+                    .into_iter()
+                    .map(Bit::Node)
+                    .collect()
+            }
+            Expr::Index(name, index) => vec![Bit::Node(self.node(name, *index))],
+            Expr::Range(name, msb, lsb) => self
+                .nodes_of(name, Some((*msb, *lsb)))
+                .into_iter()
+                .map(Bit::Node)
+                .collect(),
+            Expr::Number(width, value) => (0..width.unwrap_or(32))
+                .map(|bit| Bit::Const(bit < 128 && (value >> bit) & 1 == 1))
+                .collect(),
+            Expr::Concat(items) => {
+                let mut bits = vec![];
+                for item in items.iter().rev() {
+                    bits.extend(self.eval(item)?);
+                }
+                bits
+            }
+            Expr::Repeat(count, items) => {
+                let bits = self.eval(&Expr::Concat(items.clone()))?;
+                bits.repeat(*count)
+            }
+            // negated gates like `~(a & b)` become a single block
+            Expr::Unary("~", inner) if let Expr::Binary(symbol, lhs, rhs) = inner.as_ref() => {
+                self.binary(Self::operator(symbol).1, lhs, rhs)?
+            }
+            Expr::Unary("~", inner) => {
+                let bits = self.eval(inner)?;
+                bits.into_iter().map(|bit| self.not(bit)).collect()
+            }
+            Expr::Unary(_, inner) => {
+                let bits = self.eval(inner)?;
+                let any = self.reduce_or(bits);
+                vec![self.not(any)]
+            }
+            Expr::Binary(symbol, lhs, rhs) => self.binary(Self::operator(symbol).0, lhs, rhs)?,
+            Expr::Ternary(condition, then, otherwise) => {
+                let condition = self.eval(condition)?;
+                let select = self.reduce_or(condition);
+                let (then, otherwise) = (self.eval(then)?, self.eval(otherwise)?);
+                let width = then.len().max(otherwise.len());
+                Self::fit(otherwise, width)
+                    .into_iter()
+                    .zip(Self::fit(then, width))
+                    .map(|(a, b)| self.gate("Mux", vec![a, b, select]))
+                    .collect()
+            }
+        })
+    }
+
+    fn binary(&mut self, module: &str, lhs: &Expr, rhs: &Expr) -> Result<Vec<Bit>, String> {
+        let (lhs, rhs) = (self.eval(lhs)?, self.eval(rhs)?);
+        let width = lhs.len().max(rhs.len());
+        Ok(Self::fit(lhs, width)
+            .into_iter()
+            .zip(Self::fit(rhs, width))
+            .map(|(a, b)| self.gate(module, vec![a, b]))
+            .collect())
+    }
+
+    fn single(&mut self, expr: &Expr) -> Result<Bit, String> {
+        Ok(self
+            .eval(expr)?
+            .first()
+            .copied()
+            .unwrap_or(Bit::Const(false)))
+    }
+
+    // nets an expression on the left side of an assignment refers to
+    fn target(&mut self, expr: &Expr) -> Result<Vec<usize>, String> {
+        Ok(match expr {
+            Expr::Ident(name) => {
+                let range = self.definition.nets.get(name).and_then(|net| net.range);
+                self.nodes_of(name, range)
+            }
+            Expr::Index(name, index) => vec![self.node(name, *index)],
+            Expr::Range(name, msb, lsb) => self.nodes_of(name, Some((*msb, *lsb))),
+            Expr::Concat(items) => {
+                let mut nodes = vec![];
+                for item in items.iter().rev() {
+                    nodes.extend(self.target(item)?);
+                }
+                nodes
+            }
+            _ => return Err(String::from("expression cannot be assigned to")),
+        })
+    }
+
+    fn assign(&mut self, target: &Expr, bits: Vec<Bit>) -> Result<(), String> {
+        let nodes = self.target(target)?;
+        let width = nodes.len();
+        for (node, bit) in nodes.into_iter().zip(Self::fit(bits, width)) {
+            self.drive(node, bit)?;
+        }
+        Ok(())
+    }
+
+    fn flip_flop(&mut self, data: Bit, clock: Bit) -> Bit {
+        let cell = self.cell("D Flip-Flop", vec![Some(data), Some(clock)], 2);
+        Bit::Pin((cell, 0))
+    }
+
+    fn item(&mut self, item: &Item) -> Result<(), String> {
+        match item {
+            Item::Assign(target, value) => {
+                let bits = self.eval(value)?;
+                self.assign(target, bits)
+            }
+            Item::Gate(kind, terminals) if terminals.len() < 2 => {
+                Err(format!("`{kind}` needs an output and an input"))
+            }
+            Item::Gate(kind, terminals) if kind == "not" || kind == "buf" => {
+                let (input, outputs) = terminals.split_last().unwrap();
+                let mut bit = self.single(input)?;
+                if kind == "not" {
+                    bit = self.not(bit);
+                }
+                for output in outputs {
+                    self.assign(output, vec![bit])?;
+                }
+                Ok(())
+            }
+            Item::Gate(kind, terminals) => {
+                let (base, last) = match kind.as_str() {
+                    "and" => ("And", "And"),
+                    "nand" => ("And", "Nand"),
+                    "or" => ("Or", "Or"),
+                    "nor" => ("Or", "Nor"),
+                    "xor" => ("Xor", "Xor"),
+                    _ => ("Xor", "Xnor"),
+                };
+
+                let mut inputs = vec![];
+                for terminal in terminals[1..].iter() {
+                    inputs.push(self.single(terminal)?);
+                }
+
+                // gates with more than two inputs become a chain of two-input gates
+                let count = inputs.len();
+                let mut inputs = inputs.into_iter();
+                let mut bit = inputs.next().unwrap();
+                if count == 1 && base != last {
+                    bit = self.not(bit);
+                }
+                for (index, input) in inputs.enumerate() {
+                    let module = if index + 2 == count { last } else { base };
+                    bit = self.gate(module, vec![bit, input]);
+                }
+                self.assign(&terminals[0], vec![bit])
+            }
+            Item::Flop(clock, rising, assignments) => {
+                let mut clock = self.single(clock)?;
+                if !rising {
+                    clock = self.not(clock);
+                }
+                for (target, value) in assignments {
+                    let nodes = self.target(target)?;
+                    let width = nodes.len();
+                    let bits = Self::fit(self.eval(value)?, width);
+                    for (node, data) in nodes.into_iter().zip(bits) {
+                        let q = self.flip_flop(data, clock);
+                        self.drive(node, q)?;
+                    }
+                }
+                Ok(())
+            }
+            Item::Instance(module, connections) if module.starts_with("$_") => {
+                self.internal_cell(module, connections)
+            }
+            Item::Instance(module, connections) => self.instance(module, connections),
+        }
+    }
+
+    // gate-level cells Yosys uses when writing netlists without expressions
+    fn internal_cell(&mut self, module: &str, connections: &Connections) -> Result<(), String> {
+        let Connections::Named(connections) = connections else {
+            return Err(format!("`{module}` needs named port connections"));
+        };
+        let port = |name: &str| {
+            connections
+                .iter()
+                .find(|(port, _)| port == name)
+                .and_then(|(_, expr)| expr.as_ref())
+        };
+        let input = |this: &mut Self, name: &str| match port(name) {
+            Some(expr) => this.single(expr),
+            None => Ok(Bit::Const(false)),
+        };
+
+        let bit = match module {
+            "$_BUF_" => input(self, "A")?,
+            "$_NOT_" => {
+                let a = input(self, "A")?;
+                self.not(a)
+            }
+            "$_AND_" | "$_NAND_" | "$_OR_" | "$_NOR_" | "$_XOR_" | "$_XNOR_" => {
+                let gate = match module {
+                    "$_AND_" => "And",
+                    "$_NAND_" => "Nand",
+                    "$_OR_" => "Or",
+                    "$_NOR_" => "Nor",
+                    "$_XOR_" => "Xor",
+                    _ => "Xnor",
+                };
+                let (a, b) = (input(self, "A")?, input(self, "B")?);
+                self.gate(gate, vec![a, b])
+            }
+            "$_ANDNOT_" | "$_ORNOT_" => {
+                let (a, b) = (input(self, "A")?, input(self, "B")?);
+                let b = self.not(b);
+                self.gate(if module == "$_ANDNOT_" { "And" } else { "Or" }, vec![a, b])
+            }
+            "$_MUX_" => {
+                let (a, b, s) = (input(self, "A")?, input(self, "B")?, input(self, "S")?);
+                self.gate("Mux", vec![a, b, s])
+            }
+            "$_DFF_P_" | "$_DFF_N_" => {
+                let (data, mut clock) = (input(self, "D")?, input(self, "C")?);
+                if module == "$_DFF_N_" {
+                    clock = self.not(clock);
+                }
+                let q = self.flip_flop(data, clock);
+                return match port("Q") {
+                    Some(target) => self.assign(target, vec![q]),
+                    None => Ok(()),
+                };
+            }
+            _ => return Err(format!("cell type `{module}` is not supported")),
+        };
+
+        match port("Y") {
+            Some(target) => self.assign(target, vec![bit]),
+            None => Ok(()),
+        }
+    }
+
+    fn instance(&mut self, module: &str, connections: &Connections) -> Result<(), String> {
+        let interfaces = self.interfaces;
+        let (Some(interface), Some(_)) = (interfaces.get(module), self.modules.get(module)) else {
+            return Err(format!("unknown module `{module}`"));
+        };
+
+        let mut expressions = vec![None; interface.ports.len()];
+        match connections {
+            Connections::Ordered(ordered) if ordered.len() > interface.ports.len() => {
+                return Err(format!("too many ports connected to `{module}`"))
+            }
+            Connections::Ordered(ordered) => {
+                for (index, expr) in ordered.iter().enumerate() {
+                    expressions[index] = expr.as_ref();
+                }
+            }
+            Connections::Named(named) => {
+                for (port, expr) in named {
+                    let Some(index) = interface.ports.iter().position(|(name, ..)| name == port)
+                    else {
+                        return Err(format!("module `{module}` has no port `{port}`"));
+                    };
+                    expressions[index] = expr.as_ref();
+                }
+            }
+        }
+
+        let mut inputs = vec![];
+        for ((_, direction, range), expr) in interface.ports.iter().zip(expressions.iter()) {
+            if *direction != Direction::Input {
+                continue;
+            }
+            let width = indices(*range).len();
+            match expr {
+                Some(expr) => {
+                    let bits = Self::fit(self.eval(expr)?, width);
+                    inputs.extend(bits.into_iter().map(Some));
+                }
+                None => inputs.extend(vec![None; width]),
+            }
+        }
+
+        let outputs = interface.width(Direction::Output);
+        let cell = self.cell(module, inputs, outputs as u8);
+
+        let mut pin = 0;
+        for ((_, direction, range), expr) in interface.ports.iter().zip(expressions) {
+            if *direction != Direction::Output {
+                continue;
+            }
+            let width = indices(*range).len();
+            if let Some(expr) = expr {
+                let nodes = self.target(expr)?;
+                for (offset, node) in nodes.into_iter().take(width).enumerate() {
+                    self.drive(node, Bit::Pin((cell, (pin + offset) as u8)))?;
+                }
+            }
+            pin += width;
+        }
+        Ok(())
+    }
+
+    // output pin or constant a bit is driven by, following assignments between nets
+    fn resolve(&self, mut bit: Bit) -> Result<Option<Source>, String> {
+        for _ in 0..=self.drivers.len() {
+            match bit {
+                Bit::Node(node) => match self.drivers[node] {
+                    Some(driver) => bit = driver,
+                    None => return Ok(None),
+                },
+                Bit::Pin(pin) => return Ok(Some(Source::Pin(pin))),
+                Bit::Const(value) => return Ok(Some(Source::Const(value))),
+            }
+        }
+        match bit {
+            Bit::Node(node) => Err(format!("`{}` is assigned to itself", self.names[node])),
+            _ => unreachable!(),
+        }
+    }
+
+    fn elaborate(mut self) -> Result<Module, String> {
+        let (definition, interfaces) = (self.definition, self.interfaces);
+        let interface = &interfaces[&definition.name];
+        let (inputs, outputs) = (
+            interface.pins(Direction::Input),
+            interface.pins(Direction::Output),
+        );
+        if inputs.len().max(outputs.len()) > Block::MAX_CONNECTIONS as usize {
+            return Err(format!(
+                "modules can have at most {} input and output pins",
+                Block::MAX_CONNECTIONS
+            ));
+        }
+
+        self.cell("Input", vec![], inputs.len() as u8);
+        for (index, (net, bit, _)) in inputs.iter().enumerate() {
+            let node = self.node(net, *bit);
+            self.drive(node, Bit::Pin((0, index as u8)))?;
+        }
+        let sinks = outputs
+            .iter()
+            .map(|(net, bit, _)| Some(Bit::Node(self.node(net, *bit))))
+            .collect();
+        self.cell("Output", sinks, 0);
+
+        for (item, line) in definition.items.iter() {
+            self.item(item)
+                .map_err(|err| format!("line {line}: {err}"))?;
+        }
+
+        // resolve the output pin driving every input, replacing constants with Low/High blocks
+        let mut constants: [Option<usize>; 2] = [None, None];
+        let mut sources = vec![];
+        for cell in 0..self.cells.len() {
+            let mut pins = vec![];
+            for bit in self.cells[cell].inputs.clone() {
+                pins.push(
+                    match bit.map(|bit| self.resolve(bit)).transpose()?.flatten() {
+                        Some(Source::Pin(pin)) => Some(pin),
+                        Some(Source::Const(value)) => {
+                            let constant = match constants[value as usize] {
+                                Some(constant) => constant,
+                                None => {
+                                    let constant =
+                                        self.cell(if value { "High" } else { "Low" }, vec![], 1);
+                                    constants[value as usize] = Some(constant);
+                                    constant
+                                }
+                            };
+                            Some((constant, 0))
+                        }
+                        None => None,
+                    },
+                );
+            }
+            sources.push(pins);
+        }
+        sources.resize(self.cells.len(), vec![]);
+
+        let mut module = Module::new(
+            definition.name.clone(),
+            inputs.len() as u8,
+            outputs.len() as u8,
+        );
+        module.set_pin_names(
+            inputs.into_iter().map(|(.., name)| name).collect(),
+            outputs.into_iter().map(|(.., name)| name).collect(),
+        );
+        module.add_io_blocks();
+        let (input_block, output_block) = module.io_blocks().unwrap();
+
+        let mut block_ids = vec![input_block, output_block];
+        let mut blocks = vec![];
+        for cell in self.cells[2..].iter() {
+            let template = BUILTINS
+                .get(cell.module.as_str())
+                .map(|builtin| builtin.module())
+                .or_else(|| self.modules.get(&cell.module))
+                .ok_or_else(|| format!("unknown module `{}`", cell.module))?;
+            let block = Block::new(&template, Vector2(0, 0), None);
+            block_ids.push(block.id());
+            blocks.push(block);
+        }
+
+        let plot = module.plot_mut().unwrap();
+        blocks.into_iter().for_each(|block| plot.add_block(block));
+
+        Self::place(&sources, &block_ids, plot);
+
+        // one connection per driving output pin
+        let mut destinations: BTreeMap<Pin, Vec<Segment>> = BTreeMap::new();
+        for (cell, pins) in sources.iter().enumerate() {
+            for (input, pin) in pins.iter().enumerate() {
+                if let Some(pin) = pin {
+                    destinations
+                        .entry(*pin)
+                        .or_default()
+                        .push(Segment::Block(block_ids[cell], input as u8));
+                }
+            }
+        }
+        for ((cell, output), segments) in destinations {
+            let mut connection = Connection::new(Port::Output(block_ids[cell], output), segments);
+            plot.route_connection(&mut connection);
+            plot.add_connection(connection);
+        }
+
+        Ok(module)
+    }
+
+    // arranges the blocks in columns by their distance from the module inputs
+    fn place(sources: &[Vec<Option<Pin>>], block_ids: &[BlockID], plot: &mut Plot) {
+        fn distance(
+            cell: usize,
+            sources: &[Vec<Option<Pin>>],
+            depths: &mut Vec<Option<usize>>,
+            visiting: &mut Vec<bool>,
+        ) -> usize {
+            if let Some(depth) = depths[cell] {
+                return depth;
+            }
+            // feedback loops (e.g. through flip-flops) are cut here
+            if visiting[cell] {
+                return 0;
+            }
+
+            visiting[cell] = true;
+            let value = sources[cell]
+                .iter()
+                .flatten()
+                .map(|(source, _)| distance(*source, sources, depths, visiting) + 1)
+                .max()
+                .unwrap_or(0);
+            visiting[cell] = false;
+            depths[cell] = Some(value);
+            value
+        }
+
+        let mut depths = vec![None; sources.len()];
+        let mut visiting = vec![false; sources.len()];
+        let mut columns: Vec<Vec<usize>> = vec![];
+        for cell in 2..sources.len() {
+            let depth = distance(cell, sources, &mut depths, &mut visiting).max(1);
+            if columns.len() <= depth {
+                columns.resize(depth + 1, vec![]);
+            }
+            columns[depth].push(cell);
+        }
+        // the module inputs come first and the module outputs last
+        if columns.is_empty() {
+            columns.push(vec![]);
+        }
+        columns[0].insert(0, 0);
+        columns.push(vec![1]);
+
+        let mut rows = vec![0.; sources.len()];
+        let mut x = MARGIN;
+        for column in columns.iter_mut() {
+            // order the blocks by the average row of the blocks driving them
+            let key = |cell: &usize| {
+                let drivers = sources[*cell].iter().flatten().collect::<Vec<_>>();
+                match drivers.is_empty() {
+                    true => f64::MAX,
+                    false => {
+                        drivers.iter().map(|(source, _)| rows[*source]).sum::<f64>()
+                            / drivers.len() as f64
+                    }
+                }
+            };
+            column.sort_by(|a, b| key(a).total_cmp(&key(b)));
+
+            let mut y = MARGIN;
+            let mut width = 0;
+            for cell in column.iter() {
+                if let Some(block) = plot.get_block_mut(block_ids[*cell]) {
+                    block.set_position(Vector2(x, y));
+                    rows[*cell] = y as f64;
+                    width = width.max(block.bounds().0);
+                    y = Self::snap(y + block.bounds().1 + ROW_GAP);
+                }
+            }
+            x = Self::snap(x + width + COLUMN_GAP);
+        }
+    }
+
+    fn snap(value: i32) -> i32 {
+        (value + GRID_SIZE - 1) / GRID_SIZE * GRID_SIZE
+    }
+}
+
+// module names instantiated by a definition
+fn dependencies(definition: &Definition) -> impl Iterator<Item = &String> {
+    definition.items.iter().filter_map(|(item, _)| match item {
+        Item::Instance(module, _) => Some(module),
+        _ => None,
+    })
+}
+
+// parses structural Verilog and creates a module for each of its definitions
+pub fn import(source: &str) -> Result<ModuleFile, String> {
+    let definitions = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+    }
+    .definitions()?;
+    if definitions.is_empty() {
+        return Err(String::from("The file does not contain any modules"));
+    }
+
+    let mut interfaces = HashMap::new();
+    for definition in definitions.iter() {
+        if interfaces
+            .insert(definition.name.clone(), Interface::new(definition)?)
+            .is_some()
+        {
+            return Err(format!("Module `{}` is defined twice", definition.name));
+        }
+    }
+
+    // elaborate modules after all modules they instantiate
+    fn visit<'a>(
+        definition: &'a Definition,
+        definitions: &'a [Definition],
+        state: &mut HashMap<&'a str, bool>,
+        order: &mut Vec<&'a Definition>,
+    ) -> Result<(), String> {
+        match state.get(definition.name.as_str()) {
+            Some(true) => return Ok(()),
+            Some(false) => return Err(format!("Module `{}` instantiates itself", definition.name)),
+            None => (),
+        }
+
+        state.insert(&definition.name, false);
+        for dependency in dependencies(definition) {
+            if let Some(dependency) = definitions.iter().find(|d| &d.name == dependency) {
+                visit(dependency, definitions, state, order)?;
+            }
+        }
+        state.insert(&definition.name, true);
+        order.push(definition);
+        Ok(())
+    }
+
+    let mut order = vec![];
+    let mut state = HashMap::new();
+    for definition in definitions.iter() {
+        visit(definition, &definitions, &mut state, &mut order)?;
+    }
+
+    let mut modules = HashMap::new();
+    for definition in order {
+        let module = Elaborator::new(definition, &interfaces, &modules)
+            .elaborate()
+            .map_err(|err| format!("Module `{}`, {err}", definition.name))?;
+        modules.insert(definition.name.clone(), module);
+    }
+
+    // the top-level module is the last one not instantiated anywhere else
+    let main_name = definitions
+        .iter()
+        .rev()
+        .find(|definition| {
+            !definitions
+                .iter()
+                .any(|other| dependencies(other).any(|name| *name == definition.name))
+        })
+        .unwrap_or(&definitions[0])
+        .name
+        .clone();
+
+    Ok(ModuleFile::new(main_name, modules))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<Token> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    fn expr(source: &str) -> Expr {
+        Parser {
+            tokens: tokenize(source).unwrap(),
+            position: 0,
+        }
+        .expr()
+        .unwrap()
+    }
+
+    #[test]
+    fn escaped_identifiers() {
+        assert_eq!(
+            tokens("\\a+b  \\$auto[3] ;"),
+            [
+                Token::Ident(String::from("a+b")),
+                Token::Ident(String::from("$auto[3]")),
+                Token::Symbol(";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn sized_numbers() {
+        assert_eq!(
+            tokens("12 8'hff 4'b1x0z 'b101 16'sd1_000"),
+            [
+                Token::Number(None, 12),
+                Token::Number(Some(8), 0xff),
+                Token::Number(Some(4), 0b1000),
+                Token::Number(None, 0b101),
+                Token::Number(Some(16), 1000),
+            ]
+        );
+        assert!(tokenize("4'q0").is_err());
+        assert!(tokenize("4'b012").is_err());
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            tokens("a // b\n/* c\n*/ (* keep *) d"),
+            [
+                Token::Ident(String::from("a")),
+                Token::Ident(String::from("d"))
+            ]
+        );
+        assert_eq!(
+            tokenize("a\n/* b").unwrap_err(),
+            "line 2: unterminated comment"
+        );
+        assert_eq!(tokenize("/*/").unwrap_err(), "line 1: unterminated comment");
+        assert_eq!(
+            tokenize("(* keep").unwrap_err(),
+            "line 1: unterminated attribute"
+        );
+    }
+
+    #[test]
+    fn concatenation() {
+        let Expr::Concat(items) = expr("{a, b[1], c[3:2], 2'b01}") else {
+            panic!("expected a concatenation");
+        };
+        assert!(matches!(
+            items.as_slice(),
+            [
+                Expr::Ident(a),
+                Expr::Index(b, 1),
+                Expr::Range(c, 3, 2),
+                Expr::Number(Some(2), 1),
+            ] if a == "a" && b == "b" && c == "c"
+        ));
+    }
+
+    #[test]
+    fn repetition() {
+        let Expr::Repeat(4, items) = expr("{4{a, ~b}}") else {
+            panic!("expected a repetition");
+        };
+        assert!(matches!(
+            items.as_slice(),
+            [Expr::Ident(_), Expr::Unary("~", _)]
+        ));
+    }
+
+    #[test]
+    fn operator_precedence() {
+        // `&` binds stronger than `^`, which binds stronger than `|`
+        let Expr::Binary("|", lhs, rhs) = expr("a | b ^ c & d") else {
+            panic!("expected `|` at the top");
+        };
+        assert!(matches!(*lhs, Expr::Ident(_)));
+        assert!(
+            matches!(*rhs, Expr::Binary("^", _, ref and) if matches!(**and, Expr::Binary("&", ..)))
+        );
+    }
+
+    #[test]
+    fn yosys_netlist() {
+        let file = import(include_str!("../../tests/fixtures/counter.v")).unwrap();
+        assert_eq!(file.main_name, "counter");
+
+        let module = &file.modules["counter"];
+        assert_eq!(module.get_num_inputs(), 2);
+        assert_eq!(module.get_num_outputs(), 2);
+
+        // Input and Output blocks, two Xor gates, an And gate and a flip-flop per bit
+        let plot = module.plot().unwrap();
+        assert_eq!(plot.blocks().len(), 7);
+        let count = |name: &str| {
+            plot.blocks()
+                .values()
+                .filter(|block| block.module_id() == name)
+                .count()
+        };
+        assert_eq!(count("Xor"), 2);
+        assert_eq!(count("And"), 1);
+        assert_eq!(count("D Flip-Flop"), 2);
+
+        // one connection per driving pin: clk, en, both flip-flops and the three gates
+        assert_eq!(plot.connections().len(), 7);
+    }
+}
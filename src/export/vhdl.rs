@@ -0,0 +1,392 @@
+use super::netlist::{self, Cell, CellKind, Design, Direction, Net, NetID, Netlist};
+use crate::{
+    project::Project,
+    simulator::{builtin::random_state, Decoration},
+    FileExtension,
+};
+
+use gtk::gio;
+use std::fmt::Write;
+
+pub struct VhdlFile;
+
+impl FileExtension for VhdlFile {
+    const FILE_EXTENSION: &'static str = "vhd";
+    const FILE_PATTERN: &'static str = "*.vhd";
+
+    fn file_filter() -> gtk::FileFilter {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("VHDL files"));
+        filter.add_pattern(Self::FILE_PATTERN);
+        filter.add_pattern("*.vhdl");
+        filter
+    }
+}
+
+impl VhdlFile {
+    // writes all modules of the project and the main plot as top-level entity named after the file
+    pub fn export(project: &Project, file: &gio::File) -> Result<(), String> {
+        netlist::export_to(project, file, Self::generate)
+    }
+
+    pub fn generate(design: &Design) -> Result<String, std::fmt::Error> {
+        let mut out = String::from("-- Generated by LogicRs\n");
+        for netlist in design.modules.iter().chain([&design.top]) {
+            writeln!(out)?;
+            Self::write_entity(&mut out, netlist)?;
+            writeln!(out)?;
+            Self::write_architecture(&mut out, design, netlist)?;
+        }
+        Ok(out)
+    }
+
+    fn net(netlist: &Netlist, net: NetID) -> String {
+        netlist.net_name(net, |name, bit| format!("{name}({bit})"))
+    }
+
+    // signal driving the given input of a cell, `None` for unconnected inputs
+    fn signal(netlist: &Netlist, cell: &Cell, index: usize) -> Option<String> {
+        cell.inputs
+            .get(index)
+            .copied()
+            .flatten()
+            .map(|net| Self::net(netlist, net))
+    }
+
+    // expression driving the given input of a cell, unconnected inputs are tied to logic low
+    fn input(netlist: &Netlist, cell: &Cell, index: usize) -> String {
+        Self::signal(netlist, cell, index).unwrap_or_else(|| String::from("'0'"))
+    }
+
+    fn bit(value: u128, index: usize) -> &'static str {
+        match (value >> index) & 1 {
+            0 => "'0'",
+            _ => "'1'",
+        }
+    }
+
+    fn zeros(count: usize) -> String {
+        format!("\"{}\"", "0".repeat(count))
+    }
+
+    fn write_entity(out: &mut String, netlist: &Netlist) -> std::fmt::Result {
+        writeln!(out, "library ieee;\nuse ieee.std_logic_1164.all;\n")?;
+        writeln!(out, "entity {} is", netlist.name)?;
+
+        let ports = netlist
+            .ports
+            .iter()
+            .map(|port| {
+                let direction = match port.direction {
+                    Direction::Input => "in",
+                    Direction::Output => "out",
+                };
+                match port.width() {
+                    1 => format!("        {} : {direction} std_logic", port.name),
+                    width => format!(
+                        "        {} : {direction} std_logic_vector({} downto 0)",
+                        port.name,
+                        width - 1
+                    ),
+                }
+            })
+            .collect::<Vec<_>>();
+        if !ports.is_empty() {
+            writeln!(out, "    port (\n{}\n    );", ports.join(";\n"))?;
+        }
+
+        writeln!(out, "end entity {};", netlist.name)
+    }
+
+    fn write_architecture(
+        out: &mut String,
+        design: &Design,
+        netlist: &Netlist,
+    ) -> std::fmt::Result {
+        writeln!(out, "architecture structural of {} is", netlist.name)?;
+
+        // registers start out reset like in the simulation
+        let registers = netlist.registers();
+        for (id, net) in netlist.nets.iter().enumerate() {
+            if let Net::Wire(name) = net {
+                match registers.contains(&id) {
+                    true => writeln!(out, "    signal {name} : std_logic := '0';")?,
+                    false => writeln!(out, "    signal {name} : std_logic;")?,
+                }
+            }
+        }
+        for cell in netlist.cells.iter() {
+            if cell.kind() == CellKind::Builtin
+                && cell.module == "Random"
+                && let Decoration::Random(_, seed) = cell.decoration
+            {
+                writeln!(
+                    out,
+                    "    signal {} : std_logic_vector(63 downto 0) := x\"{:016x}\";",
+                    cell.name,
+                    random_state(seed)
+                )?;
+            }
+        }
+
+        writeln!(out, "begin")?;
+        for cell in netlist.cells.iter() {
+            match cell.kind() {
+                CellKind::Instance => Self::write_instance(out, design, netlist, cell)?,
+                CellKind::Fixed { value, device } => {
+                    if device {
+                        writeln!(
+                            out,
+                            "    -- {}: {} fixed to its current value",
+                            cell.name, cell.module
+                        )?;
+                    }
+                    for (index, net) in cell.outputs.iter().enumerate() {
+                        writeln!(
+                            out,
+                            "    {} <= {};",
+                            Self::net(netlist, *net),
+                            Self::bit(value, index)
+                        )?;
+                    }
+                }
+                CellKind::Register | CellKind::Builtin => Self::write_builtin(out, netlist, cell)?,
+                CellKind::Unsupported => {
+                    writeln!(out, "    -- {}: {} is not exported", cell.name, cell.module)?
+                }
+            }
+        }
+
+        for port in netlist.outputs() {
+            for (bit, net) in port.bits.iter().enumerate() {
+                let target = match port.width() {
+                    1 => port.name.clone(),
+                    _ => format!("{}({bit})", port.name),
+                };
+                let source = net
+                    .map(|net| Self::net(netlist, net))
+                    .unwrap_or_else(|| String::from("'0'"));
+                writeln!(out, "    {target} <= {source};")?;
+            }
+        }
+
+        writeln!(out, "end architecture structural;")
+    }
+
+    // level-sensitive process over all connected inputs of a latch
+    fn write_latch(
+        out: &mut String,
+        netlist: &Netlist,
+        cell: &Cell,
+        body: String,
+    ) -> std::fmt::Result {
+        let sensitivity = (0..cell.inputs.len())
+            .filter_map(|index| Self::signal(netlist, cell, index))
+            .collect::<Vec<_>>();
+        match sensitivity.is_empty() {
+            // latches without any inputs keep their initial state
+            true => writeln!(out, "    -- {}: no inputs connected", cell.name),
+            false => writeln!(
+                out,
+                "    process ({})\n    begin\n{body}\n    end process;",
+                sensitivity.join(", ")
+            ),
+        }
+    }
+
+    // process triggered by the rising edge of the clock input
+    fn write_clocked(
+        out: &mut String,
+        netlist: &Netlist,
+        cell: &Cell,
+        clock: usize,
+        body: String,
+    ) -> std::fmt::Result {
+        match Self::signal(netlist, cell, clock) {
+            Some(clock) => writeln!(
+                out,
+                "    process ({clock})\n    begin\n        if rising_edge({clock}) then\n{body}\n        end if;\n    end process;"
+            ),
+            // without a clock, the cell keeps its initial state
+            None => writeln!(out, "    -- {}: no clock connected", cell.name),
+        }
+    }
+
+    fn write_builtin(out: &mut String, netlist: &Netlist, cell: &Cell) -> std::fmt::Result {
+        let input = |index| Self::input(netlist, cell, index);
+        let output = |index: usize| Self::net(netlist, cell.outputs[index]);
+        let name = &cell.name;
+
+        match cell.module.as_str() {
+            gate @ ("And" | "Nand" | "Or" | "Nor" | "Xor" | "Xnor") => writeln!(
+                out,
+                "    {} <= {} {} {};",
+                output(0),
+                input(0),
+                gate.to_lowercase(),
+                input(1)
+            ),
+            "Not" => writeln!(out, "    {} <= not {};", output(0), input(0)),
+            "Mux" => writeln!(
+                out,
+                "    {} <= {} when {} = '1' else {};",
+                output(0),
+                input(1),
+                input(2),
+                input(0)
+            ),
+            "Demux" => {
+                writeln!(out, "    {} <= {} and not {};", output(0), input(0), input(1))?;
+                writeln!(out, "    {} <= {} and {};", output(1), input(0), input(1))
+            }
+            "D Latch" => Self::write_latch(
+                out,
+                netlist,
+                cell,
+                format!(
+                    "        if {} = '1' then\n            {} <= {};\n        end if;",
+                    input(1),
+                    output(0),
+                    input(0)
+                ),
+            ),
+            "SR Latch" => Self::write_latch(
+                out,
+                netlist,
+                cell,
+                format!(
+                    "        if {r} = '1' then\n            {q} <= '0';\n        elsif {s} = '1' then\n            {q} <= '1';\n        end if;",
+                    q = output(0),
+                    s = input(0),
+                    r = input(1)
+                ),
+            ),
+            "SR Nand Latch" => {
+                Self::write_latch(
+                    out,
+                    netlist,
+                    cell,
+                    format!(
+                        "        if {s} = '0' then\n            {q} <= '1';\n        elsif {r} = '0' then\n            {q} <= '0';\n        end if;",
+                        q = output(0),
+                        s = input(0),
+                        r = input(1)
+                    ),
+                )?;
+                writeln!(out, "    {} <= not {};", output(1), output(0))
+            }
+            "JK Latch" => {
+                writeln!(
+                    out,
+                    "    -- {name}: toggles once per change of J or K like the simulator, not synthesizable"
+                )?;
+                Self::write_latch(
+                    out,
+                    netlist,
+                    cell,
+                    format!(
+                        "        if {j} = '1' and {k} = '1' then\n            {q} <= not {q};\n        elsif {j} = '1' then\n            {q} <= '1';\n        elsif {k} = '1' then\n            {q} <= '0';\n        end if;",
+                        q = output(0),
+                        j = input(0),
+                        k = input(1)
+                    ),
+                )
+            }
+            "D Flip-Flop" => {
+                Self::write_clocked(
+                    out,
+                    netlist,
+                    cell,
+                    1,
+                    format!("            {} <= {};", output(0), input(0)),
+                )?;
+                writeln!(out, "    {} <= not {};", output(1), output(0))
+            }
+            "T Flip-Flop" => {
+                Self::write_clocked(
+                    out,
+                    netlist,
+                    cell,
+                    1,
+                    format!(
+                        "            if {t} = '1' then\n                {q} <= not {q};\n            end if;",
+                        q = output(0),
+                        t = input(0)
+                    ),
+                )?;
+                writeln!(out, "    {} <= not {};", output(1), output(0))
+            }
+            // the simulator draws a new value every tick, which has no clock to follow here
+            "Random" if Self::signal(netlist, cell, 0).is_none() => {
+                writeln!(
+                    out,
+                    "    -- {name}: no clock connected, keeps its first value instead of changing every tick"
+                )?;
+                for index in 0..cell.outputs.len() {
+                    writeln!(out, "    {} <= {name}({index});", output(index))?;
+                }
+                Ok(())
+            }
+            "Random" => {
+                // one xorshift64 step: x ^= x << 13; x ^= x >> 7; x ^= x << 17
+                Self::write_clocked(
+                    out,
+                    netlist,
+                    cell,
+                    0,
+                    [
+                        format!("{name}(50 downto 0) & {}", Self::zeros(13)),
+                        format!("{} & {name}(63 downto 7)", Self::zeros(7)),
+                        format!("{name}(46 downto 0) & {}", Self::zeros(17)),
+                    ]
+                    .map(|shifted| format!("            {name} <= {name} xor ({shifted});"))
+                    .join("\n"),
+                )?;
+                for index in 0..cell.outputs.len() {
+                    writeln!(out, "    {} <= {name}({index});", output(index))?;
+                }
+                Ok(())
+            }
+            module => unreachable!("`{module}` is not a register or builtin"),
+        }
+    }
+
+    fn write_instance(
+        out: &mut String,
+        design: &Design,
+        netlist: &Netlist,
+        cell: &Cell,
+    ) -> std::fmt::Result {
+        let Some(module) = design.module(&cell.module) else {
+            return writeln!(
+                out,
+                "    -- {}: unknown module `{}`",
+                cell.name, cell.module
+            );
+        };
+
+        let inputs = module.inputs().enumerate().map(|(index, port)| {
+            format!(
+                "            {} => {}",
+                port.name,
+                Self::input(netlist, cell, index)
+            )
+        });
+        let outputs = module.outputs().enumerate().map(|(index, port)| {
+            let net = cell
+                .outputs
+                .get(index)
+                .map(|net| Self::net(netlist, *net))
+                .unwrap_or_else(|| String::from("open"));
+            format!("            {} => {net}", port.name)
+        });
+
+        writeln!(
+            out,
+            "    {} : entity work.{}\n        port map (\n{}\n        );",
+            cell.name,
+            module.name,
+            inputs.chain(outputs).collect::<Vec<_>>().join(",\n")
+        )
+    }
+}
@@ -0,0 +1,295 @@
+// minimal XML reader for files of other simulators: elements, attributes, text and entities;
+// comments, processing instructions, DOCTYPEs and CDATA markers are skipped
+
+#[derive(Debug, Clone, Default)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+    pub line: usize,
+}
+
+impl Element {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+}
+
+struct Reader {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+}
+
+impl Reader {
+    fn error<T>(&self, message: &str) -> Result<T, String> {
+        Err(format!("line {}: {message}", self.line))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.position + i) == Some(&c))
+    }
+
+    fn advance(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.peek() == Some('\n') {
+                self.line += 1;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.advance(1);
+        }
+    }
+
+    // skips everything up to and including the given terminator
+    fn skip_until(&mut self, terminator: &str) -> Result<(), String> {
+        while !self.starts_with(terminator) {
+            if self.peek().is_none() {
+                return self.error(&format!("expected `{terminator}`"));
+            }
+            self.advance(1);
+        }
+        self.advance(terminator.chars().count());
+        Ok(())
+    }
+
+    // skips comments, processing instructions and declarations between elements
+    fn skip_misc(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if self.starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if self.starts_with("<!") && !self.starts_with("<![CDATA[") {
+                self.skip_until(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+        {
+            self.advance(1);
+        }
+        match start == self.position {
+            true => self.error("expected a name"),
+            false => Ok(self.chars[start..self.position].iter().collect()),
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        match self.peek() {
+            Some(found) if found == c => {
+                self.advance(1);
+                Ok(())
+            }
+            _ => self.error(&format!("expected `{c}`")),
+        }
+    }
+
+    // reads text up to the given terminator, replacing entity and character references
+    fn text(&mut self, terminator: char) -> Result<String, String> {
+        let mut text = String::new();
+        while let Some(c) = self.peek()
+            && c != terminator
+        {
+            if c != '&' {
+                text.push(c);
+                self.advance(1);
+                continue;
+            }
+
+            let start = self.position + 1;
+            let Some(length) = self.chars[start..].iter().take(12).position(|c| *c == ';') else {
+                return self.error("unterminated entity reference");
+            };
+            let entity = self.chars[start..start + length].iter().collect::<String>();
+            text.push(match entity.as_str() {
+                "lt" => '<',
+                "gt" => '>',
+                "amp" => '&',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => entity
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32)
+                    .map_or_else(|| self.error(&format!("unknown entity `&{entity};`")), Ok)?,
+            });
+            self.advance(length + 2);
+        }
+        Ok(text)
+    }
+
+    fn element(&mut self) -> Result<Element, String> {
+        let line = self.line;
+        self.expect('<')?;
+        let mut element = Element {
+            name: self.name()?,
+            line,
+            ..Default::default()
+        };
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('/') => {
+                    self.advance(1);
+                    self.expect('>')?;
+                    return Ok(element);
+                }
+                Some('>') => {
+                    self.advance(1);
+                    break;
+                }
+                Some(_) => {
+                    let key = self.name()?;
+                    self.skip_whitespace();
+                    self.expect('=')?;
+                    self.skip_whitespace();
+                    let quote = match self.peek() {
+                        Some(quote @ ('"' | '\'')) => quote,
+                        _ => return self.error("expected a quoted attribute value"),
+                    };
+                    self.advance(1);
+                    let value = self.text(quote)?;
+                    self.expect(quote)?;
+                    element.attributes.push((key, value));
+                }
+                None => return self.error("unexpected end of file"),
+            }
+        }
+
+        loop {
+            if self.starts_with("</") {
+                self.advance(2);
+                let name = self.name()?;
+                if name != element.name {
+                    return self.error(&format!(
+                        "expected `</{}>`, found `</{name}>`",
+                        element.name
+                    ));
+                }
+                self.skip_whitespace();
+                self.expect('>')?;
+                element.text = element.text.trim().to_string();
+                return Ok(element);
+            } else if self.starts_with("<![CDATA[") {
+                self.advance(9);
+                let start = self.position;
+                self.skip_until("]]>")?;
+                element
+                    .text
+                    .extend(self.chars[start..self.position - 3].iter());
+            } else if self.starts_with("<!--") || self.starts_with("<?") {
+                self.skip_misc()?;
+            } else if self.starts_with("<") {
+                element.children.push(self.element()?);
+            } else if self.peek().is_some() {
+                let text = self.text('<')?;
+                element.text.push_str(&text);
+            } else {
+                return self.error(&format!("missing `</{}>`", element.name));
+            }
+        }
+    }
+}
+
+// parses an XML document, returning its root element
+pub fn parse(source: &str) -> Result<Element, String> {
+    let mut reader = Reader {
+        chars: source.chars().collect(),
+        position: 0,
+        line: 1,
+    };
+
+    reader.skip_misc()?;
+    let root = reader.element()?;
+    reader.skip_misc()?;
+    match reader.peek() {
+        Some(_) => reader.error("unexpected content after the root element"),
+        None => Ok(root),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes() {
+        let root = parse(r#"<comp loc = "(10,20)" name='AND Gate' lib="1"/>"#).unwrap();
+        assert_eq!(root.name, "comp");
+        assert_eq!(root.attribute("loc"), Some("(10,20)"));
+        assert_eq!(root.attribute("name"), Some("AND Gate"));
+        assert_eq!(root.attribute("lib"), Some("1"));
+        assert_eq!(root.attribute("facing"), None);
+        assert!(parse("<comp loc=(10,20)/>").is_err());
+    }
+
+    #[test]
+    fn entities() {
+        let root = parse(r#"<a val="&lt;&quot;&apos;&gt;">x &amp; &#955; &#x3bb;</a>"#).unwrap();
+        assert_eq!(root.attribute("val"), Some("<\"'>"));
+        assert_eq!(root.text, "x & λ λ");
+        assert!(parse("<a>&nbsp;</a>").is_err());
+        assert!(parse("<a>& b</a>").is_err());
+    }
+
+    #[test]
+    fn comments_and_declarations() {
+        let root = parse(
+            "<?xml version=\"1.0\"?>\n<!DOCTYPE project>\n<!-- <b/> -->\n<project>\n  <!-- <c/> -->\n  <lib name=\"0\"/>\n  <?skip?>\n  <circuit><![CDATA[<raw>]]></circuit>\n</project>\n<!-- trailing -->",
+        )
+        .unwrap();
+        assert_eq!(
+            root.children
+                .iter()
+                .map(|child| child.name.as_str())
+                .collect::<Vec<_>>(),
+            ["lib", "circuit"]
+        );
+        assert_eq!(root.child("lib").unwrap().line, 6);
+        assert_eq!(root.child("circuit").unwrap().text, "<raw>");
+        assert!(parse("<a><!-- b</a>").is_err());
+    }
+
+    #[test]
+    fn mismatched_elements() {
+        assert_eq!(
+            parse("<a>\n<b></a>").unwrap_err(),
+            "line 2: expected `</b>`, found `</a>`"
+        );
+        assert!(parse("<a></a><b/>").is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::hash_map::DefaultHasher;
+use std::fmt;
 use std::hash::Hasher;
 
 use serde::{Deserialize, Serialize};
@@ -24,3 +25,9 @@ impl Id {
         Self(0)
     }
 }
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
@@ -0,0 +1,467 @@
+// versioned layout of `.lrsproj` files: every file starts with a header naming the format and
+// its version, older versions are migrated step by step before the project gets deserialized
+
+use super::Project;
+use crate::simulator::{Block, Connection, Module, Plot};
+use serde::{
+    de::{self, DeserializeOwned, IgnoredAny, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt,
+    io::Read,
+};
+
+pub const FORMAT: &str = "logicrs-project";
+pub const VERSION: u64 = 1;
+
+// migrations from each version to the next one, `MIGRATIONS[n]` upgrades version `n` to `n + 1`
+const MIGRATIONS: [fn(&mut Map<String, Value>) -> Result<(), String>; VERSION as usize] =
+    [migrate_v0];
+
+// version 0: files written before the header was introduced, which stored the runtime-only
+// `output_state` of blocks, the state stack of plots and the simulation cache of custom modules,
+// all of which get recomputed after loading
+fn migrate_v0(project: &mut Map<String, Value>) -> Result<(), String> {
+    project
+        .get_mut("modules")
+        .and_then(Value::as_object_mut)
+        .into_iter()
+        .flat_map(|modules| modules.values_mut())
+        .filter_map(|module| module.get_mut("custom_data").and_then(Value::as_object_mut))
+        .for_each(|custom| {
+            custom.remove("cache");
+        });
+
+    for_each_plot(project, |plot| {
+        plot.remove("states");
+        blocks(plot).for_each(|block| {
+            block.remove("output_state");
+        });
+    });
+    Ok(())
+}
+
+// calls `func` with the main plot and the plots of all custom modules
+fn for_each_plot(project: &mut Map<String, Value>, mut func: impl FnMut(&mut Map<String, Value>)) {
+    for (key, value) in project.iter_mut() {
+        match key.as_str() {
+            "modules" => value
+                .as_object_mut()
+                .into_iter()
+                .flat_map(|modules| modules.values_mut())
+                .filter_map(|module| module.pointer_mut("/custom_data/plot"))
+                .filter_map(Value::as_object_mut)
+                .for_each(&mut func),
+            "main_plot" => value.as_object_mut().into_iter().for_each(&mut func),
+            _ => (),
+        }
+    }
+}
+
+fn blocks(plot: &mut Map<String, Value>) -> impl Iterator<Item = &mut Map<String, Value>> {
+    plot.get_mut("blocks")
+        .and_then(Value::as_object_mut)
+        .into_iter()
+        .flat_map(|blocks| blocks.values_mut())
+        .filter_map(Value::as_object_mut)
+}
+
+// checks the format name and returns the version of a file's header
+fn check_header(header: &Map<String, Value>) -> Result<u64, String> {
+    if let Some(format) = header.get("format")
+        && format.as_str() != Some(FORMAT)
+    {
+        return Err(format!(
+            "unknown file format {format}, expected \"{FORMAT}\""
+        ));
+    }
+
+    // files without a header predate versioning
+    let version = match header.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| format!("invalid format version {version}"))?,
+    };
+    if version > VERSION {
+        return Err(format!(
+            "the file was saved by a newer release of LogicRs (format version {version}); \
+             this release only supports up to format version {VERSION}"
+        ));
+    }
+    Ok(version)
+}
+
+// reads the header and upgrades the contents to the current version
+pub fn migrate(value: &mut Value) -> Result<(), String> {
+    let Value::Object(project) = value else {
+        return Err(String::from("not a LogicRs project file"));
+    };
+
+    let version = check_header(project)?;
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(project).map_err(|err| {
+            format!(
+                "could not upgrade from format version {from} to {}: {err}",
+                from + 1
+            )
+        })?;
+        info!(
+            "Migrated project from format version {from} to {}",
+            from + 1
+        );
+    }
+
+    project.insert(String::from("format"), Value::from(FORMAT));
+    project.insert(String::from("version"), Value::from(VERSION));
+    Ok(())
+}
+
+// deserializes an up-to-date project; errors name the module and block that are malformed
+pub fn deserialize(value: &Value) -> Result<Project, String> {
+    Project::deserialize(value).map_err(|err| locate(value).unwrap_or_else(|| err.to_string()))
+}
+
+// reads a project from the stream returned by `open`; files of the current version are
+// deserialized while reading, only older ones are collected into a `Value` to be migrated first.
+// If that fails, the file is opened again and read into a `Value` to locate the malformed part
+pub fn read<R: Read>(mut open: impl FnMut() -> Result<R, String>) -> Result<Project, String> {
+    let mut deserializer = serde_json::Deserializer::from_reader(open()?);
+    let err = match deserializer
+        .deserialize_map(ProjectVisitor)
+        .and_then(|project| deserializer.end().map(|_| project))
+    {
+        Ok(project) => return Ok(project),
+        Err(err) => err,
+    };
+
+    let mut value = serde_json::from_reader::<_, Value>(open()?)
+        .map_err(|_| format!("not a valid project file: {err}"))?;
+    migrate(&mut value)?;
+    deserialize(&value)
+}
+
+struct ProjectVisitor;
+
+impl<'de> Visitor<'de> for ProjectVisitor {
+    type Value = Project;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a LogicRs project")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Project, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        // the header is written before all other fields
+        let mut header = Map::new();
+        let mut key = map.next_key::<String>()?;
+        while let Some(name) = key.take_if(|name| matches!(name.as_str(), "format" | "version")) {
+            header.insert(name, map.next_value()?);
+            key = map.next_key()?;
+        }
+
+        if check_header(&header).map_err(de::Error::custom)? < VERSION {
+            let mut project = header;
+            while let Some(name) = key {
+                project.insert(name, map.next_value()?);
+                key = map.next_key()?;
+            }
+
+            let mut value = Value::Object(project);
+            migrate(&mut value).map_err(de::Error::custom)?;
+            return deserialize(&value).map_err(de::Error::custom);
+        }
+
+        let (mut modules, mut main_plot, mut tps) = (None, None, None);
+        while let Some(name) = key {
+            match name.as_str() {
+                "modules" => modules = Some(map.next_value()?),
+                "main_plot" => main_plot = Some(map.next_value()?),
+                "tps" => tps = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+            key = map.next_key()?;
+        }
+
+        Ok(Project {
+            modules: modules.ok_or_else(|| de::Error::missing_field("modules"))?,
+            main_plot: main_plot.ok_or_else(|| de::Error::missing_field("main_plot"))?,
+            tps: tps.ok_or_else(|| de::Error::missing_field("tps"))?,
+        })
+    }
+}
+
+fn check<T: DeserializeOwned>(value: &Value, context: impl FnOnce() -> String) -> Option<String> {
+    T::deserialize(value)
+        .err()
+        .map(|err| format!("{}: {err}", context()))
+}
+
+// finds the innermost part of the project that fails to deserialize
+fn locate(value: &Value) -> Option<String> {
+    if let Some(modules) = value.get("modules").and_then(Value::as_object) {
+        for (name, module) in modules {
+            let context = format!("module `{name}`");
+            if let Some(plot) = module.pointer("/custom_data/plot")
+                && let Some(err) = locate_in_plot(plot, &context)
+            {
+                return Some(err);
+            }
+            if let Some(err) = check::<Module>(module, || context) {
+                return Some(err);
+            }
+        }
+    }
+
+    value
+        .get("main_plot")
+        .and_then(|plot| locate_in_plot(plot, "main plot"))
+}
+
+fn locate_in_plot(plot: &Value, context: &str) -> Option<String> {
+    let items = |key| {
+        plot.get(key)
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+    };
+
+    for (id, block) in items("blocks") {
+        let error = check::<Block>(block, || {
+            let name = block.get("name").and_then(Value::as_str).unwrap_or("?");
+            match block.get("position").and_then(Value::as_array) {
+                Some(position) if position.len() == 2 => format!(
+                    "{context}, block {id} (`{name}` at {}, {})",
+                    position[0], position[1]
+                ),
+                _ => format!("{context}, block {id} (`{name}`)"),
+            }
+        });
+        if error.is_some() {
+            return error;
+        }
+    }
+
+    for (id, connection) in items("connections") {
+        let error = check::<Connection>(connection, || format!("{context}, connection {id}"));
+        if error.is_some() {
+            return error;
+        }
+    }
+
+    check::<Plot>(plot, || context.to_string())
+}
+
+// checks references between blocks, connections and modules of a deserialized project
+pub fn validate(project: &Project) -> Result<(), String> {
+    let plots = [(String::from("main plot"), project.main_plot())]
+        .into_iter()
+        .chain(project.modules().iter().filter_map(|(name, module)| {
+            module.plot().map(|plot| (format!("module `{name}`"), plot))
+        }));
+
+    for (context, plot) in plots {
+        for (id, block) in plot.blocks() {
+            let block_context = || format!("{context}, block {id} (`{}`)", block.module_id());
+            if project.module(block.module_id()).is_none() {
+                return Err(format!(
+                    "{}: unknown module `{}`",
+                    block_context(),
+                    block.module_id()
+                ));
+            }
+
+            let connections = block.inputs().iter().chain(block.outputs().iter());
+            if let Some(missing) = connections
+                .flatten()
+                .find(|connection| plot.get_connection(connection).is_none())
+            {
+                return Err(format!(
+                    "{}: refers to missing connection {missing}",
+                    block_context()
+                ));
+            }
+        }
+
+        for (id, connection) in plot.connections() {
+            let blocks = [connection.origin()]
+                .into_iter()
+                .chain(connection.destinations())
+                .map(|port| port.block_id())
+                .collect::<HashSet<_>>();
+            if let Some(missing) = blocks
+                .into_iter()
+                .find(|block| plot.get_block(*block).is_none())
+            {
+                return Err(format!(
+                    "{context}, connection {id}: refers to missing block {missing}"
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// `serialize_with` helpers writing hash maps and sets ordered by their keys, so that saving an
+// unchanged project produces the same file and diffs only show what was actually edited
+
+pub fn sorted_map<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    serializer.collect_map(map.iter().collect::<BTreeMap<_, _>>())
+}
+
+pub fn sorted_set<T, S>(set: &HashSet<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Ord + Serialize,
+    S: Serializer,
+{
+    serializer.collect_seq(set.iter().collect::<BTreeSet<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::builtin::BUILTINS;
+
+    // example projects predate the header and are stored in version 0
+    const EXAMPLE: &str = include_str!("../../examples/1_4_demux.lrsproj");
+
+    fn load(source: &str) -> Result<Project, String> {
+        let mut project = read(|| Ok(source.as_bytes()))?;
+        BUILTINS
+            .iter()
+            .for_each(|(_, builtin)| project.add_module(builtin.module().clone()));
+        Ok(project)
+    }
+
+    #[test]
+    fn migrate_version_0() {
+        let mut value = serde_json::from_str::<Value>(EXAMPLE).unwrap();
+        assert!(value.get("version").is_none());
+
+        migrate(&mut value).unwrap();
+        assert_eq!(value["format"], FORMAT);
+        assert_eq!(value["version"], VERSION);
+        let mut project = value.as_object().unwrap().clone();
+        for_each_plot(&mut project, |plot| {
+            assert!(!plot.contains_key("states"));
+            assert!(blocks(plot).all(|block| !block.contains_key("output_state")));
+        });
+
+        let project = deserialize(&value).unwrap();
+        assert!(project.modules().contains_key("3bitAnd"));
+        assert_eq!(project.main_plot().connections().len(), 9);
+    }
+
+    #[test]
+    fn read_version_0() {
+        let project = load(EXAMPLE).unwrap();
+        assert_eq!(
+            project
+                .plot(&String::from("3bitAnd"))
+                .unwrap()
+                .connections()
+                .len(),
+            8
+        );
+        validate(&project).unwrap();
+    }
+
+    #[test]
+    fn read_current_version() {
+        let project = load(EXAMPLE).unwrap();
+        let saved = serde_json::to_string(&project).unwrap();
+        let reloaded = load(&saved).unwrap();
+        assert_eq!(serde_json::to_string(&reloaded).unwrap(), saved);
+    }
+
+    #[test]
+    fn reject_newer_version() {
+        let source = format!(r#"{{"format": "{FORMAT}", "version": {}}}"#, VERSION + 1);
+        let err = read(|| Ok(source.as_bytes())).unwrap_err();
+        assert!(err.contains("newer release"), "{err}");
+        assert!(
+            err.contains(&format!("format version {}", VERSION + 1)),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn reject_unknown_format() {
+        let source = r#"{"format": "something-else", "version": 1}"#;
+        let err = read(|| Ok(source.as_bytes())).unwrap_err();
+        assert!(
+            err.contains("unknown file format \"something-else\""),
+            "{err}"
+        );
+
+        let mut value = Value::from(vec![1, 2, 3]);
+        assert_eq!(
+            migrate(&mut value).unwrap_err(),
+            "not a LogicRs project file"
+        );
+    }
+
+    #[test]
+    fn locate_malformed_block() {
+        let mut value = serde_json::from_str::<Value>(EXAMPLE).unwrap();
+        migrate(&mut value).unwrap();
+        let block = value["main_plot"]["blocks"]
+            .as_object_mut()
+            .unwrap()
+            .values_mut()
+            .next()
+            .unwrap();
+        block["inputs"] = Value::from("none");
+
+        let err = deserialize(&value).unwrap_err();
+        assert!(err.starts_with("main plot, block "), "{err}");
+    }
+
+    #[test]
+    fn read_malformed_block() {
+        let mut value = serde_json::to_value(load(EXAMPLE).unwrap()).unwrap();
+        let block = value["main_plot"]["blocks"]
+            .as_object_mut()
+            .unwrap()
+            .values_mut()
+            .next()
+            .unwrap();
+        block["inputs"] = Value::from("none");
+        let source = value.to_string();
+
+        let err = read(|| Ok(source.as_bytes())).unwrap_err();
+        assert!(err.starts_with("main plot, block "), "{err}");
+
+        let err = read(|| Ok(&source.as_bytes()[..source.len() / 2])).unwrap_err();
+        assert!(err.starts_with("not a valid project file: "), "{err}");
+    }
+
+    #[test]
+    fn validate_dangling_connection() {
+        let mut project = load(EXAMPLE).unwrap();
+        let module = project.modules_mut().get_mut("3bitAnd").unwrap();
+        let connections = module.plot_mut().unwrap().connections_mut();
+        let id = *connections.keys().next().unwrap();
+        connections.remove(&id);
+
+        let err = validate(&project).unwrap_err();
+        assert!(err.starts_with("module `3bitAnd`, block "), "{err}");
+        assert!(
+            err.ends_with(&format!("refers to missing connection {id}")),
+            "{err}"
+        );
+    }
+}
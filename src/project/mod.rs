@@ -0,0 +1,462 @@
+pub mod format;
+
+use crate::{
+    simulator::{builtin::BUILTINS, *},
+    FileExtension,
+};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use gtk::{gio, prelude::FileExt};
+use serde::{ser::SerializeStruct, Deserialize, Serialize};
+use std::{
+    collections::*,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    sync::*,
+};
+
+pub type ProjectRef = Arc<Mutex<Project>>;
+
+#[derive(Deserialize)]
+pub struct Project {
+    modules: HashMap<String, Module>,
+    main_plot: Plot,
+    tps: i32,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self::new(
+            builtin::BUILTINS
+                .iter()
+                .map(|(_, builtin)| builtin.module().clone())
+                .collect(),
+        )
+    }
+}
+
+impl Serialize for Project {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("Project", 5)?;
+        state.serialize_field("format", format::FORMAT)?;
+        state.serialize_field("version", &format::VERSION)?;
+        state.serialize_field(
+            "modules",
+            &BTreeMap::<&String, &Module>::from_iter(
+                self.modules.iter().filter(|(_, module)| !module.builtin()),
+            ),
+        )?;
+        state.serialize_field("main_plot", &self.main_plot)?;
+        state.serialize_field("tps", &self.tps)?;
+        state.end()
+    }
+}
+
+impl FileExtension for Project {
+    const FILE_EXTENSION: &'static str = "lrsproj";
+    const FILE_PATTERN: &'static str = "*.lrsproj";
+
+    fn file_filter() -> gtk::FileFilter {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("LogicRs project files"));
+        filter.add_pattern(Self::FILE_PATTERN);
+        filter.add_pattern(Self::COMPRESSED_FILE_PATTERN);
+        filter
+    }
+}
+
+impl Project {
+    // gzip-compressed project files, written when the file name has this extension
+    const COMPRESSED_FILE_EXTENSION: &'static str = "lrsprojz";
+    const COMPRESSED_FILE_PATTERN: &'static str = "*.lrsprojz";
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+    pub fn new(modules: Vec<Module>) -> Self {
+        Self {
+            modules: modules
+                .iter()
+                .map(|module| (module.name().to_owned(), module.clone()))
+                .collect(),
+            main_plot: Plot::new(),
+            tps: Simulator::DEFAULT_TICKS_PER_SECOND,
+        }
+    }
+
+    pub fn load_from(file: &gio::File) -> Result<Self, String> {
+        let path = file.path().unwrap();
+        let open = || -> Result<Box<dyn Read>, String> {
+            let mut reader = BufReader::new(File::open(&path).map_err(|err| err.to_string())?);
+
+            // compressed files are recognized by their contents, regardless of their name
+            let compressed = reader
+                .fill_buf()
+                .map_err(|err| err.to_string())?
+                .starts_with(&Self::GZIP_MAGIC);
+            Ok(match compressed {
+                true => Box::new(BufReader::new(GzDecoder::new(reader))),
+                false => Box::new(reader),
+            })
+        };
+        let mut project = format::read(open)?;
+
+        BUILTINS
+            .iter()
+            .for_each(|(_, builtin)| project.add_module(builtin.module().clone()));
+        format::validate(&project)?;
+        project.link_images();
+
+        info!(
+            "Loaded from file `{}`",
+            file.path().unwrap().to_str().unwrap()
+        );
+
+        project
+            .iter_plots_mut()
+            .for_each(|plot| plot.update_all_blocks());
+        Ok(project)
+    }
+
+    pub fn write_to(&self, file: &gio::File) -> Result<(), String> {
+        info!(
+            "Writing to `{}` ...",
+            file.path().unwrap().to_str().unwrap()
+        );
+        let f = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(file.path().unwrap())
+            .map_err(|err| err.to_string())?;
+        let mut writer = BufWriter::new(f);
+
+        let compressed = file
+            .path()
+            .and_then(|path| {
+                path.extension()
+                    .map(|ext| ext == Self::COMPRESSED_FILE_EXTENSION)
+            })
+            .unwrap_or(false);
+        match compressed {
+            true => {
+                let mut encoder = GzEncoder::new(&mut writer, Compression::default());
+                self.serialize_to(&mut encoder)?;
+                encoder.finish().map_err(|err| err.to_string())?;
+            }
+            false => self.serialize_to(&mut writer)?,
+        }
+        writer.flush().map_err(|err| err.to_string())?;
+
+        info!(
+            "Wrote `{}` successfully",
+            file.path().unwrap().to_str().unwrap()
+        );
+        Ok(())
+    }
+
+    // streams the project to `writer` without building the whole text in memory first;
+    // pretty-printed with a trailing newline, so files diff nicely in version control
+    fn serialize_to(&self, mut writer: impl Write) -> Result<(), String> {
+        serde_json::to_writer_pretty(&mut writer, self).map_err(|err| err.to_string())?;
+        writer.write_all(b"\n").map_err(|err| err.to_string())
+    }
+
+    pub fn module(&self, name: &String) -> Option<&Module> {
+        self.modules.get(name)
+    }
+
+    pub fn module_mut(&mut self, name: &String) -> Option<&mut Module> {
+        self.modules.get_mut(name)
+    }
+
+    pub fn modules(&self) -> &HashMap<String, Module> {
+        &self.modules
+    }
+
+    pub fn modules_mut(&mut self) -> &mut HashMap<String, Module> {
+        &mut self.modules
+    }
+
+    pub fn add_existing_module(&mut self, module: Module) {
+        self.modules.insert(module.name().clone(), module);
+    }
+
+    pub fn add_module(&mut self, mut module: Module) {
+        if module.plot().is_some() && !module.has_io_blocks() {
+            module.add_io_blocks();
+        }
+
+        self.modules.insert(module.name().clone(), module);
+    }
+
+    // renames the pins of a module and all its instances, returns the previous names
+    pub fn set_pin_names(
+        &mut self,
+        module_name: &String,
+        input_names: Vec<String>,
+        output_names: Vec<String>,
+    ) -> Option<(Vec<String>, Vec<String>)> {
+        let module = self.modules.get_mut(module_name)?;
+        let previous = (module.input_names().clone(), module.output_names().clone());
+        module.set_pin_names(input_names.clone(), output_names.clone());
+
+        // the module's own Input/Output blocks show the names on their inner side
+        if let Some((input_block, output_block)) = module.io_blocks()
+            && let Some(plot) = module.plot_mut()
+        {
+            if let Some(block) = plot.get_block_mut(input_block) {
+                block.set_pin_names(vec![], input_names.clone());
+            }
+            if let Some(block) = plot.get_block_mut(output_block) {
+                block.set_pin_names(output_names.clone(), vec![]);
+            }
+        }
+
+        self.iter_plots_mut()
+            .flat_map(|plot| plot.blocks_mut().values_mut())
+            .filter(|block| block.module_id() == module_name)
+            .for_each(|block| block.set_pin_names(input_names.clone(), output_names.clone()));
+
+        Some(previous)
+    }
+
+    // changes the pin count of a custom module, its Input/Output blocks and all its instances;
+    // returns the previous pin count and the original connections affected in each plot
+    pub fn resize_module(
+        &mut self,
+        module_name: &String,
+        num_inputs: u8,
+        num_outputs: u8,
+    ) -> Option<(
+        (u8, u8),
+        Vec<(PlotDescriptor, Vec<Connection>)>,
+        Option<Shape>,
+    )> {
+        let module = self
+            .modules
+            .get_mut(module_name)
+            .filter(|module| !module.builtin())?;
+        let previous = (module.get_num_inputs(), module.get_num_outputs());
+        module.set_num_pins(num_inputs, num_outputs);
+
+        // a custom shape has a position for every pin, fall back to the default outline if they no longer match
+        let previous_shape = module
+            .shape()
+            .filter(|shape| shape.validate(num_inputs, num_outputs).is_err())
+            .cloned();
+        if previous_shape.is_some() {
+            module.set_shape(None);
+        }
+
+        let mut changed = vec![];
+        if let Some((input_block, output_block)) = module.io_blocks()
+            && let Some(plot) = module.plot_mut()
+        {
+            let mut connections = plot.resize_block(input_block, num_inputs, num_inputs);
+            connections.append(&mut plot.resize_block(output_block, num_outputs, num_outputs));
+            changed.push((PlotDescriptor::Module(module_name.clone()), connections));
+        }
+
+        let descriptors = self
+            .modules
+            .values()
+            .filter(|module| module.plot().is_some())
+            .map(|module| PlotDescriptor::Module(module.name().clone()))
+            .chain(std::iter::once(PlotDescriptor::Main()))
+            .collect::<Vec<_>>();
+
+        for descriptor in descriptors {
+            let Some(plot) = self.plot_by_descriptor_mut(&descriptor) else {
+                continue;
+            };
+
+            let instances = plot
+                .blocks()
+                .values()
+                .filter(|block| block.module_id() == module_name)
+                .map(|block| block.id())
+                .collect::<Vec<_>>();
+
+            let mut connections = vec![];
+            for block_id in instances {
+                if previous_shape.is_some()
+                    && let Some(block) = plot.get_block_mut(block_id)
+                {
+                    block.set_shape(None);
+                }
+                connections.append(&mut plot.resize_block(block_id, num_inputs, num_outputs));
+            }
+
+            if !connections.is_empty() {
+                changed.push((descriptor, connections));
+            }
+        }
+
+        Some((previous, changed, previous_shape))
+    }
+
+    // changes the decoration of a custom module and all its instances, returns the previous one
+    pub fn set_module_decoration(
+        &mut self,
+        module_name: &String,
+        decoration: Decoration,
+    ) -> Option<Decoration> {
+        let module = self
+            .modules
+            .get_mut(module_name)
+            .filter(|module| !module.builtin())?;
+        let previous = module.decoration().clone();
+        module.set_decoration(decoration.clone());
+
+        self.iter_plots_mut()
+            .flat_map(|plot| plot.blocks_mut().values_mut())
+            .filter(|block| block.module_id() == module_name)
+            .for_each(|block| block.set_decoration(decoration.clone()));
+
+        Some(previous)
+    }
+
+    // changes the outline of a custom module and all its instances, returns the previous one
+    pub fn set_module_shape(
+        &mut self,
+        module_name: &String,
+        shape: Option<Shape>,
+    ) -> Option<Option<Shape>> {
+        let module = self
+            .modules
+            .get_mut(module_name)
+            .filter(|module| !module.builtin())?;
+        let previous = module.shape().cloned();
+        module.set_shape(shape.clone());
+
+        self.iter_plots_mut()
+            .flat_map(|plot| plot.blocks_mut().values_mut())
+            .filter(|block| block.module_id() == module_name)
+            .for_each(|block| block.set_shape(shape.clone()));
+
+        Some(previous)
+    }
+
+    // renames a custom module and updates all blocks referencing it
+    pub fn rename_module(&mut self, old_name: &String, new_name: &String) -> Result<(), String> {
+        if new_name.is_empty() {
+            return Err("Invalid name".to_string());
+        }
+        if self.modules.contains_key(new_name) {
+            return Err(format!("Module with name \"{new_name}\" already exists"));
+        }
+
+        let mut module = match self.modules.remove(old_name) {
+            Some(module) if !module.builtin() => module,
+            Some(module) => {
+                self.modules.insert(old_name.clone(), module);
+                return Err(format!("Cannot rename builtin module \"{old_name}\""));
+            }
+            None => return Err(format!("Module \"{old_name}\" does not exist")),
+        };
+        module.set_name(new_name.clone());
+        self.modules.insert(new_name.clone(), module);
+
+        self.iter_plots_mut()
+            .flat_map(|plot| plot.blocks_mut().values_mut())
+            .filter(|block| block.module_id() == old_name)
+            .for_each(|block| block.set_name(new_name.clone()));
+
+        Ok(())
+    }
+
+    // instances are saved without the image of their module, share it again after loading
+    pub fn link_images(&mut self) {
+        let images = self
+            .modules
+            .iter()
+            .filter_map(|(name, module)| match module.decoration() {
+                Decoration::Image(image) => Some((name.clone(), image.clone())),
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+        if images.is_empty() {
+            return;
+        }
+
+        self.iter_plots_mut()
+            .flat_map(|plot| plot.blocks_mut().values_mut())
+            .for_each(|block| {
+                if let Decoration::Image(_) = block.decoration()
+                    && let Some(image) = images.get(block.module_id())
+                {
+                    *block.decoration_mut() = Decoration::Image(image.clone());
+                }
+            });
+    }
+
+    pub fn link_image(&self, block: &mut Block) {
+        if let Decoration::Image(_) = block.decoration()
+            && let Some(module) = self.modules.get(block.module_id())
+            && let Decoration::Image(image) = module.decoration()
+        {
+            *block.decoration_mut() = Decoration::Image(image.clone());
+        }
+    }
+
+    pub fn remove_module(&mut self, module_name: &String) {
+        self.modules.remove(module_name);
+    }
+
+    pub fn main_plot(&self) -> &Plot {
+        &self.main_plot
+    }
+
+    pub fn plot(&self, module_name: &String) -> Option<&Plot> {
+        self.modules
+            .get(module_name)
+            .and_then(|module| module.plot())
+    }
+
+    pub fn main_plot_mut(&mut self) -> &mut Plot {
+        &mut self.main_plot
+    }
+
+    pub fn plot_mut(&mut self, module_name: &String) -> Option<&mut Plot> {
+        self.modules
+            .get_mut(module_name)
+            .and_then(|module| module.plot_mut())
+    }
+
+    pub fn plot_by_descriptor_mut(&mut self, descriptor: &PlotDescriptor) -> Option<&mut Plot> {
+        match descriptor {
+            PlotDescriptor::Main() => Some(&mut self.main_plot),
+            PlotDescriptor::Module(module_name) => self.plot_mut(module_name),
+        }
+    }
+
+    pub fn iter_plots_mut(&mut self) -> impl Iterator<Item = &mut Plot> {
+        self.modules
+            .iter_mut()
+            .filter_map(|(_, module)| module.plot_mut())
+            .chain(std::iter::once(&mut self.main_plot))
+    }
+
+    pub fn tps(&self) -> i32 {
+        self.tps
+    }
+
+    pub fn set_tps(&mut self, tps: i32) {
+        self.tps = tps
+    }
+
+    pub fn collect_dependencies(&self, mod_name: &String, modules: &mut HashMap<String, Module>) {
+        if let Some(plot) = self.modules.get(mod_name).and_then(|module| module.plot()) {
+            plot.blocks().iter().for_each(|(_, block)| {
+                if let Some(module) = self
+                    .modules
+                    .get(block.module_id())
+                    .filter(|m| !m.builtin() && !modules.contains_key(m.name()))
+                {
+                    modules.insert(module.name().clone(), module.clone());
+                    self.collect_dependencies(module.name(), modules);
+                }
+            })
+        }
+    }
+}
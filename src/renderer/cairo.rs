@@ -1,7 +1,37 @@
-use crate::{application::selection::*, simulator::Plot};
+use crate::{
+    application::selection::*,
+    simulator::{ImageData, Plot},
+};
 
 use super::*;
-use gtk::cairo::{Antialias, Context, Error, FontFace};
+use gtk::{
+    cairo::{Antialias, Context, Error, FontFace},
+    gdk::prelude::GdkCairoContextExt,
+    gdk_pixbuf::Pixbuf,
+    gio, glib,
+};
+use std::{cell::RefCell, collections::HashMap};
+
+thread_local! {
+    // decoded images by their generation
+    static IMAGE_CACHE: RefCell<HashMap<u64, Option<Pixbuf>>> = RefCell::new(HashMap::new());
+}
+
+// decodes a PNG or SVG image, returns `None` if the data is not a supported image
+pub fn decode_image(image: &ImageData) -> Option<Pixbuf> {
+    IMAGE_CACHE.with(|cache| {
+        cache
+            .borrow_mut()
+            .entry(image.generation())
+            .or_insert_with(|| {
+                let stream = gio::MemoryInputStream::from_bytes(&glib::Bytes::from(image.data()));
+                Pixbuf::from_stream(&stream, gio::Cancellable::NONE)
+                    .map_err(|err| warn!("could not decode image: {err}"))
+                    .ok()
+            })
+            .clone()
+    })
+}
 
 pub struct CairoRenderer {
     size: Vector2<i32>,
@@ -31,6 +61,13 @@ impl CairoRenderer {
         }
     }
 
+    // renderer drawing directly onto the given context, e.g. for previews outside of a plot
+    pub fn for_context(context: Context) -> Self {
+        let mut renderer = Self::new();
+        renderer.set_context(Some(context));
+        renderer
+    }
+
     #[inline]
     fn set_context(&mut self, context: Option<Context>) -> &mut Self {
         self.context = context;
@@ -47,6 +84,31 @@ impl CairoRenderer {
         self.original_translation = self.translation;
         self
     }
+
+    // renders only the plot contents without grid and selection, e.g. onto an image surface
+    pub fn render_plot(
+        &mut self,
+        plot: &Plot,
+        context: &Context,
+        background: bool,
+    ) -> Result<&mut Self, Error> {
+        self.set_context(Some(context.clone()));
+
+        context.set_antialias(Antialias::Default);
+        context.translate(self.translation.x(), self.translation.y());
+        context.scale(self.scale, self.scale);
+
+        context.set_font_face(&self.font);
+        context.set_font_size(DEFAULT_FONT_SIZE);
+
+        if background {
+            let (bg_color_r, bg_color_g, bg_color_b, _) = unsafe { COLOR_THEME.bg_color };
+            context.set_source_rgb(bg_color_r as f64, bg_color_g as f64, bg_color_b as f64);
+            context.paint()?;
+        }
+
+        plot.render(self, plot).map(|_| self)
+    }
 }
 
 impl Default for CairoRenderer {
@@ -186,12 +248,102 @@ impl Renderer for CairoRenderer {
 
     #[inline]
     fn show_text(&self, text: &str) -> Result<&Self, Error> {
+        let Some(context) = &self.context else {
+            return Ok(self);
+        };
+
+        // keep text readable within mirrored or upside-down blocks
+        let matrix = context.matrix();
+        let mirrored = matrix.xx() * matrix.yy() - matrix.xy() * matrix.yx() < 0.;
+        let upside_down = if mirrored {
+            matrix.xx() > 0.
+        } else {
+            matrix.xx() < 0.
+        };
+        if !mirrored && !upside_down {
+            return context.show_text(text).map(|_| self);
+        }
+
+        let (x, y) = context.current_point()?;
+        let extents = context.text_extents(text)?;
+        let center = (
+            x + extents.x_bearing() + extents.width() / 2.,
+            y + extents.y_bearing() + extents.height() / 2.,
+        );
+
+        context.save()?;
+        context.translate(center.0, center.1);
+        if mirrored {
+            context.scale(-1., 1.);
+        }
+        if upside_down {
+            context.rotate(std::f64::consts::PI);
+        }
+        context.translate(-center.0, -center.1);
+        context.move_to(x, y);
+        context.show_text(text)?;
+        context.restore()?;
+        context.move_to(x + extents.x_advance(), y + extents.y_advance());
+        Ok(self)
+    }
+
+    fn image(
+        &self,
+        position: Vector2<i32>,
+        size: Vector2<i32>,
+        image: &ImageData,
+    ) -> Result<&Self, Error> {
+        let (Some(context), Some(pixbuf)) = (&self.context, decode_image(image)) else {
+            return Ok(self);
+        };
+
+        let (width, height) = (pixbuf.width() as f64, pixbuf.height() as f64);
+        let scale = f64::min(size.0 as f64 / width, size.1 as f64 / height);
+        if scale <= 0. {
+            return Ok(self);
+        }
+
+        context.save()?;
+        context.translate(
+            position.0 as f64 + (size.0 as f64 - width * scale) / 2.,
+            position.1 as f64 + (size.1 as f64 - height * scale) / 2.,
+        );
+        context.scale(scale, scale);
+        context.set_source_pixbuf(&pixbuf, 0., 0.);
+        context.rectangle(0., 0., width, height);
+        context.fill()?;
+        context.restore()?;
+        Ok(self)
+    }
+
+    fn save(&self) -> Result<&Self, Error> {
+        match &self.context {
+            Some(context) => context.save().map(|_| self),
+            None => Ok(self),
+        }
+    }
+
+    fn restore(&self) -> Result<&Self, Error> {
         match &self.context {
-            Some(context) => context.show_text(text).map(|_| self),
+            Some(context) => context.restore().map(|_| self),
             None => Ok(self),
         }
     }
 
+    fn transform(&self, origin: Vector2<i32>, x_axis: Vector2<i32>, y_axis: Vector2<i32>) -> &Self {
+        if let Some(context) = &self.context {
+            context.transform(cairo::Matrix::new(
+                x_axis.0 as f64,
+                x_axis.1 as f64,
+                y_axis.0 as f64,
+                y_axis.1 as f64,
+                origin.0 as f64,
+                origin.1 as f64,
+            ));
+        }
+        self
+    }
+
     #[inline]
     fn arc(&self, position: Vector2<i32>, radius: f64, angle1: f64, angle2: f64) -> &Self {
         if let Some(context) = &self.context {
@@ -200,6 +352,14 @@ impl Renderer for CairoRenderer {
         self
     }
 
+    #[inline]
+    fn arc_negative(&self, position: Vector2<i32>, radius: f64, angle1: f64, angle2: f64) -> &Self {
+        if let Some(context) = &self.context {
+            context.arc_negative(position.0 as f64, position.1 as f64, radius, angle1, angle2);
+        }
+        self
+    }
+
     #[inline]
     fn rectangle(&self, position: Vector2<i32>, size: Vector2<i32>) -> &Self {
         if let Some(context) = &self.context {
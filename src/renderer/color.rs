@@ -80,7 +80,16 @@ impl Theme {
         unsafe { COLOR_THEME = Self::from(&style_manager) }
     }
 
-    const DARK: Self = Self {
+    // makes this the global theme while `func` runs, e.g. for drawing outside of the editor
+    pub fn with<T>(self, func: impl FnOnce() -> T) -> T {
+        let previous = unsafe { COLOR_THEME };
+        unsafe { COLOR_THEME = self };
+        let result = func();
+        unsafe { COLOR_THEME = previous };
+        result
+    }
+
+    pub const DARK: Self = Self {
         bg_color: (0.1, 0.1, 0.1, 1.),
         border_color: (0.23, 0.23, 0.23, 1.),
         block_bg_color: (0.13, 0.13, 0.13, 1.),
@@ -103,7 +112,7 @@ impl Theme {
         decoration_fg_color: (0.8, 0.8, 0.8, 1.0),
     };
 
-    const LIGHT: Self = Self {
+    pub const LIGHT: Self = Self {
         bg_color: hex_to_color(0xfffafafa),
         border_color: (0.65, 0.65, 0.65, 1.),
         block_bg_color: hex_to_color(0xfffafafa),
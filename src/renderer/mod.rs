@@ -1,10 +1,17 @@
-use gtk::DrawingArea;
+use gtk::{
+    glib::{self, ToVariant},
+    DrawingArea,
+};
+use serde::{Deserialize, Serialize};
 
 pub mod cairo;
 pub mod color;
 pub mod vector;
 
-use crate::{application::editor::EditorMode, simulator::Plot};
+use crate::{
+    application::editor::EditorMode,
+    simulator::{ImageData, Plot},
+};
 pub use {cairo::*, color::*};
 
 use self::vector::*;
@@ -16,6 +23,44 @@ pub const DEFAULT_FONT_SIZE: f64 = 15.0;
 
 pub type ScreenSpace = Vector2<Vector2<f64>>;
 
+pub static mut GATE_STYLE: GateStyle = GateStyle::Iec;
+
+// how builtin logic gates are drawn
+#[derive(Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GateStyle {
+    // rectangular IEC 60617 symbols ("&", "≥1", ...)
+    #[default]
+    Iec = 0,
+    // ANSI/IEEE 91 distinctive shapes
+    Ansi = 1,
+}
+
+impl GateStyle {
+    pub fn get() -> Self {
+        unsafe { GATE_STYLE }
+    }
+
+    pub fn set(style: Self) {
+        unsafe { GATE_STYLE = style }
+    }
+}
+
+impl ToVariant for GateStyle {
+    fn to_variant(&self) -> glib::Variant {
+        (*self as isize as u8).to_variant()
+    }
+}
+
+impl From<u8> for GateStyle {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Iec,
+            1 => Self::Ansi,
+            _ => panic!(),
+        }
+    }
+}
+
 pub trait Renderable {
     fn render<R>(&self, renderer: &R, data: &Plot) -> Result<(), R::Error>
     where
@@ -79,6 +124,7 @@ pub trait Renderer: Default {
 
     // shape functions
     fn arc(&self, position: Vector2<i32>, radius: f64, angle1: f64, angle2: f64) -> &Self;
+    fn arc_negative(&self, position: Vector2<i32>, radius: f64, angle1: f64, angle2: f64) -> &Self;
     fn rectangle(&self, position: Vector2<i32>, size: Vector2<i32>) -> &Self;
 
     fn move_to(&self, position: Vector2<i32>) -> &Self;
@@ -90,6 +136,19 @@ pub trait Renderer: Default {
     fn fill_preserve(&self) -> Result<&Self, Self::Error>;
     fn stroke(&self) -> Result<&Self, Self::Error>;
     fn show_text(&self, text: &str) -> Result<&Self, Self::Error>;
+    // draws an encoded image, scaled to fit into the given area
+    fn image(
+        &self,
+        position: Vector2<i32>,
+        size: Vector2<i32>,
+        image: &ImageData,
+    ) -> Result<&Self, Self::Error>;
+
+    // transformations, undone by restoring the previously saved state
+    fn save(&self) -> Result<&Self, Self::Error>;
+    fn restore(&self) -> Result<&Self, Self::Error>;
+    // maps a point p to `origin + p.0 * x_axis + p.1 * y_axis`
+    fn transform(&self, origin: Vector2<i32>, x_axis: Vector2<i32>, y_axis: Vector2<i32>) -> &Self;
 
     //
     // more complex shapes building on the backend-specific basic functions
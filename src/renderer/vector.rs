@@ -6,7 +6,9 @@ pub trait VectorCast<To> {
     fn cast(value: Self) -> Vector2<To>;
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default, Clone, Copy, Serialize, Deserialize)]
+#[derive(
+    Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Clone, Copy, Serialize, Deserialize,
+)]
 pub struct Vector2<T>(pub T, pub T);
 
 impl<T: Copy> Vector2<T> {
@@ -0,0 +1,132 @@
+use crate::{
+    id::Id,
+    renderer::{vector::Vector2, *},
+};
+use serde::{Deserialize, Serialize};
+
+use super::Plot;
+
+pub type AnnotationID = Id;
+
+// free-text note placed on the canvas, not taking part in the simulation
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Annotation {
+    id: AnnotationID,
+    text: String,
+    position: Vector2<i32>,
+
+    #[serde(skip)]
+    highlighted: bool,
+}
+
+impl Annotation {
+    const FONT_SIZE: f64 = 14.0;
+    const CHAR_WIDTH: i32 = 8;
+    const LINE_HEIGHT: i32 = 18;
+    const PADDING: i32 = 6;
+
+    pub fn new(text: String, position: Vector2<i32>) -> Self {
+        Self {
+            id: Id::new(),
+            text,
+            position,
+            highlighted: false,
+        }
+    }
+
+    pub fn id(&self) -> AnnotationID {
+        self.id
+    }
+
+    pub fn text(&self) -> &String {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+    }
+
+    pub fn position(&self) -> Vector2<i32> {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vector2<i32>) {
+        self.position = position;
+    }
+
+    pub fn highlighted(&self) -> bool {
+        self.highlighted
+    }
+
+    pub fn set_highlighted(&mut self, highlighted: bool) {
+        self.highlighted = highlighted;
+    }
+
+    pub fn size(&self) -> Vector2<i32> {
+        let columns = self
+            .text
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(1) as i32;
+        let rows = self.text.lines().count().max(1) as i32;
+
+        Vector2(
+            columns * Self::CHAR_WIDTH + 2 * Self::PADDING,
+            rows * Self::LINE_HEIGHT + 2 * Self::PADDING,
+        )
+    }
+
+    pub fn is_in_area(&self, area: &ScreenSpace) -> bool {
+        let size = self.size();
+        !(self.position.0 > area.1 .0 as i32
+            || self.position.1 > area.1 .1 as i32
+            || self.position.0 + size.0 < area.0 .0 as i32
+            || self.position.1 + size.1 < area.0 .1 as i32)
+    }
+
+    pub fn touches(&self, point: Vector2<i32>) -> bool {
+        let size = self.size();
+        point.0 > self.position.0
+            && point.0 < self.position.0 + size.0
+            && point.1 > self.position.1
+            && point.1 < self.position.1 + size.1
+    }
+}
+
+impl Renderable for Annotation {
+    fn render<R>(&self, renderer: &R, _plot: &Plot) -> Result<(), R::Error>
+    where
+        R: Renderer,
+    {
+        renderer
+            .rounded_rect(self.position, self.size(), 4)
+            .set_line_width(1.)
+            .set_color(unsafe {
+                if self.highlighted {
+                    &COLOR_THEME.accent_fg_color
+                } else {
+                    &COLOR_THEME.border_color
+                }
+            })
+            .stroke()?
+            .set_font_size(Self::FONT_SIZE)
+            .set_color(unsafe { &COLOR_THEME.decoration_fg_color });
+
+        for (i, line) in self.text.lines().enumerate() {
+            renderer
+                .move_to(
+                    self.position
+                        + Vector2(
+                            Self::PADDING,
+                            Self::PADDING + (i as i32 + 1) * Self::LINE_HEIGHT - 4,
+                        ),
+                )
+                .show_text(line)?;
+        }
+
+        renderer.set_font_size(DEFAULT_FONT_SIZE);
+        Ok(())
+    }
+}
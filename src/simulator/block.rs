@@ -24,6 +24,8 @@ pub type BlockID = Id;
 pub struct Block {
     id: BlockID,
     name: String,
+    #[serde(default)]
+    label: Option<String>,
 
     position: Vector2<i32>,
     size: Vector2<i32>,
@@ -35,12 +37,23 @@ pub struct Block {
 
     inputs: Vec<Option<ConnectionID>>,
     outputs: Vec<Option<ConnectionID>>,
+    #[serde(default)]
+    input_names: Vec<String>,
+    #[serde(default)]
+    output_names: Vec<String>,
 
     state: State,
+    // recomputed by the first simulation step after loading
+    #[serde(skip)]
     output_state: u128,
 
+    #[serde(serialize_with = "Decoration::serialize_instance")]
     decoration: Decoration,
     color: Option<Color>,
+    #[serde(default)]
+    shape: Option<Shape>,
+    #[serde(default)]
+    orientation: Orientation,
 }
 
 impl Identifiable for Block {
@@ -50,6 +63,9 @@ impl Identifiable for Block {
 impl Block {
     pub const MAX_CONNECTIONS: u8 = 128;
 
+    const PIN_NAME_FONT_SIZE: f64 = 11.;
+    const PIN_NAME_CHAR_WIDTH: i32 = 7;
+
     pub fn new_sized(
         module: &&Module,
         position: Vector2<i32>,
@@ -59,18 +75,18 @@ impl Block {
         color: Option<Color>,
     ) -> Self {
         let name = module.name().clone();
-        Self {
+        let mut block = Self {
             id: Id::new(),
             position,
-            size: Vector2(
-                cmp::max(75, (name.len() * 10) as i32),
-                cmp::max(num_inputs, num_outputs) as i32 * 25 + 50,
-            ),
+            size: Vector2(0, 0),
             highlighted: false,
             unique,
             passthrough: true,
+            label: None,
             inputs: vec![None; num_inputs as usize],
             outputs: vec![None; num_outputs as usize],
+            input_names: module.input_names().clone(),
+            output_names: module.output_names().clone(),
             name,
             state: if module.builtin() {
                 State::Direct(0)
@@ -79,8 +95,12 @@ impl Block {
             },
             decoration: module.decoration().clone(),
             color,
+            shape: module.shape().cloned(),
+            orientation: Orientation::default(),
             output_state: 0,
-        }
+        };
+        block.update_size();
+        block
     }
 
     pub fn new(module: &&Module, position: Vector2<i32>, color: Option<Color>) -> Self {
@@ -94,6 +114,55 @@ impl Block {
         )
     }
 
+    fn min_size(
+        name: &str,
+        num_inputs: u8,
+        num_outputs: u8,
+        pin_names_width: i32,
+        decoration: &Decoration,
+    ) -> Vector2<i32> {
+        let size = Vector2(
+            cmp::max(75, cmp::max((name.len() * 10) as i32, pin_names_width)),
+            cmp::max(num_inputs, num_outputs) as i32 * 25 + 50,
+        );
+
+        match decoration.content_size() {
+            Some(content) => Vector2(cmp::max(size.0, content.0), cmp::max(size.1, content.1)),
+            None => size,
+        }
+    }
+
+    pub fn update_size(&mut self) {
+        if let Some(shape) = &self.shape {
+            self.size = shape.size();
+            return;
+        }
+
+        self.size = Self::min_size(
+            self.header(),
+            self.inputs.len() as u8,
+            self.outputs.len() as u8,
+            self.pin_names_width(),
+            &self.decoration,
+        );
+    }
+
+    // width needed to show the longest input and output names side by side
+    fn pin_names_width(&self) -> i32 {
+        let longest = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| name.chars().count())
+                .max()
+                .unwrap_or(0) as i32
+        };
+
+        match (longest(&self.input_names), longest(&self.output_names)) {
+            (0, 0) => 0,
+            (inputs, outputs) => (inputs + outputs + 2) * Self::PIN_NAME_CHAR_WIDTH + 20,
+        }
+    }
+
     pub fn set_color(&mut self, mut color: Option<Color>) {
         if let Some(c) = color
             && c == unsafe { COLOR_THEME.border_color }
@@ -112,10 +181,65 @@ impl Block {
         &self.name
     }
 
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.update_size();
+    }
+
     pub fn unique(&self) -> bool {
         self.unique
     }
 
+    pub fn label(&self) -> Option<&String> {
+        self.label.as_ref()
+    }
+
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label.filter(|label| !label.is_empty());
+        self.update_size();
+    }
+
+    pub fn input_names(&self) -> &Vec<String> {
+        &self.input_names
+    }
+
+    pub fn output_names(&self) -> &Vec<String> {
+        &self.output_names
+    }
+
+    pub fn with_pin_names(mut self, input_names: Vec<String>, output_names: Vec<String>) -> Self {
+        self.set_pin_names(input_names, output_names);
+        self
+    }
+
+    pub fn set_pin_names(&mut self, input_names: Vec<String>, output_names: Vec<String>) {
+        self.input_names = input_names;
+        self.output_names = output_names;
+        self.update_size();
+    }
+
+    pub fn pin_name(&self, connector: Connector) -> Option<&str> {
+        match connector {
+            Connector::Input(i) => self.input_names.get(i as usize),
+            Connector::Output(i) => self.output_names.get(i as usize),
+        }
+        .map(String::as_str)
+        .filter(|name| !name.is_empty())
+    }
+
+    // builtin gates are drawn with their distinctive shape when the ANSI/IEEE gate style is enabled
+    pub fn distinctive_gate(&self) -> Option<DistinctiveGate> {
+        match GateStyle::get() {
+            GateStyle::Ansi if self.shape.is_none() => DistinctiveGate::of(&self.name),
+            _ => None,
+        }
+    }
+
+    // text shown in the block's header
+    pub fn header(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
+
     pub fn id(&self) -> BlockID {
         self.id
     }
@@ -129,17 +253,19 @@ impl Block {
     }
 
     pub fn is_in_area(&self, area: &Vector2<Vector2<f64>>) -> bool {
+        let bounds = self.bounds();
         !(self.position.0 > area.1 .0 as i32
             || self.position.1 > area.1 .1 as i32
-            || self.position.0 + self.size.0 < area.0 .0 as i32
-            || self.position.1 + self.size.1 < area.0 .1 as i32)
+            || self.position.0 + bounds.0 < area.0 .0 as i32
+            || self.position.1 + bounds.1 < area.0 .1 as i32)
     }
 
     pub fn touches(&self, point: Vector2<i32>) -> bool {
+        let bounds = self.bounds();
         point.0 > self.position.0 - 3
-            && point.0 < self.position.0 + self.size.0 + 3
+            && point.0 < self.position.0 + bounds.0 + 3
             && point.1 > self.position.1 - 3
-            && point.1 < self.position.1 + self.size.1 + 3
+            && point.1 < self.position.1 + bounds.1 + 3
     }
 
     pub fn set_highlighted(&mut self, highlighted: bool) {
@@ -158,10 +284,30 @@ impl Block {
         self.position
     }
 
+    // size of the block before rotation, see `bounds` for the size it takes up on the plot
     pub fn size(&self) -> Vector2<i32> {
         self.size
     }
 
+    pub fn bounds(&self) -> Vector2<i32> {
+        self.orientation.size(self.size)
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
+    // rotates or mirrors the block around its center
+    pub fn transform(&mut self, transformation: Transformation) {
+        let bounds = self.bounds();
+        self.orientation = self.orientation.transformed(transformation);
+        self.position += (bounds - self.bounds()) / Vector2(2, 2);
+    }
+
     pub fn connected_to(&self) -> Vec<ConnectionID> {
         self.inputs
             .iter()
@@ -175,6 +321,28 @@ impl Block {
     }
 
     pub fn get_connector_pos(&self, connector: Connector) -> Vector2<i32> {
+        let local = self.local_connector_pos(connector);
+        self.position + self.orientation.apply(local - self.position, self.size)
+    }
+
+    // direction pointing away from the block at the given connector
+    pub fn connector_direction(&self, connector: Connector) -> Vector2<i32> {
+        self.orientation.direction(match connector {
+            Connector::Input(_) => Vector2(-1, 0),
+            Connector::Output(_) => Vector2(1, 0),
+        })
+    }
+
+    // connector position as if the block was neither rotated nor mirrored
+    fn local_connector_pos(&self, connector: Connector) -> Vector2<i32> {
+        let shaped = self.shape.as_ref().and_then(|shape| match connector {
+            Connector::Input(i) => shape.input_position(i),
+            Connector::Output(i) => shape.output_position(i),
+        });
+        if let Some(offset) = shaped {
+            return self.position + offset;
+        }
+
         match connector {
             Connector::Input(i) => Vector2(self.position.0, self.position.1 + 25 * i as i32 + 50),
             Connector::Output(i) => Vector2(
@@ -219,6 +387,45 @@ impl Block {
         &mut self.inputs
     }
 
+    pub fn decoration(&self) -> &Decoration {
+        &self.decoration
+    }
+
+    pub fn decoration_mut(&mut self) -> &mut Decoration {
+        &mut self.decoration
+    }
+
+    pub fn shape(&self) -> Option<&Shape> {
+        self.shape.as_ref()
+    }
+
+    pub fn set_shape(&mut self, shape: Option<Shape>) {
+        self.shape = shape;
+        self.update_size();
+    }
+
+    pub fn set_decoration(&mut self, decoration: Decoration) {
+        self.decoration = decoration;
+        self.update_size();
+    }
+
+    pub fn set_parameter(&mut self, parameter: Parameter, value: u128) {
+        self.decoration.set_parameter(parameter, value);
+        self.update_size();
+
+        // restart the sequence of random blocks from the new seed
+        if parameter == Parameter::Seed {
+            self.set_bytes(0);
+        }
+    }
+
+    // the connections of removed ports have to be cleaned up beforehand, see `Plot::resize_block`
+    pub fn resize(&mut self, num_inputs: u8, num_outputs: u8) {
+        self.inputs.resize(num_inputs as usize, None);
+        self.outputs.resize(num_outputs as usize, None);
+        self.update_size();
+    }
+
     pub fn is_active(&self) -> bool {
         self.decoration.is_active()
     }
@@ -237,6 +444,10 @@ impl Block {
         self.state = state;
     }
 
+    pub fn output_state(&self) -> u128 {
+        self.output_state
+    }
+
     #[inline(always)]
     pub fn set_bytes(&mut self, bytes: u128) {
         self.state = State::Direct(bytes);
@@ -258,14 +469,14 @@ impl Block {
         self.passthrough
     }
 
-    pub fn on_mouse_press(&mut self, mut position: Vector2<i32>) -> bool {
-        position -= self.position;
+    pub fn on_mouse_press(&mut self, position: Vector2<i32>) -> bool {
+        let position = self.orientation.revert(position - self.position, self.size);
         if position.0 > 15
             && position.1 > 25
             && position.0 < self.size.0 - 15
             && position.1 < self.size.1 - 10
         {
-            self.decoration.on_mouse_press()
+            self.decoration.on_mouse_press(position, self.size)
         } else {
             false
         }
@@ -275,30 +486,30 @@ impl Block {
         self.decoration.on_mouse_release();
     }
 
+    pub fn on_scroll(&mut self, delta: i32) -> bool {
+        self.decoration.on_scroll(delta)
+    }
+
+    pub fn on_key_press(&mut self, key: char) -> bool {
+        self.decoration.on_key_press(key)
+    }
+
+    pub fn on_key_release(&mut self) -> bool {
+        self.decoration.on_key_release()
+    }
+
     pub fn position_on_connection(&self, position: Vector2<i32>, is_input: bool) -> Option<u8> {
-        if is_input {
-            for i in 0..self.inputs.len() {
-                let connector_pos = (self.position.0, self.position.1 + 25 * i as i32 + 50);
-                if (position.0 - connector_pos.0).abs() < Segment::HITBOX_SIZE
-                    && (position.1 - connector_pos.1).abs() < Segment::HITBOX_SIZE
-                {
-                    return Some(i as u8);
-                }
-            }
+        let (count, connector): (_, fn(u8) -> Connector) = if is_input {
+            (self.inputs.len(), Connector::Input)
         } else {
-            for i in 0..self.outputs.len() {
-                let connector_pos = (
-                    self.position.0 + self.size.0,
-                    self.position.1 + 25 * i as i32 + 50,
-                );
-                if (position.0 - connector_pos.0).abs() < Segment::HITBOX_SIZE
-                    && (position.1 - connector_pos.1).abs() < Segment::HITBOX_SIZE
-                {
-                    return Some(i as u8);
-                }
-            }
-        }
-        None
+            (self.outputs.len(), Connector::Output)
+        };
+
+        (0..count as u8).find(|i| {
+            let connector_pos = self.get_connector_pos(connector(*i));
+            (position.0 - connector_pos.0).abs() < Segment::HITBOX_SIZE
+                && (position.1 - connector_pos.1).abs() < Segment::HITBOX_SIZE
+        })
     }
 
     pub fn simulate(
@@ -318,6 +529,10 @@ impl Block {
             self.output_state =
                 module.simulate(inputs, self, unsafe { &mut *mut_ref_ptr }, call_stack)?;
 
+            if self.decoration.free_running() && self.inputs.iter().all(Option::is_none) {
+                queued.insert(self.id);
+            }
+
             // dissect output state
             for (i, connection_id) in self.outputs.iter().enumerate() {
                 if let Some(connection) = connection_id
@@ -346,8 +561,9 @@ impl Block {
     }
 }
 
-impl Renderable for Block {
-    fn render<R>(&self, renderer: &R, plot: &Plot) -> Result<(), R::Error>
+impl Block {
+    // renders the block as if it was neither rotated nor mirrored
+    fn render_local<R>(&self, renderer: &R, plot: &Plot) -> Result<(), R::Error>
     where
         R: Renderer,
     {
@@ -356,28 +572,70 @@ impl Renderable for Block {
             .as_ref()
             .unwrap_or(unsafe { &COLOR_THEME.border_color });
 
+        let gate = self.distinctive_gate();
+
         renderer.set_line_width(2.);
-        renderer
-            .rounded_rect(self.position, self.size, 5)
-            .set_color(unsafe { &COLOR_THEME.block_bg_color })
-            .fill()?;
+        if let Some(gate) = gate {
+            let inputs = (0..self.inputs.len())
+                .map(|i| self.local_connector_pos(Connector::Input(i as u8)))
+                .collect::<Vec<_>>();
+            gate.render(
+                renderer,
+                self.position,
+                self.size,
+                &inputs,
+                self.local_connector_pos(Connector::Output(0)),
+                match self.highlighted {
+                    true => unsafe { &COLOR_THEME.accent_fg_color },
+                    false => border_color,
+                },
+            )?;
 
-        renderer
-            .top_rounded_rect(self.position, Vector2(self.size.0, 25), 5)
-            .set_color(border_color)
-            .fill()?;
+            if let Some(label) = &self.label {
+                renderer
+                    .move_to(Vector2(self.position.0 + 5, self.position.1 + 18))
+                    .set_color(unsafe { &COLOR_THEME.decoration_fg_color })
+                    .show_text(label)?;
+            }
+        } else if let Some(shape) = &self.shape {
+            // custom outlines have no header, the name is shown above instead
+            shape.trace(renderer, self.position);
+            renderer
+                .set_color(unsafe { &COLOR_THEME.block_bg_color })
+                .fill()?;
 
-        renderer
-            .move_to(Vector2(self.position.0 + 5, self.position.1 + 18))
-            .set_color(unsafe { &COLOR_THEME.block_fg_color })
-            .show_text(self.name.as_str())?;
-
-        renderer.rounded_rect(self.position, self.size, 5);
-        match self.highlighted {
-            true => renderer.set_color(unsafe { &COLOR_THEME.accent_fg_color }),
-            false => renderer.set_color(border_color),
-        };
-        renderer.stroke()?;
+            renderer
+                .move_to(Vector2(self.position.0, self.position.1 - 6))
+                .set_color(unsafe { &COLOR_THEME.decoration_fg_color })
+                .show_text(self.header())?;
+
+            shape.trace(renderer, self.position);
+        } else {
+            renderer
+                .rounded_rect(self.position, self.size, 5)
+                .set_color(unsafe { &COLOR_THEME.block_bg_color })
+                .fill()?;
+
+            renderer
+                .top_rounded_rect(self.position, Vector2(self.size.0, 25), 5)
+                .set_color(border_color)
+                .fill()?;
+
+            renderer
+                .move_to(Vector2(self.position.0 + 5, self.position.1 + 18))
+                .set_color(unsafe { &COLOR_THEME.block_fg_color })
+                .show_text(self.header())?;
+
+            renderer.rounded_rect(self.position, self.size, 5);
+        }
+
+        if gate.is_none() {
+            match self.highlighted {
+                true => renderer.set_color(unsafe { &COLOR_THEME.accent_fg_color }),
+                false => renderer.set_color(border_color),
+            };
+            renderer.stroke()?;
+        }
 
         let show_suggestion = plot.selection().connecting();
         let connector = |position, is_input, is_active| {
@@ -406,7 +664,7 @@ impl Renderable for Block {
         renderer.set_line_width(1.);
         for (i, _) in self.inputs.iter().enumerate().filter(|(_, c)| c.is_none()) {
             connector(
-                Vector2(self.position.0, self.position.1 + 25 * i as i32 + 50),
+                self.local_connector_pos(Connector::Input(i as u8)),
                 true,
                 false,
             )?;
@@ -414,15 +672,53 @@ impl Renderable for Block {
 
         for (i, _) in self.outputs.iter().enumerate().filter(|(_, c)| c.is_none()) {
             connector(
-                Vector2(
-                    self.position.0 + self.size.0,
-                    self.position.1 + 25 * i as i32 + 50,
-                ),
+                self.local_connector_pos(Connector::Output(i as u8)),
                 false,
                 (self.output_state >> i as u128) & 1 != 0,
             )?;
         }
 
+        renderer
+            .set_font_size(Self::PIN_NAME_FONT_SIZE)
+            .set_color(unsafe { &COLOR_THEME.decoration_fg_color });
+        for i in 0..self.inputs.len() {
+            if let Some(name) = self.pin_name(Connector::Input(i as u8)) {
+                renderer
+                    .move_to(self.local_connector_pos(Connector::Input(i as u8)) + Vector2(10, 4))
+                    .show_text(name)?;
+            }
+        }
+
+        for i in 0..self.outputs.len() {
+            if let Some(name) = self.pin_name(Connector::Output(i as u8)) {
+                let width = name.chars().count() as i32 * Self::PIN_NAME_CHAR_WIDTH;
+                renderer
+                    .move_to(
+                        self.local_connector_pos(Connector::Output(i as u8))
+                            + Vector2(-10 - width, 4),
+                    )
+                    .show_text(name)?;
+            }
+        }
+        renderer.set_font_size(DEFAULT_FONT_SIZE);
+
         self.decoration.render(renderer, self).map(|_| ())
     }
 }
+
+impl Renderable for Block {
+    fn render<R>(&self, renderer: &R, plot: &Plot) -> Result<(), R::Error>
+    where
+        R: Renderer,
+    {
+        if self.orientation.is_default() {
+            return self.render_local(renderer, plot);
+        }
+
+        let (origin, x_axis, y_axis) = self.orientation.matrix(self.position, self.size);
+        renderer.save()?.transform(origin, x_axis, y_axis);
+        let result = self.render_local(renderer, plot);
+        renderer.restore()?;
+        result
+    }
+}
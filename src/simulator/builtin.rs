@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use crate::simulator::{Category, Decoration};
 
-use super::{Block, Module, SimulatorFn};
+use super::{bit_mask, Block, Module, SimulatorFn};
 
 pub struct Builtin {
     module: Module,
@@ -158,6 +158,20 @@ lazy_static! {
             ),
         );
 
+        builtins.insert(
+            "Constant",
+            Builtin::new(
+                Module::new_builtin(
+                    "Constant",
+                    Category::Basic,
+                    0,
+                    8,
+                    Decoration::Constant(8, 0),
+                ),
+                |_, instance| instance.decoration().output(),
+            ),
+        );
+
         builtins.insert(
             "Button",
             Builtin::new(
@@ -197,6 +211,90 @@ lazy_static! {
             ),
         );
 
+        builtins.insert(
+            "Numeric Input",
+            Builtin::new(
+                Module::new_builtin(
+                    "Numeric Input",
+                    Category::InputOutput,
+                    0,
+                    8,
+                    Decoration::Numeric(8, 0),
+                ),
+                |_, instance| instance.decoration().output(),
+            ),
+        );
+
+        builtins.insert(
+            "Hex Keypad",
+            Builtin::new(
+                Module::new_builtin(
+                    "Hex Keypad",
+                    Category::InputOutput,
+                    0,
+                    5,
+                    Decoration::Keypad(0, false),
+                ),
+                |_, instance| instance.decoration().output(),
+            ),
+        );
+
+        builtins.insert(
+            "Keyboard",
+            Builtin::new(
+                Module::new_builtin(
+                    "Keyboard",
+                    Category::InputOutput,
+                    0,
+                    8,
+                    Decoration::Keyboard(0, false),
+                ),
+                |_, instance| instance.decoration().output(),
+            ),
+        );
+
+        builtins.insert(
+            "Pixel Display",
+            Builtin::new(
+                Module::new_builtin(
+                    "Pixel Display",
+                    Category::InputOutput,
+                    11,
+                    0,
+                    Decoration::Matrix(8, 8, vec![0; 8]),
+                ),
+                pixel_display,
+            ),
+        );
+
+        builtins.insert(
+            "Random",
+            Builtin::new(
+                Module::new_builtin(
+                    "Random",
+                    Category::InputOutput,
+                    1,
+                    8,
+                    Decoration::Random(8, 0),
+                ),
+                random,
+            ),
+        );
+
+        builtins.insert(
+            "Terminal",
+            Builtin::new(
+                Module::new_builtin(
+                    "Terminal",
+                    Category::InputOutput,
+                    9,
+                    0,
+                    Decoration::Terminal(20, 8, vec![String::new()]),
+                ),
+                terminal,
+            ),
+        );
+
         builtins.insert(
             "Input",
             Builtin::new(
@@ -411,3 +509,65 @@ fn t_flip_flop(input: u128, instance: &mut Block) -> u128 {
     instance.set_bytes((instance.bytes() & !0b10) | (input & 0b10));
     instance.bytes() & 1 | !instance.bytes() << 1
 }
+
+fn pixel_display(input: u128, instance: &mut Block) -> u128 {
+    // inputs: X0-X3, Y0-Y3, D, WE, CLR
+    let write = input & (1 << 9) > 0;
+    if input & (1 << 10) > 0 {
+        instance.decoration_mut().clear();
+    } else if write && instance.bytes() == 0 {
+        instance.decoration_mut().set_pixel(
+            (input & 0xf) as u8,
+            ((input >> 4) & 0xf) as u8,
+            input & (1 << 8) > 0,
+        );
+    }
+
+    instance.set_bytes(write as u128);
+    0
+}
+
+fn terminal(input: u128, instance: &mut Block) -> u128 {
+    // inputs: D0-D6, WE, CLR
+    let write = input & (1 << 7) > 0;
+    if input & (1 << 8) > 0 {
+        instance.decoration_mut().clear();
+    } else if write && instance.bytes() == 0 {
+        instance.decoration_mut().write_char((input & 0x7f) as u8);
+    }
+
+    instance.set_bytes(write as u128);
+    0
+}
+
+// initial xorshift state of a random generator with the given seed
+pub fn random_state(seed: u64) -> u64 {
+    // splitmix64 spreads similar seeds, xorshift must not start at zero
+    let mut z = seed.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    (z ^ (z >> 31)).max(1)
+}
+
+fn random(input: u128, instance: &mut Block) -> u128 {
+    // inputs: CLK; state: xorshift state in the lower 64 bits, previous CLK in bit 64
+    let (width, seed) = match instance.decoration() {
+        Decoration::Random(width, seed) => (*width, *seed),
+        _ => return 0,
+    };
+
+    let bytes = instance.bytes();
+    let clock = input & 1 > 0;
+    let mut state = bytes as u64;
+
+    if state == 0 {
+        state = random_state(seed);
+    } else if instance.inputs()[0].is_none() || (clock && bytes & (1 << 64) == 0) {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+    }
+
+    instance.set_bytes(state as u128 | (clock as u128) << 64);
+    state as u128 & bit_mask(width)
+}
@@ -2,6 +2,7 @@ use super::*;
 use crate::{
     application::editor::{self, EditorMode},
     id::Id,
+    project::format,
     renderer::{vector::*, *},
 };
 use serde::{Deserialize, Serialize};
@@ -72,7 +73,11 @@ impl SegmentID {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Segment {
     Block(BlockID, u8),
-    Waypoint(HashMap<Id, Segment>, Vector2<i32>, bool),
+    Waypoint(
+        #[serde(serialize_with = "format::sorted_map")] HashMap<Id, Segment>,
+        Vector2<i32>,
+        bool,
+    ),
 }
 
 impl Segment {
@@ -126,10 +131,32 @@ impl Segment {
         *self = Self::Block(block_id, port)
     }
 
+    // input at the end of an unbranched chain of waypoints
+    fn chain_target(&self) -> Option<(BlockID, u8)> {
+        match self {
+            Self::Block(block_id, port) => Some((*block_id, *port)),
+            Self::Waypoint(segments, ..) if segments.len() == 1 => {
+                segments.values().next().and_then(Self::chain_target)
+            }
+            _ => None,
+        }
+    }
+
+    // unbranched chain of waypoints at the given positions, ending in the given input
+    fn chain(waypoints: &[Vector2<i32>], block_id: BlockID, port: u8) -> Self {
+        waypoints
+            .iter()
+            .rev()
+            .fold(Self::Block(block_id, port), |segment, position| {
+                Self::Waypoint(HashMap::from([(Id::new(), segment)]), *position, false)
+            })
+    }
+
     fn render<R>(
         &self,
         active: bool,
         start: Vector2<i32>,
+        direction: Vector2<i32>,
         renderer: &R,
         plot: &Plot,
     ) -> Result<(), R::Error>
@@ -140,14 +167,20 @@ impl Segment {
             Self::Block(block_id, port) => {
                 let end_block = plot.get_block(*block_id).unwrap();
                 let end = end_block.get_connector_pos(Connector::Input(*port));
-                render_line(active, start, end, renderer)?;
+                let end_direction = end_block.connector_direction(Connector::Input(*port));
+                render_line(active, (start, direction), (end, end_direction), renderer)?;
                 render_block_connector(end, active, end_block.highlighted(), renderer)
             }
             Self::Waypoint(segments, position, highlighted) => {
-                render_line(active, start, *position, renderer)?;
+                render_line(
+                    active,
+                    (start, direction),
+                    (*position, Vector2(-1, 0)),
+                    renderer,
+                )?;
 
                 for segment in segments.values() {
-                    segment.render(active, *position, renderer, plot)?;
+                    segment.render(active, *position, Vector2(1, 0), renderer, plot)?;
                 }
 
                 render_waypoint(*position, active, *highlighted, renderer)
@@ -181,6 +214,16 @@ impl Segment {
         }
     }
 
+    fn remove_branches_to(&mut self, target: BlockID, first_port: u8) -> bool {
+        match self {
+            Self::Block(block_id, port) => *block_id == target && *port >= first_port,
+            Self::Waypoint(segments, ..) => {
+                segments.retain(|_, segment| !segment.remove_branches_to(target, first_port));
+                self.is_empty()
+            }
+        }
+    }
+
     fn refactor_id(&mut self, old_id: BlockID, new_id: BlockID) {
         match self {
             Self::Block(block_id, ..) if *block_id == old_id => *block_id = new_id,
@@ -288,7 +331,11 @@ pub struct Connection {
     id: ConnectionID,
     active: bool,
     origin: Port,
+    #[serde(serialize_with = "format::sorted_map")]
     segments: HashMap<Id, Segment>,
+    // wires leaving the origin whose waypoints are placed by the auto-router
+    #[serde(default, serialize_with = "format::sorted_set")]
+    routed: HashSet<Id>,
 }
 
 impl Identifiable for Connection {
@@ -305,6 +352,7 @@ impl Connection {
                 .into_iter()
                 .map(|segment| (Id::new(), segment))
                 .collect(),
+            routed: HashSet::new(),
         }
     }
 
@@ -326,6 +374,7 @@ impl Connection {
                 );
                 segments
             },
+            routed: HashSet::new(),
         }
     }
 
@@ -371,6 +420,13 @@ impl Connection {
         self.segments.is_empty()
     }
 
+    // removes all branches leading to inputs of `target` starting at `first_port`
+    pub fn remove_branches_to(&mut self, target: BlockID, first_port: u8) -> bool {
+        self.segments
+            .retain(|_, segment| !segment.remove_branches_to(target, first_port));
+        self.segments.is_empty()
+    }
+
     pub fn refactor_id(&mut self, old_id: BlockID, new_id: BlockID) {
         if self.origin.block_id() == old_id {
             self.origin.set_block_id(new_id)
@@ -392,8 +448,51 @@ impl Connection {
             .then_some(SegmentID::new(self.id, location))
     }
 
-    pub fn add_segment(&mut self, segment: Segment) {
-        self.segments.insert(Id::new(), segment);
+    pub fn add_segment(&mut self, segment: Segment) -> Id {
+        let id = Id::new();
+        self.segments.insert(id, segment);
+        id
+    }
+
+    // unbranched wires leaving the origin, with the input each of them ends in
+    pub fn legs(&self) -> Vec<(Id, BlockID, u8)> {
+        self.segments
+            .iter()
+            .filter_map(|(id, segment)| {
+                segment
+                    .chain_target()
+                    .map(|(block_id, port)| (*id, block_id, port))
+            })
+            .collect()
+    }
+
+    pub fn routed_legs(&self) -> Vec<(Id, BlockID, u8)> {
+        self.legs()
+            .into_iter()
+            .filter(|(id, ..)| self.routed.contains(id))
+            .collect()
+    }
+
+    pub fn is_routed(&self, leg: &Id) -> bool {
+        self.routed.contains(leg)
+    }
+
+    // returns whether the leg was routed before
+    pub fn set_routed(&mut self, leg: Id, routed: bool) -> bool {
+        match routed {
+            true => !self.routed.insert(leg),
+            false => self.routed.remove(&leg),
+        }
+    }
+
+    // replaces the waypoints of an unbranched wire, marking it as routed
+    pub fn set_route(&mut self, leg: Id, waypoints: &[Vector2<i32>]) {
+        if let Some(segment) = self.segments.get_mut(&leg)
+            && let Some((block_id, port)) = segment.chain_target()
+        {
+            *segment = Segment::chain(waypoints, block_id, port);
+            self.routed.insert(leg);
+        }
     }
 
     pub fn segments(&self) -> &HashMap<Id, Segment> {
@@ -507,10 +606,11 @@ where
         .map(|_| ())
 }
 
+// draws a wire between two points, each given with the direction the wire leaves it in
 pub fn render_line<R>(
     active: bool,
-    start: Vector2<i32>,
-    end: Vector2<i32>,
+    (start, start_direction): (Vector2<i32>, Vector2<i32>),
+    (end, end_direction): (Vector2<i32>, Vector2<i32>),
     renderer: &R,
 ) -> Result<(), R::Error>
 where
@@ -528,9 +628,14 @@ where
 
     match renderer.editor_mode() {
         EditorMode::Normal => {
+            let control = |from: Vector2<i32>, to: Vector2<i32>, direction: Vector2<i32>| {
+                let distance = (to.0 - from.0) * direction.0 + (to.1 - from.1) * direction.1;
+                let distance = (distance as f32 * 0.7) as i32;
+                from + Vector2(direction.0 * distance, direction.1 * distance)
+            };
             let offset = (
-                Vector2(start.0 + ((end.0 - start.0) as f32 * 0.7) as i32, start.1),
-                Vector2(end.0 + ((start.0 - end.0) as f32 * 0.7) as i32, end.1),
+                control(start, end, start_direction),
+                control(end, start, end_direction),
             );
             renderer
                 .move_to(start)
@@ -553,9 +658,10 @@ impl Renderable for Connection {
         }
         let origin_block = origin_block.unwrap();
         let origin_pos = origin_block.get_connector_pos(self.origin.into());
+        let origin_direction = origin_block.connector_direction(self.origin.into());
 
         for segment in self.segments.values() {
-            segment.render(self.active, origin_pos, renderer, plot)?
+            segment.render(self.active, origin_pos, origin_direction, renderer, plot)?
         }
 
         render_block_connector(
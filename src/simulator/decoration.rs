@@ -1,8 +1,14 @@
 use crate::renderer::{vector::Vector2, *};
-use serde::{Deserialize, Serialize};
-use std::f64;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    f64, fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
-use super::Block;
+use super::{bit_mask, Block, Parameter};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Decoration {
@@ -12,6 +18,133 @@ pub enum Decoration {
     Button(bool),
     Switch(bool),
     Lamp(bool),
+    // width, height, one bitmask per row
+    Matrix(u8, u8, Vec<u16>),
+    // width, value
+    Numeric(u8, u128),
+    // last key, pressed
+    Keypad(u8, bool),
+    // last ASCII code, strobe
+    Keyboard(u8, bool),
+    // columns, rows, lines of text
+    Terminal(u8, u8, Vec<String>),
+    // width, seed
+    Random(u8, u64),
+    // width, value
+    Constant(u8, u128),
+    // contents of a PNG or SVG file, shared with the module
+    Image(ImageData),
+}
+
+// encoded image of a module; clones share the data, so instances of the module don't copy it
+#[derive(Clone)]
+pub struct ImageData {
+    // identifies the image in the renderer's cache, `0` for instances not linked to their module
+    generation: u64,
+    data: Arc<[u8]>,
+}
+
+static NEXT_IMAGE_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+impl ImageData {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            generation: NEXT_IMAGE_GENERATION.fetch_add(1, Ordering::Relaxed),
+            data: data.into(),
+        }
+    }
+
+    // placeholder saved by instances, replaced by their module's image after loading
+    fn unlinked() -> Self {
+        Self {
+            generation: 0,
+            data: Arc::from([]),
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl fmt::Debug for ImageData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ImageData({} bytes)", self.data.len())
+    }
+}
+
+impl Serialize for ImageData {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.generation {
+            0 => serializer.serialize_none(),
+            _ => serializer.serialize_str(&encode_base64(&self.data)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ImageData {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(text) => decode_base64(&text)
+                .map(Self::new)
+                .map_err(de::Error::custom),
+            None => Ok(Self::unlinked()),
+        }
+    }
+}
+
+// binary data such as module images is saved as base64 text
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode_base64(data: &[u8]) -> String {
+    let mut text = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | ((*byte as u32) << (16 - 8 * i))
+        });
+        for i in 0..4 {
+            text.push(match i <= chunk.len() {
+                true => BASE64_ALPHABET[((bits >> (18 - 6 * i)) & 0x3f) as usize] as char,
+                false => '=',
+            });
+        }
+    }
+    text
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.trim_end_matches('=');
+    let mut data = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for c in text.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(format!("invalid base64 character `{}`", c as char)),
+        };
+        bits = (bits << 6) | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            data.push((bits >> count) as u8);
+        }
+    }
+    Ok(data)
 }
 
 impl Default for Decoration {
@@ -21,11 +154,51 @@ impl Default for Decoration {
 }
 
 impl Decoration {
+    // size of a single pixel of `Decoration::Matrix` in pixels at scale 1.0
+    const MATRIX_CELL_SIZE: i32 = 14;
+
+    // size of a single key of `Decoration::Keypad` in pixels at scale 1.0
+    const KEYPAD_KEY_SIZE: i32 = 24;
+    const KEYPAD_LAYOUT: [u8; 16] = [
+        0x1, 0x2, 0x3, 0xa, 0x4, 0x5, 0x6, 0xb, 0x7, 0x8, 0x9, 0xc, 0xe, 0x0, 0xf, 0xd,
+    ];
+
+    // size of a single character of `Decoration::Terminal` in pixels at scale 1.0
+    const TERMINAL_CHAR_SIZE: Vector2<i32> = Vector2(8, 14);
+
+    fn constant_label(width: u8, value: u128) -> String {
+        if width == 1 {
+            value.to_string()
+        } else {
+            format!("{value:#X}").replacen('X', "x", 1)
+        }
+    }
+
+    fn keypad_origin(size: Vector2<i32>) -> Vector2<i32> {
+        Vector2(
+            (size.0 - 4 * Self::KEYPAD_KEY_SIZE) / 2,
+            25 + (size.1 - 25 - 4 * Self::KEYPAD_KEY_SIZE) / 2,
+        )
+    }
+
+    // instances save image decorations without the data, which is only saved with their module
+    pub fn serialize_instance<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Image(_) => Self::Image(ImageData::unlinked()).serialize(serializer),
+            decoration => decoration.serialize(serializer),
+        }
+    }
+
     pub(super) fn render<R>(&self, renderer: &R, block: &Block) -> Result<(), R::Error>
     where
         R: Renderer,
     {
         match self {
+            // the distinctive gate shape already identifies the gate
+            Self::Label(_) | Self::NotLabel(_) if block.distinctive_gate().is_some() => Ok(()),
             Self::Label(label) => {
                 renderer
                     .set_font_size(26.0)
@@ -102,9 +275,350 @@ impl Decoration {
                 .set_color(unsafe { &COLOR_THEME.border_color })
                 .stroke()
                 .map(|_| ()),
+            Self::Matrix(width, height, pixels) => {
+                let origin = block.position()
+                    + Vector2(
+                        (block.size().0 - *width as i32 * Self::MATRIX_CELL_SIZE) / 2,
+                        25 + (block.size().1 - 25 - *height as i32 * Self::MATRIX_CELL_SIZE) / 2,
+                    );
+
+                for (y, row) in pixels.iter().enumerate() {
+                    for x in 0..*width {
+                        renderer
+                            .rectangle(
+                                origin
+                                    + Vector2(
+                                        x as i32 * Self::MATRIX_CELL_SIZE,
+                                        y as i32 * Self::MATRIX_CELL_SIZE,
+                                    ),
+                                Vector2(Self::MATRIX_CELL_SIZE - 2, Self::MATRIX_CELL_SIZE - 2),
+                            )
+                            .set_color(unsafe {
+                                if row & (1 << x) != 0 {
+                                    &COLOR_THEME.enabled_fg_color
+                                } else {
+                                    &COLOR_THEME.border_color
+                                }
+                            })
+                            .fill()?;
+                    }
+                }
+                Ok(())
+            }
+            Self::Numeric(_, value) => {
+                let text = value.to_string();
+                renderer
+                    .set_font_size(22.0)
+                    .move_to(Vector2(
+                        block.position().0 + (block.size().0 / 2 - 6 * text.chars().count() as i32),
+                        block.position().1 + (block.size().1 / 2 + 18),
+                    ))
+                    .set_color(unsafe { &COLOR_THEME.decoration_fg_color })
+                    .show_text(&text)?
+                    .set_font_size(DEFAULT_FONT_SIZE);
+                Ok(())
+            }
+            Self::Keypad(last, pressed) => {
+                let origin = block.position() + Self::keypad_origin(block.size());
+                for (i, key) in Self::KEYPAD_LAYOUT.iter().enumerate() {
+                    let position = origin
+                        + Vector2(
+                            (i % 4) as i32 * Self::KEYPAD_KEY_SIZE,
+                            (i / 4) as i32 * Self::KEYPAD_KEY_SIZE,
+                        );
+                    renderer
+                        .rounded_rect(
+                            position + Vector2(1, 1),
+                            Vector2(Self::KEYPAD_KEY_SIZE - 2, Self::KEYPAD_KEY_SIZE - 2),
+                            4,
+                        )
+                        .set_color(unsafe {
+                            if *pressed && last == key {
+                                &COLOR_THEME.button_active_color
+                            } else {
+                                &COLOR_THEME.border_color
+                            }
+                        })
+                        .fill()?
+                        .move_to(position + Vector2(8, 17))
+                        .set_color(unsafe { &COLOR_THEME.block_fg_color })
+                        .show_text(&format!("{key:X}"))?;
+                }
+                Ok(())
+            }
+            Self::Terminal(columns, rows, lines) => {
+                let screen_size = Vector2(
+                    *columns as i32 * Self::TERMINAL_CHAR_SIZE.0 + 8,
+                    *rows as i32 * Self::TERMINAL_CHAR_SIZE.1 + 8,
+                );
+                let origin = block.position()
+                    + Vector2(
+                        (block.size().0 - screen_size.0) / 2,
+                        25 + (block.size().1 - 25 - screen_size.1) / 2,
+                    );
+
+                renderer
+                    .rounded_rect(origin, screen_size, 4)
+                    .set_color(unsafe { &COLOR_THEME.bg_color })
+                    .fill()?
+                    .set_font_size(13.0)
+                    .set_color(unsafe { &COLOR_THEME.block_fg_color });
+
+                // draw every character on its own to get a monospaced grid
+                for (y, line) in lines.iter().enumerate() {
+                    let cursor = y + 1 == lines.len();
+                    for (x, c) in line
+                        .chars()
+                        .chain(cursor.then_some('_'))
+                        .take(*columns as usize)
+                        .enumerate()
+                    {
+                        renderer
+                            .move_to(
+                                origin
+                                    + Vector2(
+                                        4 + x as i32 * Self::TERMINAL_CHAR_SIZE.0,
+                                        2 + (y as i32 + 1) * Self::TERMINAL_CHAR_SIZE.1,
+                                    ),
+                            )
+                            .show_text(&c.to_string())?;
+                    }
+                }
+
+                renderer.set_font_size(DEFAULT_FONT_SIZE);
+                Ok(())
+            }
+            Self::Constant(width, value) => {
+                let text = Self::constant_label(*width, *value);
+                renderer
+                    .set_font_size(22.0)
+                    .move_to(Vector2(
+                        block.position().0 + (block.size().0 / 2 - 6 * text.chars().count() as i32),
+                        block.position().1 + (block.size().1 / 2 + 18),
+                    ))
+                    .set_color(unsafe { &COLOR_THEME.decoration_fg_color })
+                    .show_text(&text)?
+                    .set_font_size(DEFAULT_FONT_SIZE);
+                Ok(())
+            }
+            Self::Image(image) => renderer
+                .image(
+                    block.position() + Vector2(10, 30),
+                    Vector2(block.size().0 - 20, block.size().1 - 35),
+                    image,
+                )
+                .map(|_| ()),
+            Self::Random(..) => {
+                renderer
+                    .set_font_size(26.0)
+                    .move_to(Vector2(
+                        block.position().0 + (block.size().0 / 2 - 7),
+                        block.position().1 + (block.size().1 / 2 + 20),
+                    ))
+                    .set_color(unsafe { &COLOR_THEME.decoration_fg_color })
+                    .show_text("?")?
+                    .set_font_size(DEFAULT_FONT_SIZE);
+                Ok(())
+            }
+            Self::Keyboard(code, strobe) => {
+                let text = match char::from(*code) {
+                    c if c.is_ascii_graphic() => c.to_string(),
+                    _ => format!("{code:#04x}"),
+                };
+                renderer
+                    .set_font_size(22.0)
+                    .move_to(Vector2(
+                        block.position().0 + (block.size().0 / 2 - 6 * text.chars().count() as i32),
+                        block.position().1 + (block.size().1 / 2 + 18),
+                    ))
+                    .set_color(unsafe {
+                        if *strobe {
+                            &COLOR_THEME.suggestion_fg_color
+                        } else {
+                            &COLOR_THEME.decoration_fg_color
+                        }
+                    })
+                    .show_text(&text)?
+                    .set_font_size(DEFAULT_FONT_SIZE);
+                Ok(())
+            }
             _ => Ok(()),
         }
     }
+
+    // minimum block size needed to fit the decoration
+    pub fn content_size(&self) -> Option<Vector2<i32>> {
+        match self {
+            Self::Matrix(width, height, _) => Some(Vector2(
+                *width as i32 * Self::MATRIX_CELL_SIZE + 40,
+                *height as i32 * Self::MATRIX_CELL_SIZE + 45,
+            )),
+            Self::Terminal(columns, rows, _) => Some(Vector2(
+                *columns as i32 * Self::TERMINAL_CHAR_SIZE.0 + 40,
+                *rows as i32 * Self::TERMINAL_CHAR_SIZE.1 + 45,
+            )),
+            Self::Constant(width, value) => Some(Vector2(
+                Self::constant_label(*width, *value).chars().count() as i32 * 12 + 40,
+                0,
+            )),
+            Self::Keypad(..) => Some(Vector2(
+                4 * Self::KEYPAD_KEY_SIZE + 40,
+                4 * Self::KEYPAD_KEY_SIZE + 45,
+            )),
+            Self::Image(_) => Some(Vector2(75, 85)),
+            _ => None,
+        }
+    }
+
+    // number of inputs and outputs, if they depend on the decoration's parameters
+    pub fn connectors(&self) -> Option<(u8, u8)> {
+        match self {
+            Self::Numeric(width, _) => Some((0, *width)),
+            Self::Random(width, _) => Some((1, *width)),
+            Self::Constant(width, _) => Some((0, *width)),
+            _ => None,
+        }
+    }
+
+    // output value of input decorations
+    pub fn output(&self) -> u128 {
+        match self {
+            Self::Numeric(_, value) | Self::Constant(_, value) => *value,
+            Self::Keypad(key, pressed) => *key as u128 | (*pressed as u128) << 4,
+            Self::Keyboard(code, strobe) => *code as u128 | (*strobe as u128) << 7,
+            _ => 0,
+        }
+    }
+
+    pub fn parameters(&self) -> Vec<(Parameter, u128)> {
+        match self {
+            Self::Matrix(width, height, _) => vec![
+                (Parameter::Width, *width as u128),
+                (Parameter::Height, *height as u128),
+            ],
+            Self::Numeric(width, value) | Self::Constant(width, value) => vec![
+                (Parameter::Width, *width as u128),
+                (Parameter::Value, *value),
+            ],
+            Self::Terminal(columns, rows, _) => vec![
+                (Parameter::Width, *columns as u128),
+                (Parameter::Height, *rows as u128),
+            ],
+            Self::Random(width, seed) => vec![
+                (Parameter::Width, *width as u128),
+                (Parameter::Seed, *seed as u128),
+            ],
+            _ => vec![],
+        }
+    }
+
+    pub fn parameter_bounds(&self, parameter: Parameter) -> (u128, u128) {
+        match (self, parameter) {
+            (Self::Matrix(..), Parameter::Width | Parameter::Height) => (1, 16),
+            (Self::Numeric(..), Parameter::Width) => (1, 32),
+            (Self::Numeric(width, _), Parameter::Value) => (0, bit_mask(*width)),
+            (Self::Terminal(..), Parameter::Width) => (8, 64),
+            (Self::Terminal(..), Parameter::Height) => (1, 32),
+            (Self::Random(..) | Self::Constant(..), Parameter::Width) => (1, 64),
+            (Self::Constant(width, _), Parameter::Value) => (0, bit_mask(*width)),
+            (Self::Random(..), Parameter::Seed) => (0, u64::MAX as u128),
+            _ => (0, u128::MAX),
+        }
+    }
+
+    pub fn parameter(&self, parameter: Parameter) -> Option<u128> {
+        self.parameters()
+            .into_iter()
+            .find(|(p, _)| *p == parameter)
+            .map(|(_, value)| value)
+    }
+
+    pub fn set_parameter(&mut self, parameter: Parameter, value: u128) {
+        let (min, max) = self.parameter_bounds(parameter);
+        let value = value.clamp(min, max);
+        match (self, parameter) {
+            (Self::Matrix(width, _, pixels), Parameter::Width) => {
+                *width = value as u8;
+                pixels
+                    .iter_mut()
+                    .for_each(|row| *row &= ((1u32 << value) - 1) as u16);
+            }
+            (Self::Matrix(_, height, pixels), Parameter::Height) => {
+                *height = value as u8;
+                pixels.resize(value as usize, 0);
+            }
+            (Self::Numeric(width, current) | Self::Constant(width, current), Parameter::Width) => {
+                *width = value as u8;
+                *current &= bit_mask(*width);
+            }
+            (Self::Numeric(_, current) | Self::Constant(_, current), Parameter::Value) => {
+                *current = value
+            }
+            (Self::Terminal(columns, _, _), Parameter::Width) => *columns = value as u8,
+            (Self::Random(width, _), Parameter::Width) => *width = value as u8,
+            (Self::Random(_, seed), Parameter::Seed) => *seed = value as u64,
+            (Self::Terminal(_, rows, lines), Parameter::Height) => {
+                *rows = value as u8;
+                if lines.len() > value as usize {
+                    lines.drain(..lines.len() - value as usize);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    pub fn set_pixel(&mut self, x: u8, y: u8, on: bool) {
+        if let Self::Matrix(width, _, pixels) = self
+            && x < *width
+            && let Some(row) = pixels.get_mut(y as usize)
+        {
+            if on {
+                *row |= 1 << x;
+            } else {
+                *row &= !(1 << x);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        match self {
+            Self::Matrix(_, _, pixels) => pixels.iter_mut().for_each(|row| *row = 0),
+            Self::Terminal(_, _, lines) => *lines = vec![String::new()],
+            _ => (),
+        }
+    }
+
+    pub fn write_char(&mut self, c: u8) {
+        let Self::Terminal(columns, rows, lines) = self else {
+            return;
+        };
+
+        match c {
+            b'\n' => lines.push(String::new()),
+            // backspace
+            0x08 => {
+                if let Some(line) = lines.last_mut() {
+                    line.pop();
+                }
+            }
+            // form feed
+            0x0c => *lines = vec![String::new()],
+            0x20..=0x7e => {
+                if !lines
+                    .last()
+                    .is_some_and(|line| line.len() < *columns as usize)
+                {
+                    lines.push(String::new());
+                }
+                lines.last_mut().unwrap().push(c as char);
+            }
+            _ => (),
+        }
+
+        if lines.len() > *rows as usize {
+            lines.drain(..lines.len() - *rows as usize);
+        }
+    }
 }
 
 impl Decoration {
@@ -122,8 +636,23 @@ impl Decoration {
         }
     }
 
-    pub fn on_mouse_press(&mut self) -> bool {
+    pub fn on_mouse_press(&mut self, position: Vector2<i32>, size: Vector2<i32>) -> bool {
         match self {
+            Self::Keypad(last, pressed) => {
+                let Vector2(x, y) = position - Self::keypad_origin(size);
+                if x < 0 || y < 0 {
+                    return false;
+                }
+
+                let (column, row) = (x / Self::KEYPAD_KEY_SIZE, y / Self::KEYPAD_KEY_SIZE);
+                if column >= 4 || row >= 4 {
+                    return false;
+                }
+
+                *last = Self::KEYPAD_LAYOUT[(row * 4 + column) as usize];
+                *pressed = true;
+                true
+            }
             Self::Switch(active) => {
                 *active = !*active;
                 true
@@ -137,12 +666,103 @@ impl Decoration {
     }
 
     pub fn on_mouse_release(&mut self) {
-        if let Self::Button(active) = self {
+        if let Self::Button(active) | Self::Keypad(_, active) = self {
             *active = false
         }
     }
 
+    pub fn on_scroll(&mut self, delta: i32) -> bool {
+        match self {
+            Self::Numeric(width, value) => {
+                *value = if delta < 0 {
+                    value.wrapping_sub(1)
+                } else {
+                    value.wrapping_add(1)
+                } & bit_mask(*width);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn on_key_press(&mut self, key: char) -> bool {
+        match self {
+            Self::Numeric(width, value) => {
+                // digits are typed in decimal, like the value is displayed
+                if key == '\u{8}' {
+                    *value /= 10;
+                } else if let Some(digit) = key.to_digit(10) {
+                    *value = value
+                        .saturating_mul(10)
+                        .saturating_add(digit as u128)
+                        .min(bit_mask(*width));
+                } else {
+                    return false;
+                }
+                true
+            }
+            Self::Keypad(last, pressed) if let Some(digit) = key.to_digit(16) => {
+                *last = digit as u8;
+                *pressed = true;
+                true
+            }
+            Self::Keyboard(code, strobe) if key.is_ascii() => {
+                *code = key as u8;
+                *strobe = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn on_key_release(&mut self) -> bool {
+        match self {
+            Self::Keypad(_, active) | Self::Keyboard(_, active) if *active => {
+                *active = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // whether the block gets simulated every tick while none of its inputs are connected
+    pub fn free_running(&self) -> bool {
+        matches!(self, Self::Random(..))
+    }
+
     pub fn clickable(&self) -> bool {
-        matches!(self, Self::Switch(_) | Self::Button(_))
+        matches!(self, Self::Switch(_) | Self::Button(_) | Self::Keypad(..))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64() {
+        for data in [&b""[..], b"f", b"fo", b"foo", b"foob", &[0, 255, 128, 7]] {
+            assert_eq!(decode_base64(&encode_base64(data)).unwrap(), data);
+        }
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        assert!(decode_base64("Zm9v!").is_err());
+    }
+
+    #[test]
+    fn image_instances() {
+        let image = Decoration::Image(ImageData::new(b"foo".to_vec()));
+        assert_eq!(
+            serde_json::to_string(&image).unwrap(),
+            r#"{"Image":"Zm9v"}"#
+        );
+
+        let mut serializer = serde_json::Serializer::new(vec![]);
+        image.serialize_instance(&mut serializer).unwrap();
+        let json = String::from_utf8(serializer.into_inner()).unwrap();
+        assert_eq!(json, r#"{"Image":null}"#);
+        let Decoration::Image(instance) = serde_json::from_str(&json).unwrap() else {
+            panic!("expected an image");
+        };
+        assert_eq!(instance.generation(), 0);
     }
 }
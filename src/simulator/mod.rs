@@ -1,9 +1,14 @@
+pub mod annotation;
 pub mod block;
 pub mod builtin;
 pub mod connection;
 pub mod decoration;
 pub mod modules;
+pub mod orientation;
+pub mod parameter;
 pub mod plot;
+pub mod router;
+pub mod shape;
 pub mod state;
 
 use gtk::{prelude::Cast, subclass::prelude::ObjectSubclassIsExt};
@@ -18,7 +23,10 @@ use std::{
     thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
-pub use {block::*, connection::*, decoration::*, modules::*, plot::*, state::*};
+pub use {
+    annotation::*, block::*, connection::*, decoration::*, modules::*, orientation::*,
+    parameter::*, plot::*, shape::*, state::*,
+};
 
 use crate::{
     project::{Project, ProjectRef},
@@ -57,13 +65,19 @@ impl UICallback {
     fn exec(&self, window: &RefCell<Option<MainWindow>>) {
         match self {
             Self::Redraw => {
-                if let Some(view) = window
-                    .borrow()
-                    .as_ref()
-                    .and_then(|window| window.imp().circuit_panel.imp().view.selected_page())
-                    .and_then(|page| page.child().downcast::<CircuitView>().ok())
-                {
-                    view.rerender();
+                if let Some(window) = window.borrow().as_ref() {
+                    if let Some(view) = window
+                        .imp()
+                        .circuit_panel
+                        .imp()
+                        .view
+                        .selected_page()
+                        .and_then(|page| page.child().downcast::<CircuitView>().ok())
+                    {
+                        view.rerender();
+                    }
+                    // the simulation changed the states shown for the selected block
+                    window.properties().refresh_state();
                 }
             }
             Self::Error(err) => {
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 
 use crate::{
     id::Id,
+    renderer::vector::Vector2,
     simulator::{builtin::BUILTINS, *},
 };
 
@@ -27,6 +28,7 @@ pub struct Custom {
     plot: Plot,
     input_block: BlockID,
     output_block: BlockID,
+    #[serde(skip)]
     cache: HashMap<u128, u128>,
 }
 
@@ -56,6 +58,12 @@ pub struct Module {
     builtin: bool,
     num_inputs: u8,
     num_outputs: u8,
+    #[serde(default)]
+    input_names: Vec<String>,
+    #[serde(default)]
+    output_names: Vec<String>,
+    #[serde(default)]
+    shape: Option<Shape>,
     decoration: Decoration,
     custom_data: Option<Custom>,
 }
@@ -71,6 +79,9 @@ impl Module {
             custom_data: Some(Custom::new(Plot::new())),
             num_inputs,
             num_outputs,
+            input_names: vec![],
+            output_names: vec![],
+            shape: None,
             decoration: Decoration::None,
         }
     }
@@ -89,6 +100,9 @@ impl Module {
             custom_data: None,
             num_inputs,
             num_outputs,
+            input_names: vec![],
+            output_names: vec![],
+            shape: None,
             decoration,
         }
     }
@@ -114,6 +128,53 @@ impl Module {
         }
     }
 
+    pub fn io_blocks(&self) -> Option<(BlockID, BlockID)> {
+        self.custom_data
+            .as_ref()
+            .map(|data| (data.input_block, data.output_block))
+    }
+
+    // generates the Input/Output blocks inside a new custom module
+    pub fn add_io_blocks(&mut self) {
+        let num_inputs = self.get_num_inputs();
+        let num_outputs = self.get_num_outputs();
+
+        let input_module = BUILTINS
+            .get(builtin::INPUT_MODULE_NAME.as_str())
+            .unwrap()
+            .module();
+        let input_block = Block::new_sized(
+            &input_module,
+            Vector2(50, 50),
+            true,
+            num_inputs,
+            num_inputs,
+            None,
+        )
+        .with_pin_names(vec![], self.input_names().clone());
+
+        let output_module = BUILTINS
+            .get(builtin::OUTPUT_MODULE_NAME.as_str())
+            .unwrap()
+            .module();
+        let output_block = Block::new_sized(
+            &output_module,
+            Vector2(400, 50),
+            true,
+            num_outputs,
+            num_outputs,
+            None,
+        )
+        .with_pin_names(self.output_names().clone(), vec![]);
+
+        self.set_io_blocks(input_block.id(), output_block.id());
+
+        if let Some(plot) = self.plot_mut() {
+            plot.add_block(input_block);
+            plot.add_block(output_block);
+        }
+    }
+
     pub fn has_io_blocks(&self) -> bool {
         match &self.custom_data {
             Some(data) => data.input_block != Id::empty() && data.output_block != Id::empty(),
@@ -137,6 +198,10 @@ impl Module {
         &self.name
     }
 
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     pub fn get_num_inputs(&self) -> u8 {
         self.num_inputs
     }
@@ -145,10 +210,40 @@ impl Module {
         self.num_outputs
     }
 
+    pub fn set_num_pins(&mut self, num_inputs: u8, num_outputs: u8) {
+        self.num_inputs = num_inputs;
+        self.num_outputs = num_outputs;
+    }
+
+    pub fn input_names(&self) -> &Vec<String> {
+        &self.input_names
+    }
+
+    pub fn output_names(&self) -> &Vec<String> {
+        &self.output_names
+    }
+
+    pub fn set_pin_names(&mut self, input_names: Vec<String>, output_names: Vec<String>) {
+        self.input_names = input_names;
+        self.output_names = output_names;
+    }
+
     pub fn decoration(&self) -> &Decoration {
         &self.decoration
     }
 
+    pub fn set_decoration(&mut self, decoration: Decoration) {
+        self.decoration = decoration;
+    }
+
+    pub fn shape(&self) -> Option<&Shape> {
+        self.shape.as_ref()
+    }
+
+    pub fn set_shape(&mut self, shape: Option<Shape>) {
+        self.shape = shape;
+    }
+
     pub fn simulate(
         &mut self,
         inputs: u128,
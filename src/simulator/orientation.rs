@@ -0,0 +1,115 @@
+use crate::renderer::vector::Vector2;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transformation {
+    RotateClockwise,
+    RotateCounterClockwise,
+    Mirror,
+}
+
+// orientation of a block on the plot: the block is first mirrored horizontally,
+// then rotated clockwise by the given number of quarter turns
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Orientation {
+    rotation: u8,
+    mirrored: bool,
+}
+
+impl Orientation {
+    pub fn rotation(&self) -> u8 {
+        self.rotation
+    }
+
+    pub fn mirrored(&self) -> bool {
+        self.mirrored
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn transformed(self, transformation: Transformation) -> Self {
+        match transformation {
+            Transformation::RotateClockwise => Self {
+                rotation: (self.rotation + 1) % 4,
+                ..self
+            },
+            Transformation::RotateCounterClockwise => Self {
+                rotation: (self.rotation + 3) % 4,
+                ..self
+            },
+            // mirroring a rotated block on the plot reverses its rotation
+            Transformation::Mirror => Self {
+                rotation: (4 - self.rotation) % 4,
+                mirrored: !self.mirrored,
+            },
+        }
+    }
+
+    // bounding box of a block with the given unrotated size
+    pub fn size(&self, size: Vector2<i32>) -> Vector2<i32> {
+        match self.rotation % 2 {
+            0 => size,
+            _ => Vector2(size.1, size.0),
+        }
+    }
+
+    // maps an offset inside the unrotated block to an offset inside its bounding box
+    pub fn apply(&self, offset: Vector2<i32>, size: Vector2<i32>) -> Vector2<i32> {
+        let Vector2(w, h) = size;
+        let x = if self.mirrored {
+            w - offset.0
+        } else {
+            offset.0
+        };
+        let y = offset.1;
+
+        match self.rotation {
+            1 => Vector2(h - y, x),
+            2 => Vector2(w - x, h - y),
+            3 => Vector2(y, w - x),
+            _ => Vector2(x, y),
+        }
+    }
+
+    // maps an offset inside the bounding box back to the unrotated block
+    pub fn revert(&self, offset: Vector2<i32>, size: Vector2<i32>) -> Vector2<i32> {
+        let Vector2(w, h) = size;
+        let Vector2(x, y) = match self.rotation {
+            1 => Vector2(offset.1, h - offset.0),
+            2 => Vector2(w - offset.0, h - offset.1),
+            3 => Vector2(w - offset.1, offset.0),
+            _ => offset,
+        };
+
+        match self.mirrored {
+            true => Vector2(w - x, y),
+            false => Vector2(x, y),
+        }
+    }
+
+    // rotates and mirrors a direction without moving it
+    pub fn direction(&self, direction: Vector2<i32>) -> Vector2<i32> {
+        self.apply(direction, Vector2(0, 0))
+    }
+
+    // affine transformation (origin, x axis, y axis) mapping plot coordinates of the
+    // unrotated block at `position` to the plot coordinates of the oriented block
+    pub fn matrix(
+        &self,
+        position: Vector2<i32>,
+        size: Vector2<i32>,
+    ) -> (Vector2<i32>, Vector2<i32>, Vector2<i32>) {
+        let x_axis = self.direction(Vector2(1, 0));
+        let y_axis = self.direction(Vector2(0, 1));
+        let translation = self.apply(Vector2(0, 0), size);
+
+        let origin = position + translation
+            - Vector2(
+                x_axis.0 * position.0 + y_axis.0 * position.1,
+                x_axis.1 * position.0 + y_axis.1 * position.1,
+            );
+        (origin, x_axis, y_axis)
+    }
+}
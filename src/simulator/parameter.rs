@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Parameter {
+    Width,
+    Height,
+    Value,
+    Seed,
+}
+
+impl Parameter {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Width => "Width",
+            Self::Height => "Height",
+            Self::Value => "Value",
+            Self::Seed => "Seed",
+        }
+    }
+
+    // whether the parameter is entered as text (binary, decimal or hexadecimal)
+    pub fn is_text(&self) -> bool {
+        matches!(self, Self::Value | Self::Seed)
+    }
+}
+
+pub fn bit_mask(width: u8) -> u128 {
+    if width >= 128 {
+        u128::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+// parses values like `42`, `0x2a`, `0b101010` or `0o52`
+pub fn parse_value(text: &str) -> Option<u128> {
+    let text = text.trim().replace('_', "").to_ascii_lowercase();
+    let (digits, radix) = if let Some(hex) = text.strip_prefix("0x") {
+        (hex, 16)
+    } else if let Some(bin) = text.strip_prefix("0b") {
+        (bin, 2)
+    } else if let Some(oct) = text.strip_prefix("0o") {
+        (oct, 8)
+    } else {
+        (text.as_str(), 10)
+    };
+
+    u128::from_str_radix(digits, radix).ok()
+}
@@ -1,7 +1,8 @@
 use super::*;
 use crate::{
     application::selection::*,
-    project::{Project, ProjectRef},
+    id::Id,
+    project::{format, Project, ProjectRef},
     renderer::{vector::Vector2, *},
 };
 use serde::{Deserialize, Serialize};
@@ -84,9 +85,15 @@ impl PlotProvider {
 
 #[derive(Serialize, Debug, Default, Deserialize, Clone)]
 pub struct Plot {
+    #[serde(serialize_with = "format::sorted_map")]
     blocks: HashMap<BlockID, Block>,
+    #[serde(serialize_with = "format::sorted_map")]
     connections: HashMap<ConnectionID, Connection>,
 
+    #[serde(default, serialize_with = "format::sorted_map")]
+    annotations: HashMap<AnnotationID, Annotation>,
+
+    #[serde(skip)]
     states: Vec<PlotState>,
 
     #[serde(skip)]
@@ -105,6 +112,7 @@ impl Plot {
         Self {
             blocks: HashMap::new(),
             connections: HashMap::new(),
+            annotations: HashMap::new(),
             states: vec![PlotState::default()],
             selection: Selection::None,
             to_update: HashSet::new(),
@@ -145,6 +153,125 @@ impl Plot {
         self.blocks.get_mut(&id)
     }
 
+    // returns the previous parameter values and the original versions of all connections
+    // that had to be changed because of ports being removed
+    pub fn configure_block(
+        &mut self,
+        id: BlockID,
+        parameters: &[(Parameter, u128)],
+    ) -> (Vec<(Parameter, u128)>, Vec<Connection>) {
+        let mut previous = vec![];
+        let mut connectors = None;
+        if let Some(block) = self.blocks.get_mut(&id) {
+            for (parameter, value) in parameters {
+                if let Some(old) = block.decoration().parameter(*parameter) {
+                    previous.push((*parameter, old));
+                    block.set_parameter(*parameter, *value);
+                }
+            }
+            connectors = block.decoration().connectors();
+            self.to_update.insert(id);
+        }
+
+        let changed = match connectors {
+            Some((num_inputs, num_outputs)) => self.resize_block(id, num_inputs, num_outputs),
+            None => vec![],
+        };
+        (previous, changed)
+    }
+
+    // changes the number of ports of a block, disconnecting everything from removed ports;
+    // returns the original versions of all affected connections
+    pub fn resize_block(
+        &mut self,
+        id: BlockID,
+        num_inputs: u8,
+        num_outputs: u8,
+    ) -> Vec<Connection> {
+        let Some(block) = self.blocks.get(&id) else {
+            return vec![];
+        };
+
+        let dropped_outputs = block
+            .outputs()
+            .iter()
+            .skip(num_outputs as usize)
+            .filter_map(|c| *c)
+            .collect::<Vec<_>>();
+        let dropped_inputs = block
+            .inputs()
+            .iter()
+            .skip(num_inputs as usize)
+            .filter_map(|c| *c)
+            .collect::<Vec<_>>();
+
+        let mut changed = vec![];
+        for connection_id in dropped_outputs {
+            if let Some(mut connection) = self.remove_connection(connection_id) {
+                connection.set_active(true);
+                changed.push(connection);
+            }
+        }
+
+        for connection_id in dropped_inputs {
+            if changed.iter().any(|c| c.id() == connection_id) {
+                continue;
+            }
+
+            if let Some(mut connection) = self.remove_connection(connection_id) {
+                connection.set_active(true);
+                let mut pruned = connection.clone();
+                changed.push(connection);
+                if !pruned.remove_branches_to(id, num_inputs) {
+                    self.add_connection(pruned);
+                }
+            }
+        }
+
+        if let Some(block) = self.blocks.get_mut(&id) {
+            block.resize(num_inputs, num_outputs);
+        }
+        self.to_update.insert(id);
+        changed
+    }
+
+    // reverts connections changed by `resize_block` back to their original state
+    pub fn restore_connections(&mut self, connections: &[Connection]) {
+        for connection in connections {
+            self.remove_connection(connection.id());
+        }
+        for connection in connections {
+            self.add_connection(connection.clone());
+        }
+    }
+
+    pub fn annotations(&self) -> &HashMap<AnnotationID, Annotation> {
+        &self.annotations
+    }
+
+    pub fn add_annotation(&mut self, annotation: Annotation) {
+        self.annotations.insert(annotation.id(), annotation);
+    }
+
+    pub fn remove_annotation(&mut self, id: AnnotationID) -> Option<Annotation> {
+        self.annotations.remove(&id)
+    }
+
+    pub fn get_annotation(&self, id: AnnotationID) -> Option<&Annotation> {
+        self.annotations.get(&id)
+    }
+
+    pub fn get_annotation_mut(&mut self, id: AnnotationID) -> Option<&mut Annotation> {
+        self.annotations.get_mut(&id)
+    }
+
+    pub fn get_annotation_at(&self, position: Vector2<i32>) -> Option<AnnotationID> {
+        self.annotations
+            .values()
+            .find(|annotation| annotation.touches(position))
+            .map(|annotation| annotation.id())
+    }
+
     pub fn get_waypoint_at(&self, position: Vector2<i32>) -> Option<SegmentID> {
         for connection in self.connections.values() {
             if let Some(waypoint) = connection.waypoint_at(position) {
@@ -192,8 +319,11 @@ impl Plot {
 
     fn add_to_existing_connection(&mut self, existing: ConnectionID, connection: &Connection) {
         if let Some(existing) = self.connections.get_mut(&existing) {
-            for segment in connection.segments().values() {
-                existing.add_segment(segment.clone());
+            for (leg, segment) in connection.segments() {
+                let id = existing.add_segment(segment.clone());
+                if connection.is_routed(leg) {
+                    existing.set_routed(id, true);
+                }
             }
             let destinations = existing.destinations();
             let id = existing.id();
@@ -201,6 +331,70 @@ impl Plot {
         }
     }
 
+    // routes an unbranched wire of the connection around all blocks of this plot
+    fn route_leg(&self, connection: &mut Connection, leg: Id, target: (BlockID, u8)) {
+        let (Some(origin), Some(destination)) = (
+            self.blocks.get(&connection.origin().block_id()),
+            self.blocks.get(&target.0),
+        ) else {
+            return;
+        };
+
+        let obstacles = self
+            .blocks
+            .values()
+            .map(|block| (block.position(), block.bounds()))
+            .collect::<Vec<_>>();
+
+        let waypoints = router::route(
+            (
+                origin.get_connector_pos(connection.origin().into()),
+                origin.connector_direction(connection.origin().into()),
+            ),
+            (
+                destination.get_connector_pos(Connector::Input(target.1)),
+                destination.connector_direction(Connector::Input(target.1)),
+            ),
+            &obstacles,
+        );
+        connection.set_route(leg, &waypoints);
+    }
+
+    // routes all unbranched wires of the connection
+    pub fn route_connection(&self, connection: &mut Connection) {
+        for (leg, block_id, port) in connection.legs() {
+            self.route_leg(connection, leg, (block_id, port));
+        }
+    }
+
+    // updates the routed wires attached to a block after it was moved or rotated
+    pub fn reroute_block(&mut self, block_id: BlockID) {
+        let Some(block) = self.blocks.get(&block_id) else {
+            return;
+        };
+
+        for connection_id in block.connected_to() {
+            let Some(mut connection) = self.connections.get(&connection_id).cloned() else {
+                continue;
+            };
+
+            let from_block = connection.origin().block_id() == block_id;
+            let legs = connection
+                .routed_legs()
+                .into_iter()
+                .filter(|(_, target, _)| from_block || *target == block_id)
+                .collect::<Vec<_>>();
+            if legs.is_empty() {
+                continue;
+            }
+
+            for (leg, target, port) in legs {
+                self.route_leg(&mut connection, leg, (target, port));
+            }
+            self.connections.insert(connection_id, connection);
+        }
+    }
+
     pub unsafe fn add_connection_unsafe(&mut self, connection: Connection) {
         self.to_update.insert(connection.origin().block_id());
         self.connections.insert(connection.id(), connection);
@@ -267,6 +461,101 @@ impl Plot {
         deleted_connections
     }
 
+    // copy of the selected blocks and annotations with the connections between the blocks
+    pub fn excerpt(&self) -> Self {
+        let selected = self.selected();
+        let block_ids = selected
+            .iter()
+            .filter_map(Selectable::block_id)
+            .collect::<Vec<BlockID>>();
+        let mut excerpt = Self::new();
+
+        for block in block_ids.iter().filter_map(|id| self.blocks.get(id)) {
+            let mut block = block.clone();
+            block.outputs_mut().iter_mut().for_each(|c| {
+                if let Some(connection) = c.and_then(|id| self.connections.get(&id)) {
+                    let mut connection = connection.clone();
+                    if connection.remove_unselected_branches(&block_ids) {
+                        *c = None;
+                    } else {
+                        excerpt.connections.insert(connection.id(), connection);
+                    }
+                }
+            });
+
+            block.inputs_mut().iter_mut().for_each(|c| {
+                if let Some(connection) = c.and_then(|id| self.connections.get(&id))
+                    && !block_ids.contains(&connection.origin().block_id())
+                {
+                    *c = None;
+                }
+            });
+            excerpt.blocks.insert(block.id(), block);
+        }
+
+        for annotation in selected
+            .iter()
+            .filter_map(Selectable::annotation_id)
+            .filter_map(|id| self.annotations.get(&id))
+        {
+            excerpt
+                .annotations
+                .insert(annotation.id(), annotation.clone());
+        }
+
+        excerpt
+    }
+
+    pub fn clear_highlights(&mut self) {
+        self.blocks
+            .values_mut()
+            .for_each(|block| block.set_highlighted(false));
+        self.connections.values_mut().for_each(|connection| {
+            connection.for_each_mut_segment(|segment| segment.set_highlighted(false))
+        });
+        self.annotations
+            .values_mut()
+            .for_each(|annotation| annotation.set_highlighted(false));
+    }
+
+    // smallest area containing all blocks, waypoints and annotations, `None` if the plot is empty
+    pub fn bounds(&self) -> Option<(Vector2<i32>, Vector2<i32>)> {
+        fn waypoints(segment: &Segment, corners: &mut Vec<Vector2<i32>>) {
+            if let Segment::Waypoint(segments, position, _) = segment {
+                let hs = Vector2(Segment::HITBOX_SIZE, Segment::HITBOX_SIZE);
+                corners.extend([*position - hs, *position + hs]);
+                segments
+                    .values()
+                    .for_each(|segment| waypoints(segment, corners));
+            }
+        }
+
+        let mut corners = Vec::new();
+        for block in self.blocks.values() {
+            corners.extend([block.position(), block.position() + block.bounds()]);
+        }
+        for annotation in self.annotations.values() {
+            corners.extend([
+                annotation.position(),
+                annotation.position() + annotation.size(),
+            ]);
+        }
+        for connection in self.connections.values() {
+            connection
+                .segments()
+                .values()
+                .for_each(|segment| waypoints(segment, &mut corners));
+        }
+
+        let first = *corners.first()?;
+        Some(corners.iter().fold((first, first), |(min, max), corner| {
+            (
+                Vector2(cmp::min(min.0, corner.0), cmp::min(min.1, corner.1)),
+                Vector2(cmp::max(max.0, corner.0), cmp::max(max.1, corner.1)),
+            )
+        }))
+    }
+
     pub fn add_block_to_update(&mut self, block: BlockID) {
         self.to_update.insert(block);
     }
@@ -339,6 +628,15 @@ impl Renderable for Plot {
     {
         let screen_space = renderer.screen_space();
 
+        // render all annotations beneath the circuit
+        for annotation in self
+            .annotations
+            .values()
+            .filter(|annotation| annotation.is_in_area(&screen_space))
+        {
+            annotation.render(renderer, plot)?;
+        }
+
         // render all blocks
         for (_, block) in self
             .blocks
@@ -383,6 +681,11 @@ impl SelectionField for Plot {
                 {
                     waypoint.set_highlighted(false)
                 }
+                Selectable::Annotation(id)
+                    if let Some(annotation) = self.get_annotation_mut(id) =>
+                {
+                    annotation.set_highlighted(false)
+                }
                 _ => (),
             },
             Selection::Many(ids) => {
@@ -397,6 +700,11 @@ impl SelectionField for Plot {
                     {
                         waypoint.set_highlighted(false)
                     }
+                    Selectable::Annotation(id)
+                        if let Some(annotation) = self.get_annotation_mut(*id) =>
+                    {
+                        annotation.set_highlighted(false)
+                    }
                     _ => (),
                 });
             }
@@ -406,7 +714,10 @@ impl SelectionField for Plot {
                     .for_each(|(_, v)| v.set_highlighted(false));
                 self.connections_mut().iter_mut().for_each(|(_, c)| {
                     c.for_each_mut_segment(|segment| segment.set_highlighted(false))
-                })
+                });
+                self.annotations
+                    .values_mut()
+                    .for_each(|annotation| annotation.set_highlighted(false));
             }
             _ => (),
         }
@@ -452,6 +763,13 @@ impl SelectionField for Plot {
                 })
             }
 
+            for annotation in self.annotations.values_mut() {
+                if annotation.is_in_area(&area) {
+                    annotation.set_highlighted(true);
+                    selected.push(Selectable::Annotation(annotation.id()));
+                }
+            }
+
             self.selection = Selection::Many(selected)
         }
     }
@@ -461,11 +779,19 @@ impl SelectionField for Plot {
             self.blocks
                 .keys()
                 .map(|id| Selectable::Block(*id))
+                .chain(
+                    self.annotations
+                        .keys()
+                        .map(|id| Selectable::Annotation(*id)),
+                )
                 .collect(),
         );
         self.blocks
             .iter_mut()
             .for_each(|(_, block)| block.set_highlighted(true));
+        self.annotations
+            .values_mut()
+            .for_each(|annotation| annotation.set_highlighted(true));
 
         fn highlight_segment(segment: &mut Segment) {
             segment.set_highlighted(true)
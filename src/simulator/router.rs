@@ -0,0 +1,226 @@
+use crate::{application::editor::GRID_SIZE, renderer::vector::Vector2};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+// additional cost of changing direction, keeps routes free of unnecessary bends
+const BEND_COST: i32 = 3;
+// free space (in grid cells) around the start, end and obstacles the router may use
+const SEARCH_MARGIN: i32 = 8;
+// upper bound of visited grid cells before falling back to a simple route
+const MAX_VISITED: usize = 40_000;
+
+const DIRECTIONS: [Vector2<i32>; 4] =
+    [Vector2(1, 0), Vector2(0, 1), Vector2(-1, 0), Vector2(0, -1)];
+
+// axis-aligned rectangle (position, size) wires may not pass through
+pub type Obstacle = (Vector2<i32>, Vector2<i32>);
+
+fn direction_index(direction: Vector2<i32>) -> usize {
+    DIRECTIONS
+        .iter()
+        .position(|d| *d == direction)
+        .unwrap_or_default()
+}
+
+// rounds a coordinate to the grid, away from the connector it belongs to
+fn snap(value: i32, direction: i32) -> i32 {
+    match direction {
+        1 => (value as f64 / GRID_SIZE as f64).ceil() as i32,
+        -1 => (value as f64 / GRID_SIZE as f64).floor() as i32,
+        _ => (value as f64 / GRID_SIZE as f64).round() as i32,
+    }
+}
+
+// grid cell right in front of a connector
+fn exit_cell(position: Vector2<i32>, direction: Vector2<i32>) -> Vector2<i32> {
+    let stub = position + direction * Vector2::from(GRID_SIZE / 2);
+    Vector2(snap(stub.0, direction.0), snap(stub.1, direction.1))
+}
+
+// connects a connector to its exit cell using only horizontal and vertical lines
+fn exit_path(
+    position: Vector2<i32>,
+    direction: Vector2<i32>,
+    cell: Vector2<i32>,
+) -> Vec<Vector2<i32>> {
+    let cell = cell * GRID_SIZE.into();
+    let corner = match direction.0 {
+        0 => Vector2(position.0, cell.1),
+        _ => Vector2(cell.0, position.1),
+    };
+    vec![position, corner, cell]
+}
+
+fn is_blocked(cell: Vector2<i32>, obstacles: &[Obstacle]) -> bool {
+    let point = cell * GRID_SIZE.into();
+    obstacles.iter().any(|(position, size)| {
+        point.0 >= position.0
+            && point.0 <= position.0 + size.0
+            && point.1 >= position.1
+            && point.1 <= position.1 + size.1
+    })
+}
+
+// A* search over grid cells, returns the visited cells from `start` to `end`
+fn search(
+    start: (Vector2<i32>, usize),
+    end: (Vector2<i32>, usize),
+    obstacles: &[Obstacle],
+) -> Option<Vec<Vector2<i32>>> {
+    let (mut min, mut max) = (
+        Vector2(start.0 .0.min(end.0 .0), start.0 .1.min(end.0 .1)),
+        Vector2(start.0 .0.max(end.0 .0), start.0 .1.max(end.0 .1)),
+    );
+    for (position, size) in obstacles {
+        let (from, to) = (
+            Vector2(snap(position.0, -1), snap(position.1, -1)),
+            Vector2(snap(position.0 + size.0, 1), snap(position.1 + size.1, 1)),
+        );
+        if to.0 >= min.0 - SEARCH_MARGIN
+            && from.0 <= max.0 + SEARCH_MARGIN
+            && to.1 >= min.1 - SEARCH_MARGIN
+            && from.1 <= max.1 + SEARCH_MARGIN
+        {
+            min = Vector2(min.0.min(from.0), min.1.min(from.1));
+            max = Vector2(max.0.max(to.0), max.1.max(to.1));
+        }
+    }
+    min -= SEARCH_MARGIN.into();
+    max += SEARCH_MARGIN.into();
+
+    let heuristic = |cell: Vector2<i32>| (cell.0 - end.0 .0).abs() + (cell.1 - end.0 .1).abs();
+
+    let mut queue = BinaryHeap::new();
+    let mut costs = HashMap::new();
+    let mut previous = HashMap::new();
+
+    costs.insert(start, 0);
+    queue.push(Reverse((
+        heuristic(start.0),
+        0,
+        start.0 .0,
+        start.0 .1,
+        start.1,
+    )));
+
+    while let Some(Reverse((_, cost, x, y, direction))) = queue.pop() {
+        let state = (Vector2(x, y), direction);
+        if costs.get(&state).is_some_and(|known| *known < cost) {
+            continue;
+        }
+
+        if state.0 == end.0 {
+            let mut cells = vec![state.0];
+            let mut current = state;
+            while let Some(prev) = previous.get(&current) {
+                current = *prev;
+                cells.push(current.0);
+            }
+            cells.reverse();
+            return Some(cells);
+        }
+
+        if costs.len() > MAX_VISITED {
+            return None;
+        }
+
+        for (next_direction, offset) in DIRECTIONS.iter().enumerate() {
+            // never reverse on the spot
+            if next_direction == (direction + 2) % 4 {
+                continue;
+            }
+
+            let cell = state.0 + *offset;
+            if cell.0 < min.0
+                || cell.1 < min.1
+                || cell.0 > max.0
+                || cell.1 > max.1
+                || (cell != end.0 && is_blocked(cell, obstacles))
+            {
+                continue;
+            }
+
+            let mut next_cost = cost + 1;
+            if next_direction != direction {
+                next_cost += BEND_COST;
+            }
+            // arriving at the end from the wrong side needs another bend
+            if cell == end.0 && next_direction != end.1 {
+                next_cost += BEND_COST;
+            }
+
+            let next = (cell, next_direction);
+            if !costs.get(&next).is_some_and(|known| *known <= next_cost) {
+                costs.insert(next, next_cost);
+                previous.insert(next, state);
+                queue.push(Reverse((
+                    next_cost + heuristic(cell),
+                    next_cost,
+                    cell.0,
+                    cell.1,
+                    next_direction,
+                )));
+            }
+        }
+    }
+
+    None
+}
+
+// removes duplicate points and points lying on a straight line between their neighbours
+fn simplify(points: Vec<Vector2<i32>>) -> Vec<Vector2<i32>> {
+    let mut simplified: Vec<Vector2<i32>> = Vec::with_capacity(points.len());
+    for point in points {
+        if simplified.last() == Some(&point) {
+            continue;
+        }
+
+        if let [.., a, b] = simplified.as_slice()
+            && ((a.0 == b.0 && b.0 == point.0) || (a.1 == b.1 && b.1 == point.1))
+        {
+            simplified.pop();
+        }
+        simplified.push(point);
+    }
+    simplified
+}
+
+// computes an orthogonal wire route from an output to an input connector around the given
+// obstacles, returns the corner points in between, excluding `start` and `end` themselves
+pub fn route(
+    (start, start_direction): (Vector2<i32>, Vector2<i32>),
+    (end, end_direction): (Vector2<i32>, Vector2<i32>),
+    obstacles: &[Obstacle],
+) -> Vec<Vector2<i32>> {
+    let start_cell = exit_cell(start, start_direction);
+    let end_cell = exit_cell(end, end_direction);
+
+    let cells = search(
+        (start_cell, direction_index(start_direction)),
+        // the wire enters the input connector against its direction
+        (end_cell, direction_index(end_direction * Vector2::from(-1))),
+        obstacles,
+    )
+    .unwrap_or_else(|| {
+        // no free route found, cross the obstacles with a simple z-shaped wire
+        let middle = (start_cell.0 + end_cell.0) / 2;
+        vec![
+            start_cell,
+            Vector2(middle, start_cell.1),
+            Vector2(middle, end_cell.1),
+            end_cell,
+        ]
+    });
+
+    let mut points = exit_path(start, start_direction, start_cell);
+    points.extend(cells.into_iter().map(|cell| cell * GRID_SIZE.into()));
+    points.extend(exit_path(end, end_direction, end_cell).into_iter().rev());
+
+    let points = simplify(points);
+    match points.len() {
+        0..=2 => vec![],
+        len => points[1..len - 1].to_vec(),
+    }
+}
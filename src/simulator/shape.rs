@@ -0,0 +1,561 @@
+use crate::renderer::{vector::Vector2, *};
+use serde::{Deserialize, Serialize};
+use std::{cmp, f64, fmt};
+
+// a single drawing command of a custom block outline, relative to the block's position
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum PathCommand {
+    MoveTo(Vector2<i32>),
+    LineTo(Vector2<i32>),
+    CurveTo(Vector2<i32>, Vector2<i32>, Vector2<i32>),
+    // center, radius, start and end angle in degrees
+    Arc(Vector2<i32>, i32, i32, i32),
+    ArcNegative(Vector2<i32>, i32, i32, i32),
+    Close,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ShapePreset {
+    Rectangle,
+    And,
+    Or,
+    Buffer,
+    Trapezoid,
+}
+
+impl ShapePreset {
+    pub const ALL: [Self; 5] = [
+        Self::Rectangle,
+        Self::And,
+        Self::Or,
+        Self::Buffer,
+        Self::Trapezoid,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Rectangle => "Rectangle",
+            Self::And => "AND Gate",
+            Self::Or => "OR Gate",
+            Self::Buffer => "Buffer (Triangle)",
+            Self::Trapezoid => "Multiplexer (Trapezoid)",
+        }
+    }
+}
+
+// custom outline of a block with the positions of its pins
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Shape {
+    size: Vector2<i32>,
+    outline: Vec<PathCommand>,
+    inputs: Vec<Vector2<i32>>,
+    outputs: Vec<Vector2<i32>>,
+}
+
+impl Shape {
+    pub const MIN_SIZE: i32 = 10;
+    pub const MAX_SIZE: i32 = 1000;
+    const PIN_SPACING: i32 = 25;
+
+    pub fn size(&self) -> Vector2<i32> {
+        self.size
+    }
+
+    pub fn outline(&self) -> &Vec<PathCommand> {
+        &self.outline
+    }
+
+    pub fn inputs(&self) -> &Vec<Vector2<i32>> {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &Vec<Vector2<i32>> {
+        &self.outputs
+    }
+
+    pub fn input_position(&self, index: u8) -> Option<Vector2<i32>> {
+        self.inputs.get(index as usize).copied()
+    }
+
+    pub fn output_position(&self, index: u8) -> Option<Vector2<i32>> {
+        self.outputs.get(index as usize).copied()
+    }
+
+    // evenly distributed pin heights, centered vertically
+    fn spread(count: u8, height: i32) -> impl Iterator<Item = i32> {
+        let offset = (height - (count as i32 - 1) * Self::PIN_SPACING) / 2;
+        (0..count as i32).map(move |i| offset + i * Self::PIN_SPACING)
+    }
+
+    pub fn preset(preset: ShapePreset, num_inputs: u8, num_outputs: u8) -> Self {
+        let height = cmp::max(num_inputs, num_outputs).max(1) as i32 * Self::PIN_SPACING + 25;
+        let half = height / 2;
+        let width = match preset {
+            ShapePreset::Rectangle => 75,
+            ShapePreset::Trapezoid => 60,
+            ShapePreset::And | ShapePreset::Or | ShapePreset::Buffer => 40 + half,
+        };
+
+        let outline = match preset {
+            ShapePreset::Rectangle => vec![
+                PathCommand::MoveTo(Vector2(0, 0)),
+                PathCommand::LineTo(Vector2(width, 0)),
+                PathCommand::LineTo(Vector2(width, height)),
+                PathCommand::LineTo(Vector2(0, height)),
+                PathCommand::Close,
+            ],
+            ShapePreset::And => vec![
+                PathCommand::MoveTo(Vector2(0, 0)),
+                PathCommand::LineTo(Vector2(40, 0)),
+                PathCommand::Arc(Vector2(40, half), half, -90, 90),
+                PathCommand::LineTo(Vector2(0, height)),
+                PathCommand::Close,
+            ],
+            ShapePreset::Or => or_outline(Vector2(width, height)),
+            ShapePreset::Buffer => vec![
+                PathCommand::MoveTo(Vector2(0, 0)),
+                PathCommand::LineTo(Vector2(width, half)),
+                PathCommand::LineTo(Vector2(0, height)),
+                PathCommand::Close,
+            ],
+            ShapePreset::Trapezoid => vec![
+                PathCommand::MoveTo(Vector2(0, 0)),
+                PathCommand::LineTo(Vector2(width, height / 5)),
+                PathCommand::LineTo(Vector2(width, height * 4 / 5)),
+                PathCommand::LineTo(Vector2(0, height)),
+                PathCommand::Close,
+            ],
+        };
+
+        // horizontal position of the outline at the given height, on the left or right side
+        let edge = |y: i32, left: bool| {
+            let dy = (y - half) as f64 / half as f64;
+            match (preset, left) {
+                (ShapePreset::Or, true) => (width as f64 / 6. * (1. - dy * dy)) as i32,
+                (_, true) => 0,
+                (ShapePreset::And, false) => {
+                    40 + (half as f64 * (1. - dy * dy).max(0.).sqrt()) as i32
+                }
+                (ShapePreset::Or, false) => width - (width as f64 * 0.1 * dy * dy) as i32,
+                (ShapePreset::Buffer, false) => width - (width as f64 * dy.abs()) as i32,
+                (_, false) => width,
+            }
+        };
+
+        Self {
+            size: Vector2(width, height),
+            outline,
+            inputs: Self::spread(num_inputs, height)
+                .map(|y| Vector2(edge(y, true), y))
+                .collect(),
+            outputs: Self::spread(num_outputs, height)
+                .map(|y| Vector2(edge(y, false), y))
+                .collect(),
+        }
+    }
+
+    pub fn validate(&self, num_inputs: u8, num_outputs: u8) -> Result<(), String> {
+        if self.inputs.len() != num_inputs as usize {
+            return Err(format!(
+                "Expected {num_inputs} input pin(s), found {}",
+                self.inputs.len()
+            ));
+        }
+        if self.outputs.len() != num_outputs as usize {
+            return Err(format!(
+                "Expected {num_outputs} output pin(s), found {}",
+                self.outputs.len()
+            ));
+        }
+        Ok(())
+    }
+
+    // parses the text representation used by the shape editor, see `fmt::Display`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut size = None;
+        let mut outline = vec![];
+        let mut inputs = vec![];
+        let mut outputs = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let command = words.next().unwrap_or_default();
+            let args = words
+                .map(|word| word.parse::<i32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| format!("Line {}: {err}", i + 1))?;
+
+            let expect = |count: usize| {
+                if args.len() == count {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Line {}: `{command}` expects {count} number(s), found {}",
+                        i + 1,
+                        args.len()
+                    ))
+                }
+            };
+
+            match command {
+                "size" => {
+                    expect(2)?;
+                    if args
+                        .iter()
+                        .any(|&v| !(Self::MIN_SIZE..=Self::MAX_SIZE).contains(&v))
+                    {
+                        return Err(format!(
+                            "Line {}: size has to be between {} and {}",
+                            i + 1,
+                            Self::MIN_SIZE,
+                            Self::MAX_SIZE
+                        ));
+                    }
+                    size = Some(Vector2(args[0], args[1]));
+                }
+                "move" => {
+                    expect(2)?;
+                    outline.push(PathCommand::MoveTo(Vector2(args[0], args[1])));
+                }
+                "line" => {
+                    expect(2)?;
+                    outline.push(PathCommand::LineTo(Vector2(args[0], args[1])));
+                }
+                "curve" => {
+                    expect(6)?;
+                    outline.push(PathCommand::CurveTo(
+                        Vector2(args[0], args[1]),
+                        Vector2(args[2], args[3]),
+                        Vector2(args[4], args[5]),
+                    ));
+                }
+                "arc" | "arcn" => {
+                    expect(5)?;
+                    let center = Vector2(args[0], args[1]);
+                    outline.push(if command == "arc" {
+                        PathCommand::Arc(center, args[2], args[3], args[4])
+                    } else {
+                        PathCommand::ArcNegative(center, args[2], args[3], args[4])
+                    });
+                }
+                "close" => {
+                    expect(0)?;
+                    outline.push(PathCommand::Close);
+                }
+                "input" => {
+                    expect(2)?;
+                    inputs.push(Vector2(args[0], args[1]));
+                }
+                "output" => {
+                    expect(2)?;
+                    outputs.push(Vector2(args[0], args[1]));
+                }
+                _ => return Err(format!("Line {}: unknown command `{command}`", i + 1)),
+            }
+        }
+
+        let size = size.ok_or_else(|| "Missing `size` command".to_string())?;
+        if outline.is_empty() {
+            return Err("The outline is empty".to_string());
+        }
+        if let Some(pin) = inputs
+            .iter()
+            .chain(outputs.iter())
+            .find(|pin| pin.0 < 0 || pin.1 < 0 || pin.0 > size.0 || pin.1 > size.1)
+        {
+            return Err(format!(
+                "Pin at {}, {} lies outside of the shape's size",
+                pin.0, pin.1
+            ));
+        }
+
+        Ok(Self {
+            size,
+            outline,
+            inputs,
+            outputs,
+        })
+    }
+
+    // adds the outline to the renderer's current path
+    pub fn trace<R>(&self, renderer: &R, origin: Vector2<i32>)
+    where
+        R: Renderer,
+    {
+        trace_path(renderer, origin, &self.outline);
+    }
+}
+
+// outline of an OR gate filling `size`, shared by the shape preset and the distinctive gate symbol
+fn or_outline(size: Vector2<i32>) -> Vec<PathCommand> {
+    let Vector2(width, height) = size;
+    vec![
+        PathCommand::MoveTo(Vector2(0, 0)),
+        PathCommand::LineTo(Vector2(width * 2 / 5, 0)),
+        PathCommand::CurveTo(
+            Vector2(width * 3 / 4, 0),
+            Vector2(width * 19 / 20, height / 4),
+            Vector2(width, height / 2),
+        ),
+        PathCommand::CurveTo(
+            Vector2(width * 19 / 20, height * 3 / 4),
+            Vector2(width * 3 / 4, height),
+            Vector2(width * 2 / 5, height),
+        ),
+        PathCommand::LineTo(Vector2(0, height)),
+        PathCommand::CurveTo(
+            Vector2(width * 2 / 9, height * 3 / 4),
+            Vector2(width * 2 / 9, height / 4),
+            Vector2(0, 0),
+        ),
+    ]
+}
+
+// adds the given commands to the renderer's current path, relative to `origin`
+fn trace_path<R>(renderer: &R, origin: Vector2<i32>, outline: &[PathCommand])
+where
+    R: Renderer,
+{
+    let mut start = origin;
+    for command in outline.iter() {
+        match command {
+            PathCommand::MoveTo(position) => {
+                start = origin + *position;
+                renderer.move_to(start);
+            }
+            PathCommand::LineTo(position) => {
+                renderer.line_to(origin + *position);
+            }
+            PathCommand::CurveTo(a, b, c) => {
+                renderer.curve_to(origin + *a, origin + *b, origin + *c);
+            }
+            PathCommand::Arc(center, radius, from, to) => {
+                renderer.arc(
+                    origin + *center,
+                    *radius as f64,
+                    (*from as f64).to_radians(),
+                    (*to as f64).to_radians(),
+                );
+            }
+            PathCommand::ArcNegative(center, radius, from, to) => {
+                renderer.arc_negative(
+                    origin + *center,
+                    *radius as f64,
+                    (*from as f64).to_radians(),
+                    (*to as f64).to_radians(),
+                );
+            }
+            PathCommand::Close => {
+                renderer.line_to(start);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "size {} {}", self.size.0, self.size.1)?;
+        for command in self.outline.iter() {
+            match command {
+                PathCommand::MoveTo(p) => writeln!(f, "move {} {}", p.0, p.1),
+                PathCommand::LineTo(p) => writeln!(f, "line {} {}", p.0, p.1),
+                PathCommand::CurveTo(a, b, c) => {
+                    writeln!(f, "curve {} {} {} {} {} {}", a.0, a.1, b.0, b.1, c.0, c.1)
+                }
+                PathCommand::Arc(c, r, from, to) => {
+                    writeln!(f, "arc {} {} {r} {from} {to}", c.0, c.1)
+                }
+                PathCommand::ArcNegative(c, r, from, to) => {
+                    writeln!(f, "arcn {} {} {r} {from} {to}", c.0, c.1)
+                }
+                PathCommand::Close => writeln!(f, "close"),
+            }?;
+        }
+        for pin in self.inputs.iter() {
+            writeln!(f, "input {} {}", pin.0, pin.1)?;
+        }
+        for pin in self.outputs.iter() {
+            writeln!(f, "output {} {}", pin.0, pin.1)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GateOutline {
+    And,
+    Or,
+    Xor,
+    Buffer,
+}
+
+// ANSI/IEEE distinctive-shape symbol of a builtin logic gate, drawn inside the block's bounds
+#[derive(Clone, Copy)]
+pub struct DistinctiveGate {
+    outline: GateOutline,
+    negated: bool,
+}
+
+impl DistinctiveGate {
+    const INSET: i32 = 6;
+    const BUBBLE_RADIUS: i32 = 5;
+    const XOR_GAP: i32 = 7;
+
+    pub fn of(module: &str) -> Option<Self> {
+        let (outline, negated) = match module {
+            "And" => (GateOutline::And, false),
+            "Nand" => (GateOutline::And, true),
+            "Or" => (GateOutline::Or, false),
+            "Nor" => (GateOutline::Or, true),
+            "Xor" => (GateOutline::Xor, false),
+            "Xnor" => (GateOutline::Xor, true),
+            "Not" => (GateOutline::Buffer, true),
+            _ => return None,
+        };
+        Some(Self { outline, negated })
+    }
+
+    // vertical center of the gate body below the block's header, relative to the block's position
+    fn center(size: Vector2<i32>) -> i32 {
+        (25 + size.1) / 2
+    }
+
+    // top-left corner and size of the gate body, relative to the block's position
+    fn body(&self, size: Vector2<i32>) -> (Vector2<i32>, Vector2<i32>) {
+        let left = Self::INSET
+            + match self.outline {
+                GateOutline::Xor => Self::XOR_GAP,
+                _ => 0,
+            };
+        let right = size.0
+            - Self::INSET
+            - match self.negated {
+                true => Self::BUBBLE_RADIUS * 2,
+                false => 0,
+            };
+        (Vector2(left, 30), Vector2(right - left, size.1 - 35))
+    }
+
+    // horizontal offset of the body's input side at the given height
+    fn input_edge(&self, y: i32, body: Vector2<i32>) -> i32 {
+        match self.outline {
+            GateOutline::Or | GateOutline::Xor => {
+                let dy = ((y as f64 / body.1 as f64) * 2. - 1.).clamp(-1., 1.);
+                (body.0 as f64 / 6. * (1. - dy * dy)) as i32
+            }
+            _ => 0,
+        }
+    }
+
+    fn trace<R>(&self, renderer: &R, origin: Vector2<i32>, size: Vector2<i32>)
+    where
+        R: Renderer,
+    {
+        let (w, h) = (size.0 as f64, size.1 as f64);
+        let point = |x: f64, y: f64| origin + Vector2(x as i32, y as i32);
+
+        match self.outline {
+            GateOutline::And => {
+                // quarter ellipses approximated by cubic bezier curves
+                const K: f64 = 0.5523;
+                let rx = (h / 2.).min(w * 3. / 5.);
+                renderer
+                    .move_to(point(0., 0.))
+                    .line_to(point(w - rx, 0.))
+                    .curve_to(
+                        point(w - rx + K * rx, 0.),
+                        point(w, h / 2. - K * h / 2.),
+                        point(w, h / 2.),
+                    )
+                    .curve_to(
+                        point(w, h / 2. + K * h / 2.),
+                        point(w - rx + K * rx, h),
+                        point(w - rx, h),
+                    )
+                    .line_to(point(0., h))
+                    .line_to(point(0., 0.));
+            }
+            GateOutline::Or | GateOutline::Xor => trace_path(renderer, origin, &or_outline(size)),
+            GateOutline::Buffer => {
+                renderer
+                    .move_to(point(0., 0.))
+                    .line_to(point(w, h / 2.))
+                    .line_to(point(0., h))
+                    .line_to(point(0., 0.));
+            }
+        }
+    }
+
+    pub fn render<R>(
+        &self,
+        renderer: &R,
+        position: Vector2<i32>,
+        size: Vector2<i32>,
+        inputs: &[Vector2<i32>],
+        output: Vector2<i32>,
+        color: &Color,
+    ) -> Result<(), R::Error>
+    where
+        R: Renderer,
+    {
+        let (offset, body) = self.body(size);
+        let origin = position + offset;
+        let tip = Vector2(origin.0 + body.0, position.1 + Self::center(size));
+
+        renderer.set_color(color);
+        for input in inputs {
+            let edge = origin.0 + self.input_edge(input.1 - origin.1, body);
+            renderer.move_to(*input).line_to(Vector2(edge, input.1));
+        }
+        renderer.stroke()?;
+
+        let end = match self.negated {
+            true => tip + Vector2(Self::BUBBLE_RADIUS * 2, 0),
+            false => tip,
+        };
+        // pins are placed like in the rectangular style, so the output lead bends towards its pin
+        let bend = (end.0 + output.0) / 2;
+        renderer
+            .move_to(end)
+            .line_to(Vector2(bend, end.1))
+            .line_to(Vector2(bend, output.1))
+            .line_to(output)
+            .stroke()?;
+
+        self.trace(renderer, origin, body);
+        renderer
+            .set_color(unsafe { &COLOR_THEME.block_bg_color })
+            .fill()?;
+        self.trace(renderer, origin, body);
+        renderer.set_color(color).stroke()?;
+
+        if self.outline == GateOutline::Xor {
+            // the input side of the OR outline, repeated in front of the gate
+            let mut back = or_outline(body).split_off(4);
+            back[0] = PathCommand::MoveTo(Vector2(0, body.1));
+            trace_path(renderer, origin - Vector2(Self::XOR_GAP, 0), &back);
+            renderer.stroke()?;
+        }
+
+        if self.negated {
+            renderer
+                .arc(
+                    tip + Vector2(Self::BUBBLE_RADIUS, 0),
+                    Self::BUBBLE_RADIUS as f64,
+                    0.,
+                    f64::consts::TAU,
+                )
+                .set_color(unsafe { &COLOR_THEME.block_bg_color })
+                .fill_preserve()?
+                .set_color(color)
+                .stroke()?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,11 +1,18 @@
 use super::*;
 
+use crate::project::format;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct PlotState {
+    #[serde(serialize_with = "format::sorted_map")]
     blocks: HashMap<BlockID, State>,
+    // derived from the blocks' states, recomputed after loading
+    #[serde(skip)]
     connections: HashMap<ConnectionID, bool>,
+    // whether `connections` is known; states read from a file recompute the whole plot
+    #[serde(skip)]
+    complete: bool,
 }
 
 impl From<&mut Plot> for PlotState {
@@ -27,6 +34,7 @@ impl From<&Plot> for PlotState {
                 .iter()
                 .map(|(id, connection)| (*id, connection.is_active()))
                 .collect(),
+            complete: true,
         }
     }
 }
@@ -45,7 +53,11 @@ impl PlotState {
                 if let Some(state) = self.connections.get(id) {
                     connection.set_active(*state);
                 }
-            })
+            });
+
+        if !self.complete {
+            plot.update_all_blocks();
+        }
     }
 }
 
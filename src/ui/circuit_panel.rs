@@ -83,6 +83,10 @@ impl CircuitPanel {
         self.imp().remove_tab(module_name)
     }
 
+    pub fn rename_tab(&self, old_name: &String, new_name: &String, plot_provider: PlotProvider) {
+        self.imp().rename_tab(old_name, new_name, plot_provider)
+    }
+
     pub fn push_error(&self, error: String) {
         let template = self.imp();
         if template.info_bar.is_visible() {
@@ -161,6 +165,18 @@ impl CircuitPanelTemplate {
         }
     }
 
+    fn rename_tab(&self, old_name: &String, new_name: &String, plot_provider: PlotProvider) {
+        let mut pages = self.pages.borrow_mut();
+        if let Some(page) = pages.remove(old_name) {
+            page.set_title(new_name);
+            if let Ok(view) = page.child().downcast::<CircuitView>() {
+                view.set_plot_provider(plot_provider);
+                view.rerender();
+            }
+            pages.insert(new_name.clone(), page);
+        }
+    }
+
     fn set_title(&self, title: &str) {
         (self
             .header_bar
@@ -210,6 +226,11 @@ impl ObjectImpl for CircuitPanelTemplate {
             }
         }));
 
+        self.view
+            .connect_selected_page_notify(glib::clone!(@weak self as widget => move |_| {
+                widget.application.borrow().imp().refresh_properties();
+            }));
+
         self.view.connect_close_page(glib::clone!(@weak self as widget => @default-return false, move |view, page| {
             let is_main = page.child().downcast::<CircuitView>()
                 .map(|circuit_view| circuit_view.plot_provider().is_main());
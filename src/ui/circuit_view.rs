@@ -43,6 +43,10 @@ impl CircuitView {
         self.imp().plot_provider()
     }
 
+    pub fn set_plot_provider(&self, plot_provider: PlotProvider) {
+        self.imp().set_plot_provider(plot_provider);
+    }
+
     pub fn mouse_world_position(&self) -> Vector2<f64> {
         let mouse_position = self.imp().mouse_position.get();
         self.imp().renderer.borrow().screen_to_world(mouse_position)
@@ -77,6 +81,9 @@ pub struct CircuitViewTemplate {
     #[template_child]
     area_context_menu: TemplateChild<gtk::PopoverMenu>,
 
+    #[template_child]
+    annotation_context_menu: TemplateChild<gtk::PopoverMenu>,
+
     #[template_child]
     left_osd_box: TemplateChild<gtk::Box>,
 
@@ -118,6 +125,11 @@ impl CircuitViewTemplate {
         self
     }
 
+    // called whenever the selection changes outside of an action
+    fn refresh_properties(&self) {
+        self.application.borrow().imp().refresh_properties();
+    }
+
     fn init_buttons(&self) {
         self.zoom_reset
             .connect_clicked(glib::clone!(@weak self as widget => move |_| {
@@ -228,6 +240,9 @@ impl CircuitViewTemplate {
                     gdk::Key::Control_L | gdk::Key::Control_R => widget.ctrl_down.set(true),
                     gdk::Key::Shift_L | gdk::Key::Shift_R => widget.shift_down.set(true),
                     gdk::Key::Alt_L | gdk::Key::Alt_R => widget.alt_down.set(true),
+                    _ if !widget.ctrl_down.get() && !widget.alt_down.get() && let Some(c) = key.to_unicode() => {
+                        widget.key_to_selected_block(Some(c));
+                    }
                     _ => ()
                 }
                 gtk::Inhibit(true)
@@ -243,18 +258,63 @@ impl CircuitViewTemplate {
                     },
                     gdk::Key::Shift_L | gdk::Key::Shift_R => widget.shift_down.set(false),
                     gdk::Key::Alt_L | gdk::Key::Alt_R => widget.alt_down.set(false),
-                    _ => ()
+                    _ => {
+                        widget.key_to_selected_block(None);
+                    }
                 }
             ),
         );
         self.drawing_area.add_controller(&key_controller);
     }
 
+    // forwards key events to the selected block, `None` meaning a key was released
+    fn key_to_selected_block(&self, key: Option<char>) -> bool {
+        let handled = self
+            .plot_provider
+            .borrow()
+            .with_mut(|plot| {
+                let id = match plot.selection() {
+                    Selection::Single(Selectable::Block(id), _) => *id,
+                    _ => return false,
+                };
+
+                let handled = plot.get_block_mut(id).is_some_and(|block| match key {
+                    Some(key) => block.on_key_press(key),
+                    None => block.on_key_release(),
+                });
+                if handled {
+                    plot.add_block_to_update(id);
+                }
+                handled
+            })
+            .unwrap_or(false);
+
+        if handled {
+            self.drawing_area.queue_draw();
+            self.refresh_properties();
+        }
+        handled
+    }
+
     fn init_scrolling(&self) {
         let scroll_controller =
             gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
         scroll_controller.connect_scroll(glib::clone!(@weak self as widget => @default-panic, move |_, _, y| {
-            widget.renderer.borrow_mut().zoom(if y > 0. { 0.9 } else { 1.1 }, Some(widget.mouse_position.get()));
+            let position = VectorCast::cast(widget.renderer.borrow().screen_to_world(widget.mouse_position.get()));
+            let scrolled = widget.plot_provider.borrow().with_mut(|plot| {
+                if let Some(id) = plot.get_block_at(position) && plot.get_block_mut(id).unwrap().on_scroll(if y > 0. { -1 } else { 1 }) {
+                    plot.add_block_to_update(id);
+                    true
+                } else {
+                    false
+                }
+            }).unwrap_or(false);
+
+            if scrolled {
+                widget.refresh_properties();
+            } else {
+                widget.renderer.borrow_mut().zoom(if y > 0. { 0.9 } else { 1.1 }, Some(widget.mouse_position.get()));
+            }
             widget.drawing_area.queue_draw();
 
             gtk::Inhibit(true)
@@ -264,14 +324,14 @@ impl CircuitViewTemplate {
 
     fn init_drawing_area(&self) {
         self.drawing_area.set_draw_func(
-            glib::clone!(@weak self as widget => move |area, context, width, height|
+            glib::clone!(@weak self as widget => move |area, context, width, height| {
                 widget.plot_provider.borrow().with_mut(|plot|
                     widget.renderer.borrow_mut()
                         .callback(plot, *widget.editor_mode.borrow(), area, context, width, height)
                         .map(|_| ())
                         .unwrap_or_die()
                 );
-            ),
+            }),
         );
 
         self.drawing_area.set_focusable(true);
@@ -349,6 +409,19 @@ impl CircuitViewTemplate {
                         self.context_menu.popup();
                     }
                 }
+                None if let Some(id) = plot.get_annotation_at(VectorCast::cast(position)) => {
+                    let start_position = plot.get_annotation(id).unwrap().position();
+                    plot.unhighlight();
+                    plot.get_annotation_mut(id).unwrap().set_highlighted(true);
+                    plot.set_selection(Selection::Single(
+                        Selectable::Annotation(id),
+                        start_position,
+                    ));
+
+                    self.annotation_context_menu
+                        .set_pointing_to(Some(&gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+                    self.annotation_context_menu.popup();
+                }
                 None => {
                     self.area_context_menu
                         .set_pointing_to(Some(&gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
@@ -358,6 +431,7 @@ impl CircuitViewTemplate {
         });
 
         self.drawing_area.queue_draw();
+        self.refresh_properties();
     }
 
     fn set_left_osd_visible(&self, visible: bool) {
@@ -409,6 +483,22 @@ impl CircuitViewTemplate {
                     }
                     p.set_selection(Selection::Many(selected));
 
+                    true
+                } else if let Some(annotation_id) = p.get_annotation_at(position) {
+                    let mut selected = selected.clone();
+                    let annotation = p.get_annotation_mut(annotation_id).unwrap();
+                    if let Some(index) = selected
+                        .iter()
+                        .position(|sel| sel == &Selectable::Annotation(annotation_id))
+                    {
+                        selected.remove(index);
+                        annotation.set_highlighted(false);
+                    } else {
+                        annotation.set_highlighted(true);
+                        selected.push(Selectable::Annotation(annotation_id));
+                    }
+                    p.set_selection(Selection::Many(selected));
+
                     true
                 } else {
                     false
@@ -432,12 +522,14 @@ impl CircuitViewTemplate {
             Some(Selection::Many(block_ids)) => {
                 if self.shift_down.get() && self.selection_shift_click(block_ids, position) {
                     self.drawing_area.queue_draw();
+                    self.refresh_properties();
                     return;
                 }
             }
             Some(Selection::Single(block_id, _)) => {
                 if self.shift_down.get() && self.selection_shift_click(vec![block_id], position) {
                     self.drawing_area.queue_draw();
+                    self.refresh_properties();
                     return;
                 }
             }
@@ -481,12 +573,18 @@ impl CircuitViewTemplate {
                     waypoint.set_highlighted(true);
                     plot.set_selection(Selection::Single(Selectable::Waypoint(id), start))
                 }
+            } else if let Some(id) = plot.get_annotation_at(position) {
+                let annotation = plot.get_annotation_mut(id).unwrap();
+                let start = annotation.position();
+                annotation.set_highlighted(true);
+                plot.set_selection(Selection::Single(Selectable::Annotation(id), start));
             } else {
                 plot.set_selection(Selection::Area(position, position));
             }
         });
 
         self.drawing_area.queue_draw();
+        self.refresh_properties();
     }
 
     fn drag_update(&self, offset: Vector2<i32>) {
@@ -520,6 +618,15 @@ impl CircuitViewTemplate {
 
                             waypoint.unwrap().set_position(new_position);
                         }
+                        Selectable::Annotation(id) => {
+                            let annotation = plot.get_annotation_mut(id);
+                            if annotation.is_none() {
+                                plot.set_selection(Selection::None);
+                                return;
+                            }
+
+                            annotation.unwrap().set_position(new_position);
+                        }
                     }
                     self.drawing_area.queue_draw();
                 }
@@ -590,9 +697,18 @@ impl CircuitViewTemplate {
                                     id,
                                     Vector2(start_x, start_y),
                                     new_position,
+                                    false,
                                 )
                             })
                     }
+                    Selectable::Annotation(annotation_id) => {
+                        self.application.borrow().new_action(Action::MoveAnnotation(
+                            plot_provider.clone(),
+                            annotation_id,
+                            Vector2(start_x, start_y),
+                            new_position,
+                        ))
+                    }
                 }
             }
             Selection::Connection(ConnectionSource::Block(origin_id, output), _, position) => {
@@ -604,10 +720,14 @@ impl CircuitViewTemplate {
                             && let Some(block) = plot.get_block(block_id)
                             && let Some(i) = block.position_on_connection(position, true)
                         {
-                            block
-                                .connection(Connector::Input(i))
-                                .is_none()
-                                .then(|| Connection::new_basic(origin_id, output, block_id, i))
+                            block.connection(Connector::Input(i)).is_none().then(|| {
+                                let mut connection =
+                                    Connection::new_basic(origin_id, output, block_id, i);
+                                if matches!(*self.editor_mode.borrow(), EditorMode::Grid) {
+                                    plot.route_connection(&mut connection);
+                                }
+                                connection
+                            })
                         } else {
                             Some(Connection::new(
                                 Port::Output(origin_id, output),
@@ -656,7 +776,8 @@ impl CircuitViewTemplate {
             }
             Selection::Area(_, _) => {
                 plot_provider.with_mut(|plot| plot.highlight_area());
-                self.drawing_area.queue_draw()
+                self.drawing_area.queue_draw();
+                self.refresh_properties();
             }
             Selection::MouseEvent(block_id) => {
                 plot_provider.with_mut(|plot| {
@@ -667,6 +788,7 @@ impl CircuitViewTemplate {
                     plot.add_block_to_update(block_id);
                 });
                 self.drawing_area.queue_draw();
+                self.refresh_properties();
             }
             _ => {}
         };
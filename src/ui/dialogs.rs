@@ -1,15 +1,19 @@
 use adw::prelude::*;
 use gtk::{
-    subclass::prelude::ObjectSubclassIsExt, traits::DialogExt, Align, Box, ButtonsType,
-    ColorButton, Entry, Label, MessageDialog, Orientation, ResponseType,
+    glib, subclass::prelude::ObjectSubclassIsExt, traits::DialogExt, Align, Box, ButtonsType,
+    ColorButton, Entry, Grid, Label, MessageDialog, Orientation, ResponseType,
 };
 
 use crate::{
-    application::{action::Action, selection::SelectionField, Application},
-    renderer::{IntoColor, IntoRGBA, COLOR_THEME},
-    simulator::Module,
+    application::{action::Action, selection::*, Application},
+    renderer::{
+        decode_image, vector::Vector2, CairoRenderer, IntoColor, IntoRGBA, Renderer, COLOR_THEME,
+    },
+    simulator::{
+        parse_value, Annotation, Decoration, ImageData, Module, Parameter, Shape, ShapePreset,
+    },
 };
-use std::future::Future;
+use std::{cell::RefCell, future::Future, rc::Rc};
 
 fn create_new_module(
     app: Application,
@@ -121,6 +125,488 @@ pub async fn new_module(app: Application, window: gtk::Window, _data: ()) {
     }
 }
 
+pub async fn edit_module_interface(app: Application, window: gtk::Window, module_name: String) {
+    let pins = app
+        .imp()
+        .project()
+        .lock()
+        .unwrap()
+        .module(&module_name)
+        .filter(|module| !module.builtin())
+        .map(|module| {
+            (
+                module.get_num_inputs(),
+                module.get_num_outputs(),
+                module.shape().is_some(),
+            )
+        });
+
+    let Some((num_inputs, num_outputs, has_shape)) = pins else {
+        return;
+    };
+
+    let content = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .hexpand(true)
+        .spacing(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+
+    let pin_chooser = |label: &str, value: u8, tooltip: &str| {
+        content.append(&Label::new(Some(label)));
+        let adjustment = gtk::Adjustment::new(value as f64, 1.0, 129.0, 1.0, 1.0, 1.0);
+        let chooser = gtk::SpinButton::builder()
+            .climb_rate(1.0)
+            .adjustment(&adjustment)
+            .numeric(true)
+            .tooltip_text(tooltip)
+            .build();
+        content.append(&chooser);
+        chooser
+    };
+
+    let input_chooser = pin_chooser("Inputs:", num_inputs, "Select the number of input pins.");
+    let output_chooser = pin_chooser("Outputs:", num_outputs, "Select the number of output pins.");
+
+    let dialog = MessageDialog::builder()
+        .transient_for(&window)
+        .modal(true)
+        .buttons(ButtonsType::OkCancel)
+        .title("Edit Module Interface")
+        .text(&format!("Edit Interface of \"{module_name}\""))
+        .secondary_text(match has_shape {
+            true => "Connections to removed pins get disconnected in every plot. The module's custom shape is reset if it does not match the new pins",
+            false => "Connections to removed pins get disconnected in every plot",
+        })
+        .resizable(false)
+        .build();
+    dialog.content_area().append(&content);
+
+    let answer = dialog.run_future().await;
+    dialog.close();
+
+    let new = (
+        input_chooser.value_as_int() as u8,
+        output_chooser.value_as_int() as u8,
+    );
+    if answer == ResponseType::Ok && new != (num_inputs, num_outputs) {
+        app.new_action(Action::EditModuleInterface(
+            app.imp().project().clone(),
+            module_name,
+            new,
+            (num_inputs, num_outputs),
+            vec![],
+            None,
+        ));
+    }
+}
+
+pub async fn rename_module(app: Application, window: gtk::Window, module_name: String) {
+    let dialog = MessageDialog::builder()
+        .transient_for(&window)
+        .modal(true)
+        .resizable(false)
+        .title("Rename Module")
+        .text(&format!("Rename Module \"{module_name}\""))
+        .buttons(ButtonsType::OkCancel)
+        .build();
+
+    let entry = Entry::builder()
+        .text(&module_name)
+        .max_length(Module::MAX_MODULE_NAME_LEN)
+        .activates_default(true)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    dialog.content_area().append(&entry);
+    dialog.set_default_response(ResponseType::Ok);
+
+    let answer = dialog.run_future().await;
+    dialog.close();
+
+    let new_name = entry.buffer().text().trim().to_string();
+    if answer != ResponseType::Ok || new_name == module_name {
+        return;
+    }
+
+    let err = if new_name.is_empty() {
+        Some("Invalid name".to_string())
+    } else if app
+        .imp()
+        .project()
+        .lock()
+        .unwrap()
+        .module(&new_name)
+        .is_some()
+    {
+        Some(format!("Module with name \"{new_name}\" already exists"))
+    } else {
+        None
+    };
+
+    match err {
+        Some(err) => {
+            gtk::glib::MainContext::default().spawn_local(basic_error(app, window, err));
+        }
+        None => app.new_action(Action::RenameModule(
+            app.imp().project().clone(),
+            module_name,
+            new_name,
+        )),
+    }
+}
+
+pub async fn edit_module_appearance(app: Application, window: gtk::Window, module_name: String) {
+    const STYLES: [&str; 4] = ["None", "Label", "Negated Label", "Image"];
+
+    let current = app
+        .imp()
+        .project()
+        .lock()
+        .unwrap()
+        .module(&module_name)
+        .filter(|module| !module.builtin())
+        .map(|module| module.decoration().clone());
+
+    let Some(current) = current else {
+        return;
+    };
+
+    let (style, text, image) = match &current {
+        Decoration::Label(text) => (1, text.clone(), None),
+        Decoration::NotLabel(text) => (2, text.clone(), None),
+        Decoration::Image(image) => (3, String::new(), Some(image.clone())),
+        _ => (0, String::new(), None),
+    };
+    let image = Rc::new(RefCell::new(image));
+
+    let style_chooser = gtk::DropDown::from_strings(&STYLES);
+    style_chooser.set_selected(style);
+
+    let label_entry = Entry::builder()
+        .text(&text)
+        .placeholder_text("Label")
+        .activates_default(true)
+        .build();
+
+    let image_button = gtk::Button::with_label(if image.borrow().is_some() {
+        "Change Image…"
+    } else {
+        "Choose Image…"
+    });
+
+    let update_sensitivity = {
+        let label_entry = label_entry.clone();
+        let image_button = image_button.clone();
+        move |selected: u32| {
+            label_entry.set_sensitive(selected == 1 || selected == 2);
+            image_button.set_sensitive(selected == 3);
+        }
+    };
+    update_sensitivity(style);
+    style_chooser.connect_selected_notify(move |chooser| update_sensitivity(chooser.selected()));
+
+    image_button.connect_clicked(glib::clone!(@weak window, @strong image => move |button| {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("PNG and SVG images"));
+        filter.add_mime_type("image/png");
+        filter.add_mime_type("image/svg+xml");
+
+        let file_chooser = gtk::FileChooserNative::builder()
+            .transient_for(&window)
+            .modal(true)
+            .title("Choose Module Image")
+            .action(gtk::FileChooserAction::Open)
+            .accept_label("Open")
+            .cancel_label("Cancel")
+            .filter(&filter)
+            .build();
+
+        file_chooser.connect_response({
+            let file_chooser = RefCell::new(Some(file_chooser.clone()));
+            glib::clone!(@weak button, @strong image => move |_, response| {
+                if let Some(file_chooser) = file_chooser.take() {
+                    if response != ResponseType::Accept {
+                        return;
+                    }
+                    if let Some(path) = file_chooser.file().and_then(|file| file.path()) {
+                        match std::fs::read(&path) {
+                            Ok(data) => {
+                                image.replace(Some(ImageData::new(data)));
+                                button.set_label(&path.file_name().unwrap_or_default().to_string_lossy());
+                            }
+                            Err(err) => warn!("could not read image `{}`: {err}", path.display()),
+                        }
+                    }
+                }
+            })
+        });
+        file_chooser.show();
+    }));
+
+    let grid = Grid::builder()
+        .row_spacing(6)
+        .column_spacing(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    let rows: [(&str, &gtk::Widget); 3] = [
+        ("Style:", style_chooser.upcast_ref()),
+        ("Label:", label_entry.upcast_ref()),
+        ("Image:", image_button.upcast_ref()),
+    ];
+    for (i, (title, widget)) in rows.into_iter().enumerate() {
+        grid.attach(
+            &Label::builder().label(title).halign(Align::Start).build(),
+            0,
+            i as i32,
+            1,
+            1,
+        );
+        grid.attach(widget, 1, i as i32, 1, 1);
+    }
+
+    let dialog = MessageDialog::builder()
+        .transient_for(&window)
+        .modal(true)
+        .resizable(false)
+        .title("Edit Appearance")
+        .text(&format!("Appearance of \"{module_name}\""))
+        .buttons(ButtonsType::OkCancel)
+        .build();
+    dialog.content_area().append(&grid);
+    dialog.set_default_response(ResponseType::Ok);
+
+    let answer = dialog.run_future().await;
+    dialog.close();
+
+    if answer != ResponseType::Ok {
+        return;
+    }
+
+    let text = label_entry.buffer().text().trim().to_string();
+    let decoration = match style_chooser.selected() {
+        1 => Ok(Decoration::Label(text)),
+        2 => Ok(Decoration::NotLabel(text)),
+        3 => match image.take() {
+            Some(image) if decode_image(&image).is_some() => Ok(Decoration::Image(image)),
+            Some(_) => Err("The selected file is not a supported image".to_string()),
+            None => Err("No image selected".to_string()),
+        },
+        _ => Ok(Decoration::None),
+    };
+
+    match decoration {
+        Ok(decoration) => app.new_action(Action::SetModuleDecoration(
+            app.imp().project().clone(),
+            module_name,
+            decoration,
+            current,
+        )),
+        Err(err) => gtk::glib::MainContext::default().spawn_local(basic_error(app, window, err)),
+    }
+}
+
+// draws a shape scaled to fit into the preview area
+fn render_shape_preview(context: &gtk::cairo::Context, shape: &Shape, width: i32, height: i32) {
+    const MARGIN: f64 = 12.;
+
+    let size = shape.size();
+    let scale = f64::min(
+        (width as f64 - 2. * MARGIN) / size.0 as f64,
+        (height as f64 - 2. * MARGIN) / size.1 as f64,
+    )
+    .min(2.);
+    context.translate(
+        (width as f64 - size.0 as f64 * scale) / 2.,
+        (height as f64 - size.1 as f64 * scale) / 2.,
+    );
+    context.scale(scale, scale);
+
+    let renderer = CairoRenderer::for_context(context.clone());
+    shape.trace(&renderer, Vector2(0, 0));
+    renderer
+        .set_line_width(2.)
+        .set_color(unsafe { &COLOR_THEME.block_bg_color });
+    let _ = renderer.fill_preserve();
+    renderer.set_color(unsafe { &COLOR_THEME.border_color });
+    let _ = renderer.stroke();
+
+    for (pin, color) in shape
+        .inputs()
+        .iter()
+        .map(|pin| (pin, unsafe { &COLOR_THEME.disabled_fg_color }))
+        .chain(
+            shape
+                .outputs()
+                .iter()
+                .map(|pin| (pin, unsafe { &COLOR_THEME.enabled_fg_color })),
+        )
+    {
+        renderer
+            .arc(*pin, 4., 0., std::f64::consts::TAU)
+            .set_color(color);
+        let _ = renderer.fill();
+    }
+}
+
+pub async fn edit_module_shape(app: Application, window: gtk::Window, module_name: String) {
+    let module = app
+        .imp()
+        .project()
+        .lock()
+        .unwrap()
+        .module(&module_name)
+        .filter(|module| !module.builtin())
+        .map(|module| {
+            (
+                module.get_num_inputs(),
+                module.get_num_outputs(),
+                module.shape().cloned(),
+            )
+        });
+
+    let Some((num_inputs, num_outputs, current)) = module else {
+        return;
+    };
+
+    let text_view = gtk::TextView::builder()
+        .monospace(true)
+        .accepts_tab(false)
+        .build();
+    text_view.buffer().set_text(
+        &current
+            .clone()
+            .unwrap_or_else(|| Shape::preset(ShapePreset::Rectangle, num_inputs, num_outputs))
+            .to_string(),
+    );
+
+    let scrolled = gtk::ScrolledWindow::builder()
+        .child(&text_view)
+        .min_content_width(260)
+        .min_content_height(240)
+        .hexpand(true)
+        .build();
+
+    let preview = gtk::DrawingArea::builder()
+        .content_width(220)
+        .content_height(240)
+        .build();
+    let status = Label::builder().halign(Align::Start).wrap(true).build();
+
+    let parse = {
+        let buffer = text_view.buffer();
+        move || {
+            let (start, end) = buffer.bounds();
+            Shape::parse(&buffer.text(&start, &end, false)).and_then(|shape| {
+                shape.validate(num_inputs, num_outputs)?;
+                Ok(shape)
+            })
+        }
+    };
+
+    preview.set_draw_func({
+        let parse = parse.clone();
+        move |_, context, width, height| {
+            if let Ok(shape) = parse() {
+                render_shape_preview(context, &shape, width, height);
+            }
+        }
+    });
+
+    let update = {
+        let parse = parse.clone();
+        let preview = preview.clone();
+        let status = status.clone();
+        move || {
+            status.set_label(&parse().err().unwrap_or_default());
+            preview.queue_draw();
+        }
+    };
+    update();
+    text_view.buffer().connect_changed({
+        let update = update.clone();
+        move |_| update()
+    });
+
+    let preset_chooser = gtk::DropDown::from_strings(
+        &ShapePreset::ALL
+            .iter()
+            .map(|preset| preset.label())
+            .collect::<Vec<_>>(),
+    );
+    let preset_button = gtk::Button::with_label("Apply Preset");
+    preset_button.connect_clicked(
+        glib::clone!(@weak text_view, @weak preset_chooser => move |_| {
+            if let Some(preset) = ShapePreset::ALL.get(preset_chooser.selected() as usize) {
+                text_view
+                    .buffer()
+                    .set_text(&Shape::preset(*preset, num_inputs, num_outputs).to_string());
+            }
+        }),
+    );
+
+    let presets = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    presets.append(&preset_chooser);
+    presets.append(&preset_button);
+
+    let help = Label::builder()
+        .label("Commands: size w h, move x y, line x y, curve x1 y1 x2 y2 x y,\narc cx cy r from to, arcn cx cy r from to, close, input x y, output x y")
+        .halign(Align::Start)
+        .css_classes(vec!["dim-label".to_string()])
+        .build();
+
+    let grid = Grid::builder()
+        .row_spacing(6)
+        .column_spacing(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    grid.attach(&presets, 0, 0, 2, 1);
+    grid.attach(&scrolled, 0, 1, 1, 1);
+    grid.attach(&preview, 1, 1, 1, 1);
+    grid.attach(&status, 0, 2, 2, 1);
+    grid.attach(&help, 0, 3, 2, 1);
+
+    let dialog = MessageDialog::builder()
+        .transient_for(&window)
+        .modal(true)
+        .title("Edit Shape")
+        .text(&format!("Shape of \"{module_name}\""))
+        .buttons(ButtonsType::OkCancel)
+        .build();
+    dialog.add_button("Reset", ResponseType::Reject);
+    dialog.content_area().append(&grid);
+
+    let answer = dialog.run_future().await;
+    dialog.close();
+
+    let shape = match answer {
+        ResponseType::Ok => match parse() {
+            Ok(shape) => Some(shape),
+            Err(err) => {
+                gtk::glib::MainContext::default().spawn_local(basic_error(app, window, err));
+                return;
+            }
+        },
+        ResponseType::Reject => None,
+        _ => return,
+    };
+
+    if shape != current {
+        app.new_action(Action::SetModuleShape(
+            app.imp().project().clone(),
+            module_name,
+            shape,
+            current,
+        ));
+    }
+}
+
 pub async fn basic_error(_app: Application, window: gtk::Window, message: String) {
     let dialog = MessageDialog::builder()
         .transient_for(&window)
@@ -135,6 +621,21 @@ pub async fn basic_error(_app: Application, window: gtk::Window, message: String
     dialog.close();
 }
 
+pub async fn import_summary(_app: Application, window: gtk::Window, summary: Vec<String>) {
+    let dialog = MessageDialog::builder()
+        .transient_for(&window)
+        .modal(true)
+        .buttons(ButtonsType::Ok)
+        .resizable(false)
+        .text("Some components were left out or changed")
+        .secondary_text(&summary.join("\n"))
+        .title("Import Summary")
+        .build();
+
+    dialog.run_future().await;
+    dialog.close();
+}
+
 pub async fn confirm_delete_module(app: Application, window: gtk::Window, module_name: String) {
     let dialog = MessageDialog::builder()
         .transient_for(&window)
@@ -196,6 +697,390 @@ pub async fn select_border_color(app: Application, window: gtk::Window, _data: (
     }
 }
 
+pub async fn configure_block(app: Application, window: gtk::Window, _data: ()) {
+    let plot_provider = match app.imp().current_plot() {
+        Some(plot_provider) => plot_provider,
+        None => return,
+    };
+
+    let selected = plot_provider
+        .with(|plot| match plot.selection().blocks().as_slice() {
+            [block_id] => plot.get_block(*block_id).map(|block| {
+                let decoration = block.decoration();
+                let parameters = decoration
+                    .parameters()
+                    .into_iter()
+                    .map(|(parameter, value)| {
+                        (parameter, value, decoration.parameter_bounds(parameter))
+                    })
+                    .collect::<Vec<_>>();
+                (block.id(), parameters)
+            }),
+            _ => None,
+        })
+        .flatten();
+
+    if let Some((block_id, parameters)) = selected
+        && !parameters.is_empty()
+    {
+        let dialog = MessageDialog::builder()
+            .transient_for(&window)
+            .modal(true)
+            .resizable(false)
+            .title("Configure Block")
+            .text("Configure Block")
+            .buttons(ButtonsType::OkCancel)
+            .build();
+
+        let grid = Grid::builder()
+            .row_spacing(6)
+            .column_spacing(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+
+        let inputs = parameters
+            .iter()
+            .enumerate()
+            .map(|(i, (parameter, value, (min, max)))| {
+                let input = ParameterInput::new(*parameter, *value, *min, *max);
+
+                grid.attach(
+                    &Label::builder()
+                        .label(parameter.label())
+                        .halign(Align::Start)
+                        .build(),
+                    0,
+                    i as i32,
+                    1,
+                    1,
+                );
+                grid.attach(input.widget(), 1, i as i32, 1, 1);
+                (*parameter, input)
+            })
+            .collect::<Vec<_>>();
+
+        dialog.content_area().append(&grid);
+
+        let answer = dialog.run_future().await;
+        dialog.close();
+
+        if answer == ResponseType::Ok {
+            let values = inputs
+                .iter()
+                .map(|(parameter, input)| match input.value() {
+                    Some(value) => Ok((*parameter, value)),
+                    None => Err(format!("Invalid value for \"{}\"", parameter.label())),
+                })
+                .collect::<Result<Vec<_>, String>>();
+
+            match values {
+                Ok(values) => app.new_action(Action::ConfigureBlock(
+                    plot_provider,
+                    block_id,
+                    values,
+                    vec![],
+                    vec![],
+                )),
+                Err(err) => basic_error(app, window, err).await,
+            }
+        }
+    }
+}
+
+pub(super) enum ParameterInput {
+    Number(gtk::SpinButton),
+    Text(Entry),
+}
+
+impl ParameterInput {
+    pub(super) fn new(parameter: Parameter, value: u128, min: u128, max: u128) -> Self {
+        if parameter.is_text() {
+            Self::Text(
+                Entry::builder()
+                    .text(&value.to_string())
+                    .placeholder_text("e.g. 42, 0x2a, 0b101010")
+                    .build(),
+            )
+        } else {
+            let adjustment =
+                gtk::Adjustment::new(value as f64, min as f64, max as f64, 1.0, 1.0, 0.0);
+            Self::Number(
+                gtk::SpinButton::builder()
+                    .climb_rate(1.0)
+                    .adjustment(&adjustment)
+                    .numeric(true)
+                    .build(),
+            )
+        }
+    }
+
+    pub(super) fn widget(&self) -> &gtk::Widget {
+        match self {
+            Self::Number(input) => input.upcast_ref(),
+            Self::Text(input) => input.upcast_ref(),
+        }
+    }
+
+    pub(super) fn value(&self) -> Option<u128> {
+        match self {
+            Self::Number(input) => Some(input.value_as_int() as u128),
+            Self::Text(input) => parse_value(&input.buffer().text()),
+        }
+    }
+
+    pub(super) fn set_value(&self, value: u128, min: u128, max: u128) {
+        match self {
+            Self::Number(input) => {
+                input.set_range(min as f64, max as f64);
+                input.set_value(value as f64);
+            }
+            Self::Text(input) => input.set_text(&value.to_string()),
+        }
+    }
+
+    // `func` gets called when the user changes (or, for text inputs, confirms) the value
+    pub(super) fn connect_changed<F>(&self, func: F)
+    where
+        F: Fn(Option<u128>) + 'static,
+    {
+        match self {
+            Self::Number(input) => {
+                input.connect_value_changed(move |input| func(Some(input.value_as_int() as u128)));
+            }
+            Self::Text(input) => {
+                input.connect_activate(move |input| func(parse_value(&input.buffer().text())));
+            }
+        }
+    }
+}
+
+pub async fn set_label(app: Application, window: gtk::Window, _data: ()) {
+    let plot_provider = match app.imp().current_plot() {
+        Some(plot_provider) => plot_provider,
+        None => return,
+    };
+
+    let selected = plot_provider
+        .with(|plot| match plot.selection().blocks().as_slice() {
+            [block_id] => plot
+                .get_block(*block_id)
+                .map(|block| (block.id(), block.label().cloned())),
+            _ => None,
+        })
+        .flatten();
+
+    if let Some((block_id, label)) = selected {
+        let dialog = MessageDialog::builder()
+            .transient_for(&window)
+            .modal(true)
+            .resizable(false)
+            .title("Set Label")
+            .text("Set Label")
+            .secondary_text("Leave empty to show the module name")
+            .buttons(ButtonsType::OkCancel)
+            .build();
+
+        let entry = Entry::builder()
+            .text(label.as_deref().unwrap_or_default())
+            .activates_default(true)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        dialog.content_area().append(&entry);
+        dialog.set_default_response(ResponseType::Ok);
+
+        let answer = dialog.run_future().await;
+        dialog.close();
+
+        if answer == ResponseType::Ok {
+            let text = entry.buffer().text().trim().to_string();
+            app.new_action(Action::SetLabel(
+                plot_provider,
+                block_id,
+                (!text.is_empty()).then_some(text),
+                None,
+            ));
+        }
+    }
+}
+
+pub async fn pin_names(app: Application, window: gtk::Window, module_name: String) {
+    let pins = app
+        .imp()
+        .project()
+        .lock()
+        .unwrap()
+        .module(&module_name)
+        .filter(|module| !module.builtin())
+        .map(|module| {
+            let names = |names: &Vec<String>, count: u8| {
+                (0..count as usize)
+                    .map(|i| names.get(i).cloned().unwrap_or_default())
+                    .collect::<Vec<_>>()
+            };
+            (
+                names(module.input_names(), module.get_num_inputs()),
+                names(module.output_names(), module.get_num_outputs()),
+            )
+        });
+
+    let Some((input_names, output_names)) = pins else {
+        return;
+    };
+
+    let dialog = MessageDialog::builder()
+        .transient_for(&window)
+        .modal(true)
+        .title("Pin Names")
+        .text(&format!("Pin Names of \"{module_name}\""))
+        .secondary_text("Leave a name empty to show no name")
+        .buttons(ButtonsType::OkCancel)
+        .build();
+
+    let grid = Grid::builder()
+        .row_spacing(6)
+        .column_spacing(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+
+    let entries = |names: &Vec<String>, column: i32, kind: &str| {
+        grid.attach(
+            &Label::builder()
+                .label(&format!("{kind}s"))
+                .halign(Align::Start)
+                .build(),
+            column,
+            0,
+            1,
+            1,
+        );
+
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let entry = Entry::builder()
+                    .text(name)
+                    .placeholder_text(&format!("{kind} {i}"))
+                    .activates_default(true)
+                    .build();
+                grid.attach(&entry, column, i as i32 + 1, 1, 1);
+                entry
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let input_entries = entries(&input_names, 0, "Input");
+    let output_entries = entries(&output_names, 1, "Output");
+
+    let scrolled = gtk::ScrolledWindow::builder()
+        .child(&grid)
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .propagate_natural_height(true)
+        .max_content_height(400)
+        .build();
+    dialog.content_area().append(&scrolled);
+    dialog.set_default_response(ResponseType::Ok);
+
+    let answer = dialog.run_future().await;
+    dialog.close();
+
+    if answer == ResponseType::Ok {
+        let names = |entries: &Vec<Entry>| {
+            entries
+                .iter()
+                .map(|entry| entry.buffer().text().trim().to_string())
+                .collect::<Vec<_>>()
+        };
+        let new = (names(&input_entries), names(&output_entries));
+
+        if new != (input_names, output_names) {
+            app.new_action(Action::SetPinNames(
+                app.imp().project().clone(),
+                module_name,
+                new,
+                (vec![], vec![]),
+            ));
+        }
+    }
+}
+
+async fn annotation_text(window: &gtk::Window, title: &str, text: &str) -> Option<String> {
+    let dialog = MessageDialog::builder()
+        .transient_for(window)
+        .modal(true)
+        .title(title)
+        .text(title)
+        .buttons(ButtonsType::OkCancel)
+        .build();
+
+    let text_view = gtk::TextView::builder()
+        .wrap_mode(gtk::WrapMode::WordChar)
+        .accepts_tab(false)
+        .build();
+    text_view.buffer().set_text(text);
+
+    let scrolled = gtk::ScrolledWindow::builder()
+        .child(&text_view)
+        .min_content_width(300)
+        .min_content_height(120)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    dialog.content_area().append(&scrolled);
+
+    let answer = dialog.run_future().await;
+    dialog.close();
+
+    let buffer = text_view.buffer();
+    let text = buffer
+        .text(&buffer.start_iter(), &buffer.end_iter(), false)
+        .trim_end()
+        .to_string();
+    (answer == ResponseType::Ok && !text.is_empty()).then_some(text)
+}
+
+pub async fn new_annotation(app: Application, window: gtk::Window, position: Vector2<i32>) {
+    if let Some(plot_provider) = app.imp().current_plot()
+        && let Some(text) = annotation_text(&window, "New Annotation", "").await
+    {
+        app.new_action(Action::NewAnnotation(
+            plot_provider,
+            Annotation::new(text, position),
+        ));
+    }
+}
+
+pub async fn edit_annotation(app: Application, window: gtk::Window, _data: ()) {
+    let plot_provider = match app.imp().current_plot() {
+        Some(plot_provider) => plot_provider,
+        None => return,
+    };
+
+    let selected = plot_provider
+        .with(|plot| match plot.selection() {
+            Selection::Single(Selectable::Annotation(id), _) => plot
+                .get_annotation(*id)
+                .map(|annotation| (*id, annotation.text().clone())),
+            _ => None,
+        })
+        .flatten();
+
+    if let Some((annotation_id, old)) = selected
+        && let Some(new) = annotation_text(&window, "Edit Annotation", &old).await
+        && new != old
+    {
+        app.new_action(Action::EditAnnotation(
+            plot_provider,
+            annotation_id,
+            new,
+            old,
+        ));
+    }
+}
+
 pub fn run<F, T>(
     application: Application,
     window: gtk::Window,
@@ -1,4 +1,4 @@
-use super::{circuit_panel::*, circuit_view::*, module_list::*};
+use super::{circuit_panel::*, circuit_view::*, module_list::*, properties::*};
 use crate::{application::*, config, simulator::*};
 use adw::subclass::prelude::AdwApplicationWindowImpl;
 use gtk::{gdk::Display, gio, glib, prelude::*, subclass::prelude::*, IconTheme};
@@ -43,6 +43,24 @@ impl MainWindow {
         panel.remove_tab(module_name);
     }
 
+    pub fn update_module_in_ui(&self, app: &Application, module: &Module) {
+        let module_list = &self.imp().module_list;
+        module_list.remove_module_from_ui(module.name());
+        module_list.add_module_to_ui(app, module);
+    }
+
+    pub fn rename_module_in_ui(&self, app: &Application, old_name: &String, module: &Module) {
+        let module_list = &self.imp().module_list;
+        module_list.remove_module_from_ui(old_name);
+        module_list.add_module_to_ui(app, module);
+
+        self.imp().circuit_panel.rename_tab(
+            old_name,
+            module.name(),
+            PlotProvider::Module(app.imp().project().clone(), module.name().clone()),
+        );
+    }
+
     pub fn rerender_circuit(&self) {
         if let Some(a) = self.imp().circuit_panel.imp().view.selected_page()
             && let Ok(view) = a.child().downcast::<CircuitView>()
@@ -99,6 +117,10 @@ impl MainWindow {
     pub fn module_list(&self) -> &ModuleList {
         &self.imp().module_list
     }
+
+    pub fn properties(&self) -> &Properties {
+        &self.imp().properties
+    }
 }
 
 #[derive(gtk::CompositeTemplate, Default)]
@@ -112,10 +134,14 @@ pub struct MainWindowTemplate {
 
     #[template_child]
     pub circuit_panel: TemplateChild<CircuitPanel>,
+
+    #[template_child]
+    pub properties: TemplateChild<Properties>,
 }
 
 impl MainWindowTemplate {
     fn set_application(&self, app: Application) {
+        self.properties.set_application(app.clone());
         self.circuit_panel.set_application(app);
     }
 }
@@ -145,6 +171,9 @@ impl ObjectImpl for MainWindowTemplate {
         let circuit_panel = self.circuit_panel.get();
         let circuit_panel_template = CircuitPanelTemplate::from_instance(&circuit_panel);
 
+        let properties = self.properties.get();
+        let properties_template = PropertiesTemplate::from_instance(&properties);
+
         self.leaflet
             .set_fold_threshold_policy(adw::FoldThresholdPolicy::Minimum);
 
@@ -160,6 +189,18 @@ impl ObjectImpl for MainWindowTemplate {
             gtk::Widget::NONE,
         );
 
+        self.leaflet.property_expression("folded").bind(
+            &circuit_panel_template.header_bar.get(),
+            "show-end-title-buttons",
+            gtk::Widget::NONE,
+        );
+
+        self.leaflet.property_expression("folded").bind(
+            &properties_template.header_bar.get(),
+            "show-start-title-buttons",
+            gtk::Widget::NONE,
+        );
+
         self.leaflet.property_expression("folded").bind(
             &circuit_panel_template.back_button.get(),
             "visible",
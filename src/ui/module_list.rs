@@ -2,7 +2,7 @@ use gtk::{gdk, gio, glib, prelude::*, subclass::prelude::*, StateFlags};
 
 use crate::{
     application::{selection::*, Application},
-    renderer::vector::Vector2,
+    renderer::{decode_image, vector::Vector2},
     simulator::*,
 };
 
@@ -177,17 +177,42 @@ impl ModuleListTemplate {
         ]
     }
 
+    const ICON_SIZE: i32 = 24;
+    const BUILTIN_ICON_PATH: &'static str = "/com/spydr06/logicrs/modules";
+
+    // builtins use their picture from `assets/modules`, custom modules their image decoration
+    fn module_icon(module: &Module) -> Option<gtk::Image> {
+        let icon = if module.builtin() {
+            let path = format!(
+                "{}/{}.png",
+                Self::BUILTIN_ICON_PATH,
+                module.name().to_lowercase().replace(' ', "-")
+            );
+            gio::resources_get_info(&path, gio::ResourceLookupFlags::NONE)
+                .is_ok()
+                .then(|| gtk::Image::from_resource(&path))
+        } else if let Decoration::Image(image) = module.decoration() {
+            decode_image(image)
+                .map(|pixbuf| gtk::Image::from_paintable(Some(&gdk::Texture::for_pixbuf(&pixbuf))))
+        } else {
+            None
+        };
+
+        if let Some(icon) = &icon {
+            icon.set_pixel_size(Self::ICON_SIZE);
+        }
+        icon
+    }
+
     fn module_item_content(&self, module: &Module) -> gtk::Box {
         let b = gtk::Box::builder()
             .orientation(gtk::Orientation::Horizontal)
             .build();
 
-        b.append(
-            &gtk::Image::builder()
-                .icon_name("module-symbolic")
-                .margin_end(12)
-                .build(),
-        );
+        let icon = Self::module_icon(module)
+            .unwrap_or_else(|| gtk::Image::builder().icon_name("module-symbolic").build());
+        icon.set_margin_end(12);
+        b.append(&icon);
         b.append(
             &gtk::Label::builder()
                 .label(module.name())
@@ -222,6 +247,7 @@ impl ModuleListTemplate {
                     p.unhighlight();
                     p.set_selection(Selection::MoveBlock(Box::new(block.clone())))
                 });
+                application.imp().refresh_properties();
             }
         }));
         item.add_controller(&left_click_gesture);
@@ -280,6 +306,31 @@ impl ModuleListTemplate {
             "app.edit-module",
             &name.to_variant()
         );
+        add_menu_item!(model, "_Rename", "app.rename-module", &name.to_variant());
+        add_menu_item!(
+            model,
+            "Edit _Interface",
+            "app.edit-module-interface",
+            &name.to_variant()
+        );
+        add_menu_item!(
+            model,
+            "Edit _Appearance",
+            "app.edit-module-appearance",
+            &name.to_variant()
+        );
+        add_menu_item!(
+            model,
+            "Edit _Shape",
+            "app.edit-module-shape",
+            &name.to_variant()
+        );
+        add_menu_item!(
+            model,
+            "Edit _Pin Names",
+            "app.edit-pin-names",
+            &name.to_variant()
+        );
         add_menu_item!(model, "E_xport", "app.export-module", &name.to_variant());
         add_menu_item!(model, "_Delete", "app.delete-module", &name.to_variant());
 
@@ -1,4 +1,11 @@
+use super::dialogs::{self, ParameterInput};
+use crate::{
+    application::{action::Action, selection::SelectionField, Application},
+    renderer::{vector::Vector2, Color, IntoColor, IntoRGBA, COLOR_THEME},
+    simulator::*,
+};
 use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+use std::cell::{Cell, RefCell};
 
 glib::wrapper! {
     pub struct Properties(ObjectSubclass<PropertiesTemplate>)
@@ -6,13 +13,378 @@ glib::wrapper! {
         @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
 }
 
-impl Properties {}
+impl Properties {
+    pub fn set_application(&self, app: Application) {
+        self.imp().application.replace(app);
+    }
+
+    // re-reads the selected block of the current plot
+    pub fn refresh(&self) {
+        self.imp().refresh();
+    }
+
+    // re-reads only the simulation results of the inspected block
+    pub fn refresh_state(&self) {
+        self.imp().refresh_state();
+    }
+}
+
+// the user-editable properties of a block
+#[derive(PartialEq)]
+struct Editable {
+    id: BlockID,
+    label: Option<String>,
+    color: Option<Color>,
+    parameters: Vec<(Parameter, u128, (u128, u128))>,
+}
+
+// everything shown about a block, read while the plot is locked
+struct Inspected {
+    editable: Editable,
+    module: String,
+    position: Vector2<i32>,
+    simulated: Simulated,
+}
+
+// the parts of a block that change while the simulation is running
+struct Simulated {
+    ports: Vec<(String, String)>,
+    state: String,
+    outputs: String,
+}
+
+impl Inspected {
+    fn new(plot: &Plot, block: &Block) -> Self {
+        let decoration = block.decoration();
+        Self {
+            editable: Editable {
+                id: block.id(),
+                label: block.label().cloned(),
+                color: *block.color(),
+                parameters: decoration
+                    .parameters()
+                    .into_iter()
+                    .map(|(parameter, value)| {
+                        (parameter, value, decoration.parameter_bounds(parameter))
+                    })
+                    .collect(),
+            },
+            module: block.name().clone(),
+            position: block.position(),
+            simulated: Simulated::new(plot, block),
+        }
+    }
+}
+
+impl Simulated {
+    fn new(plot: &Plot, block: &Block) -> Self {
+        let level = |active: bool| if active { "1" } else { "0" };
+
+        let inputs = block.inputs().iter().enumerate().map(|(i, connection)| {
+            let description = match connection.and_then(|id| plot.get_connection(&id)) {
+                Some(connection) => format!(
+                    "{} from {} (output {})",
+                    level(connection.is_active()),
+                    plot.get_block(connection.origin().block_id())
+                        .map(|origin| origin.header())
+                        .unwrap_or("?"),
+                    connection.origin().index()
+                ),
+                None => "Unconnected".to_string(),
+            };
+            (format!("Input {i}"), description)
+        });
+
+        let outputs = block.outputs().iter().enumerate().map(|(i, connection)| {
+            let active = (block.output_state() >> i) & 1 != 0;
+            let description = match connection.and_then(|id| plot.get_connection(&id)) {
+                Some(connection) => format!(
+                    "{} to {} input(s)",
+                    level(active),
+                    connection.destinations().len()
+                ),
+                None => format!("{} (unconnected)", level(active)),
+            };
+            (format!("Output {i}"), description)
+        });
+
+        Self {
+            ports: inputs.chain(outputs).collect(),
+            state: match block.state() {
+                State::None => "None".to_string(),
+                State::Direct(bytes) => format!("{bytes:#x}"),
+                State::Inherit(_) => "Stored in sub-circuit".to_string(),
+            },
+            outputs: match block.outputs().len() {
+                0 => "None".to_string(),
+                n => format!("{:#0width$b}", block.output_state(), width = n + 2),
+            },
+        }
+    }
+}
 
 #[derive(gtk::CompositeTemplate, Default)]
 #[template(resource = "/content/properties.ui")]
-pub struct PropertiesTemplate {}
+pub struct PropertiesTemplate {
+    #[template_child]
+    pub header_bar: TemplateChild<adw::HeaderBar>,
+
+    #[template_child]
+    stack: TemplateChild<gtk::Stack>,
+
+    #[template_child]
+    module_row: TemplateChild<adw::ActionRow>,
+
+    #[template_child]
+    id_row: TemplateChild<adw::ActionRow>,
+
+    #[template_child]
+    position_row: TemplateChild<adw::ActionRow>,
+
+    #[template_child]
+    label_row: TemplateChild<adw::EntryRow>,
+
+    #[template_child]
+    color_button: TemplateChild<gtk::ColorButton>,
+
+    #[template_child]
+    color_reset_button: TemplateChild<gtk::Button>,
+
+    #[template_child]
+    parameter_group: TemplateChild<adw::PreferencesGroup>,
+
+    #[template_child]
+    connection_group: TemplateChild<adw::PreferencesGroup>,
+
+    #[template_child]
+    state_row: TemplateChild<adw::ActionRow>,
+
+    #[template_child]
+    output_row: TemplateChild<adw::ActionRow>,
+
+    application: RefCell<Application>,
+    inspected: RefCell<Option<(PlotProvider, Editable)>>,
+    parameter_rows: RefCell<Vec<(adw::ActionRow, ParameterInput)>>,
+    connection_rows: RefCell<Vec<adw::ActionRow>>,
+
+    // set while widgets get updated programmatically to not record their signals as actions
+    updating: Cell<bool>,
+}
+
+impl PropertiesTemplate {
+    fn refresh(&self) {
+        let selected = self
+            .application
+            .borrow()
+            .imp()
+            .current_plot()
+            .and_then(|plot_provider| {
+                plot_provider
+                    .with(|plot| match plot.selection().blocks().as_slice() {
+                        [block_id] => plot
+                            .get_block(*block_id)
+                            .map(|block| Inspected::new(plot, block)),
+                        _ => None,
+                    })
+                    .flatten()
+                    .map(|inspected| (plot_provider, inspected))
+            });
 
-impl PropertiesTemplate {}
+        let Some((plot_provider, inspected)) = selected else {
+            self.stack.set_visible_child_name("empty");
+            self.inspected.replace(None);
+            return;
+        };
+
+        self.stack.set_visible_child_name("block");
+        self.updating.set(true);
+
+        let Inspected {
+            editable,
+            module,
+            position,
+            simulated,
+        } = inspected;
+
+        self.module_row.set_subtitle(&module);
+        self.id_row.set_subtitle(&editable.id.to_string());
+        self.position_row
+            .set_subtitle(&format!("{}, {}", position.0, position.1));
+        self.update_simulated(&simulated);
+
+        // only touch the editors if the block changed, so unsaved input is kept
+        let previous = self.inspected.take();
+        match previous {
+            Some((_, previous)) if previous == editable => (),
+            previous => self.update_editors(&editable, previous.as_ref().map(|(_, e)| e)),
+        }
+
+        self.inspected.replace(Some((plot_provider, editable)));
+        self.updating.set(false);
+    }
+
+    // called on every simulation tick, the selection and the editors are only updated by `refresh`
+    fn refresh_state(&self) {
+        let simulated = self
+            .inspected
+            .borrow()
+            .as_ref()
+            .and_then(|(plot_provider, editable)| {
+                plot_provider
+                    .with(|plot| {
+                        plot.get_block(editable.id)
+                            .map(|block| Simulated::new(plot, block))
+                    })
+                    .flatten()
+            });
+
+        if let Some(simulated) = simulated {
+            self.update_simulated(&simulated);
+        }
+    }
+
+    fn update_simulated(&self, simulated: &Simulated) {
+        self.state_row.set_subtitle(&simulated.state);
+        self.output_row.set_subtitle(&simulated.outputs);
+        self.update_ports(&simulated.ports);
+    }
+
+    fn update_ports(&self, ports: &[(String, String)]) {
+        let mut rows = self.connection_rows.borrow_mut();
+        while rows.len() > ports.len() {
+            self.connection_group.remove(&rows.pop().unwrap());
+        }
+        while rows.len() < ports.len() {
+            let row = adw::ActionRow::new();
+            self.connection_group.add(&row);
+            rows.push(row);
+        }
+
+        for (row, (title, description)) in rows.iter().zip(ports) {
+            row.set_title(title);
+            row.set_subtitle(description);
+        }
+        self.connection_group.set_visible(!ports.is_empty());
+    }
+
+    fn update_editors(&self, editable: &Editable, previous: Option<&Editable>) {
+        self.label_row
+            .set_text(editable.label.as_deref().unwrap_or_default());
+        self.color_button.set_rgba(
+            &editable
+                .color
+                .unwrap_or(unsafe { COLOR_THEME.border_color })
+                .into_rgba(),
+        );
+        self.color_reset_button
+            .set_sensitive(editable.color.is_some());
+
+        let same_parameters = previous.is_some_and(|previous| {
+            previous.id == editable.id
+                && previous
+                    .parameters
+                    .iter()
+                    .map(|(parameter, ..)| parameter)
+                    .eq(editable.parameters.iter().map(|(parameter, ..)| parameter))
+        });
+
+        if same_parameters {
+            for ((_, input), (_, value, (min, max))) in self
+                .parameter_rows
+                .borrow()
+                .iter()
+                .zip(editable.parameters.iter())
+            {
+                input.set_value(*value, *min, *max);
+            }
+        } else {
+            for (row, _) in self.parameter_rows.take() {
+                self.parameter_group.remove(&row);
+            }
+
+            let rows = editable
+                .parameters
+                .iter()
+                .map(|(parameter, value, (min, max))| {
+                    let input = ParameterInput::new(*parameter, *value, *min, *max);
+                    input.widget().set_valign(gtk::Align::Center);
+
+                    let parameter = *parameter;
+                    input.connect_changed(
+                        glib::clone!(@weak self as widget => move |value| widget.parameter_changed(parameter, value)),
+                    );
+
+                    let row = adw::ActionRow::builder().title(parameter.label()).build();
+                    row.add_suffix(input.widget());
+                    self.parameter_group.add(&row);
+                    (row, input)
+                })
+                .collect();
+            self.parameter_rows.replace(rows);
+        }
+
+        self.parameter_group
+            .set_visible(!editable.parameters.is_empty());
+    }
+
+    fn selected(&self) -> Option<(PlotProvider, BlockID)> {
+        if self.updating.get() {
+            return None;
+        }
+
+        self.inspected
+            .borrow()
+            .as_ref()
+            .map(|(plot_provider, editable)| (plot_provider.clone(), editable.id))
+    }
+
+    fn parameter_changed(&self, parameter: Parameter, value: Option<u128>) {
+        let Some((plot_provider, block_id)) = self.selected() else {
+            return;
+        };
+
+        let current = self.inspected.borrow().as_ref().and_then(|(_, editable)| {
+            editable
+                .parameters
+                .iter()
+                .find(|(p, ..)| *p == parameter)
+                .map(|(_, value, _)| *value)
+        });
+        if value.is_some() && value == current {
+            return;
+        }
+
+        let app = self.application.borrow().clone();
+        match value {
+            Some(value) => app.new_action(Action::ConfigureBlock(
+                plot_provider,
+                block_id,
+                vec![(parameter, value)],
+                vec![],
+                vec![],
+            )),
+            None => {
+                if let Some(window) = app.active_window() {
+                    let message = format!("Invalid value for \"{}\"", parameter.label());
+                    dialogs::run(app, window, message, dialogs::basic_error);
+                }
+            }
+        }
+    }
+
+    fn set_color(&self, color: Color) {
+        if let Some((plot_provider, block_id)) = self.selected() {
+            self.application
+                .borrow()
+                .new_action(Action::ChangeBorderColor(
+                    plot_provider,
+                    color,
+                    vec![block_id],
+                    vec![],
+                ));
+        }
+    }
+}
 
 #[glib::object_subclass]
 impl ObjectSubclass for PropertiesTemplate {
@@ -32,6 +404,29 @@ impl ObjectSubclass for PropertiesTemplate {
 impl ObjectImpl for PropertiesTemplate {
     fn constructed(&self) {
         self.parent_constructed();
+
+        self.label_row
+            .connect_apply(glib::clone!(@weak self as widget => move |row| {
+                if let Some((plot_provider, block_id)) = widget.selected() {
+                    let label = row.text().trim().to_string();
+                    widget.application.borrow().new_action(Action::SetLabel(
+                        plot_provider,
+                        block_id,
+                        (!label.is_empty()).then_some(label),
+                        None,
+                    ));
+                }
+            }));
+
+        self.color_button
+            .connect_color_set(glib::clone!(@weak self as widget => move |button| {
+                widget.set_color(button.rgba().into_color())
+            }));
+
+        self.color_reset_button
+            .connect_clicked(glib::clone!(@weak self as widget => move |_| {
+                widget.set_color(unsafe { COLOR_THEME.border_color })
+            }));
     }
 }
 
@@ -0,0 +1,20 @@
+/* Generated by Yosys 0.33 (git sha1 2584903a060, clang 15.0.0 -fPIC -Os) */
+
+module counter(clk, en, q);
+  wire _0_;
+  wire _1_;
+  wire _2_;
+  input clk;
+  wire clk;
+  input en;
+  wire en;
+  output [1:0] q;
+  reg [1:0] q;
+  assign _0_ = q[0] ^ en;
+  assign _1_ = q[0] & en;
+  assign _2_ = q[1] ^ _1_;
+  always @(posedge clk)
+    q[0] <= _0_;
+  always @(posedge clk)
+    q[1] <= _2_;
+endmodule